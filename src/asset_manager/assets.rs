@@ -0,0 +1,113 @@
+//! Owns all assets of one type, e.g. all textures
+//!
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc;
+
+use super::Handle;
+
+/// Source of the ids that tell apart the handles of different assets
+static NEXT_OWNER_ID: AtomicU64 = AtomicU64::new(0);
+
+pub struct Assets<T> {
+    owner_id: u64,
+    slots: Vec<Option<T>>,
+    free_slots: Vec<usize>,
+    drop_sender: mpsc::Sender<usize>,
+    drop_receiver: mpsc::Receiver<usize>,
+}
+
+impl<T> Default for Assets<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Assets<T> {
+    pub fn new() -> Self {
+        let (drop_sender, drop_receiver) = mpsc::channel();
+
+        Self {
+            owner_id: NEXT_OWNER_ID.fetch_add(1, Ordering::Relaxed),
+            slots: Vec::new(),
+            free_slots: Vec::new(),
+            drop_sender,
+            drop_receiver,
+        }
+    }
+
+    pub fn insert(&mut self, asset: T) -> Handle<T> {
+        self.free_unused();
+
+        let index = match self.free_slots.pop() {
+            Some(index) => {
+                self.slots[index] = Some(asset);
+                index
+            }
+            None => {
+                self.slots.push(Some(asset));
+                self.slots.len() - 1
+            }
+        };
+
+        Handle::new(self.owner_id, index, self.drop_sender.clone())
+    }
+
+    /// Returns None if the handle was created by other assets
+    pub fn get(&self, handle: &Handle<T>) -> Option<&T> {
+        if !self.owns(handle) {
+            return None;
+        }
+        self.slots.get(handle.index())?.as_ref()
+    }
+
+    /// Returns None if the handle was created by other assets
+    pub fn get_mut(&mut self, handle: &Handle<T>) -> Option<&mut T> {
+        if !self.owns(handle) {
+            return None;
+        }
+        self.slots.get_mut(handle.index())?.as_mut()
+    }
+
+    /// Replaces the asset in place, all handles refer to the new asset afterwards
+    ///
+    /// Returns the old asset, or None if the handle was created by other assets.
+    pub fn replace(&mut self, handle: &Handle<T>, asset: T) -> Option<T> {
+        if !self.owns(handle) {
+            return None;
+        }
+        let slot = self.slots.get_mut(handle.index())?;
+        if slot.is_none() {
+            return None;
+        }
+
+        slot.replace(asset)
+    }
+
+    fn owns(&self, handle: &Handle<T>) -> bool {
+        handle.owner_id() == self.owner_id
+    }
+
+    /// Drops all assets without handles and returns how many were freed
+    pub fn free_unused(&mut self) -> usize {
+        let mut nr_freed = 0;
+
+        while let Ok(index) = self.drop_receiver.try_recv() {
+            if self.slots[index].take().is_some() {
+                self.free_slots.push(index);
+                nr_freed += 1;
+            }
+        }
+
+        nr_freed
+    }
+
+    /// Number of assets, including the ones that are not freed yet
+    pub fn len(&self) -> usize {
+        self.slots.len() - self.free_slots.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
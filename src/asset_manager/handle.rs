@@ -0,0 +1,78 @@
+//! A typed and reference counted reference to an asset
+//!
+
+use std::marker::PhantomData;
+use std::sync::mpsc;
+use std::sync::Arc;
+
+/// Tells the owning assets which slot can be freed, when the last handle is dropped
+struct HandleRef {
+    /// The id of the assets that created the handle
+    owner_id: u64,
+    index: usize,
+    drop_sender: mpsc::Sender<usize>,
+}
+
+impl Drop for HandleRef {
+    fn drop(&mut self) {
+        // the assets might have been dropped before the handle
+        let _res = self.drop_sender.send(self.index);
+    }
+}
+
+pub struct Handle<T> {
+    handle_ref: Arc<HandleRef>,
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<T> Handle<T> {
+    pub(super) fn new(owner_id: u64, index: usize, drop_sender: mpsc::Sender<usize>) -> Self {
+        Self {
+            handle_ref: Arc::new(HandleRef {
+                owner_id,
+                index,
+                drop_sender,
+            }),
+            marker: PhantomData,
+        }
+    }
+
+    pub(super) fn owner_id(&self) -> u64 {
+        self.handle_ref.owner_id
+    }
+
+    pub(super) fn index(&self) -> usize {
+        self.handle_ref.index
+    }
+
+    /// Number of handles referencing the same asset, including this one
+    pub fn ref_count(&self) -> usize {
+        Arc::strong_count(&self.handle_ref)
+    }
+}
+
+impl<T> Clone for Handle<T> {
+    fn clone(&self) -> Self {
+        Self {
+            handle_ref: self.handle_ref.clone(),
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<T> PartialEq for Handle<T> {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.handle_ref, &other.handle_ref)
+    }
+}
+
+impl<T> Eq for Handle<T> {}
+
+impl<T> std::fmt::Debug for Handle<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Handle")
+            .field("index", &self.index())
+            .field("ref_count", &self.ref_count())
+            .finish()
+    }
+}
@@ -0,0 +1,14 @@
+//! Stores GPU assets like textures, meshes and pipelines behind reference counted handles
+//!
+//! An asset stays alive as long as a handle to it exists. Its slot is freed, and with it
+//! the GPU memory, by the next call to `free_unused` or `insert` after the last handle was dropped.
+//!
+
+mod assets;
+mod handle;
+
+#[cfg(test)]
+mod tests;
+
+pub use assets::Assets;
+pub use handle::Handle;
@@ -0,0 +1,64 @@
+//! Unit tests
+
+use super::*;
+
+#[test]
+fn handles_count_references() {
+    let mut assets = Assets::new();
+    let handle = assets.insert(String::from("texture"));
+    assert_eq!(handle.ref_count(), 1);
+
+    let other = handle.clone();
+    assert_eq!(handle.ref_count(), 2);
+    assert_eq!(handle, other);
+
+    drop(other);
+    assert_eq!(handle.ref_count(), 1);
+    assert_eq!(assets.free_unused(), 0);
+    assert_eq!(assets.get(&handle).unwrap(), "texture");
+}
+
+#[test]
+fn replace_in_place() {
+    let mut assets = Assets::new();
+    let handle = assets.insert(String::from("old"));
+    let other = handle.clone();
+
+    assert_eq!(assets.replace(&handle, String::from("new")).unwrap(), "old");
+    assert_eq!(assets.get(&other).unwrap(), "new");
+}
+
+#[test]
+fn free_when_last_handle_is_dropped() {
+    let mut assets = Assets::new();
+    let handle = assets.insert(String::from("a"));
+    let other = handle.clone();
+    let kept = assets.insert(String::from("b"));
+    assert_eq!(assets.len(), 2);
+
+    drop(handle);
+    assert_eq!(assets.free_unused(), 0);
+    drop(other);
+    assert_eq!(assets.free_unused(), 1);
+    assert_eq!(assets.len(), 1);
+
+    // the freed slot is reused
+    let reused = assets.insert(String::from("c"));
+    assert_eq!(assets.len(), 2);
+    assert_eq!(assets.get(&reused).unwrap(), "c");
+    assert_eq!(assets.get(&kept).unwrap(), "b");
+}
+
+#[test]
+fn handles_of_other_assets_are_rejected() {
+    let mut assets = Assets::new();
+    let mut other_assets = Assets::new();
+    let handle = assets.insert(String::from("texture"));
+    let other_handle = other_assets.insert(String::from("other"));
+
+    assert_eq!(assets.get(&other_handle), None);
+    assert_eq!(assets.get_mut(&other_handle), None);
+    assert_eq!(assets.replace(&other_handle, String::from("new")), None);
+    assert_eq!(assets.get(&handle).unwrap(), "texture");
+    assert_eq!(other_assets.get(&other_handle).unwrap(), "other");
+}
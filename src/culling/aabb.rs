@@ -0,0 +1,73 @@
+//! An axis aligned bounding box
+//!
+
+use cgmath::*;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Aabb {
+    pub min: Point3<f32>,
+    pub max: Point3<f32>,
+}
+
+impl Aabb {
+    pub fn new(min: Point3<f32>, max: Point3<f32>) -> Self {
+        Self { min, max }
+    }
+
+    /// The smallest box containing all points, None if there are no points
+    pub fn from_points<I>(points: I) -> Option<Self>
+    where
+        I: IntoIterator<Item = Point3<f32>>,
+    {
+        let mut points = points.into_iter();
+        let first = points.next()?;
+
+        Some(points.fold(Self::new(first, first), |aabb, point| Self {
+            min: Point3::new(
+                aabb.min.x.min(point.x),
+                aabb.min.y.min(point.y),
+                aabb.min.z.min(point.z),
+            ),
+            max: Point3::new(
+                aabb.max.x.max(point.x),
+                aabb.max.y.max(point.y),
+                aabb.max.z.max(point.z),
+            ),
+        }))
+    }
+
+    pub fn center(&self) -> Point3<f32> {
+        self.min.midpoint(self.max)
+    }
+
+    /// Half of the size along every axis
+    pub fn half_extents(&self) -> Vector3<f32> {
+        (self.max - self.min) * 0.5
+    }
+
+    /// The box containing all corners of this box after the transformation
+    pub fn transform(&self, matrix: &Matrix4<f32>) -> Self {
+        let corners = (0..8).map(|index| {
+            let corner = Point3::new(
+                if index & 1 == 0 {
+                    self.min.x
+                } else {
+                    self.max.x
+                },
+                if index & 2 == 0 {
+                    self.min.y
+                } else {
+                    self.max.y
+                },
+                if index & 4 == 0 {
+                    self.min.z
+                } else {
+                    self.max.z
+                },
+            );
+            matrix.transform_point(corner)
+        });
+
+        Self::from_points(corners).unwrap_or(*self)
+    }
+}
@@ -0,0 +1,47 @@
+//! A sphere containing all vertices of a mesh
+//!
+
+use cgmath::*;
+
+use super::Aabb;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct BoundingSphere {
+    pub center: Point3<f32>,
+    pub radius: f32,
+}
+
+impl BoundingSphere {
+    pub fn new(center: Point3<f32>, radius: f32) -> Self {
+        Self { center, radius }
+    }
+
+    /// A sphere around the center of the bounding box, not the smallest one but close to it
+    pub fn from_points<I>(points: I) -> Option<Self>
+    where
+        I: IntoIterator<Item = Point3<f32>>,
+        I::IntoIter: Clone,
+    {
+        let points = points.into_iter();
+        let center = Aabb::from_points(points.clone())?.center();
+        let radius = points
+            .map(|point| point.distance(center))
+            .fold(0.0, f32::max);
+
+        Some(Self { center, radius })
+    }
+
+    /// The sphere containing this one after the transformation, e.g. the model matrix
+    /// of an instance
+    pub fn transform(&self, matrix: &Matrix4<f32>) -> Self {
+        let scale = [matrix.x, matrix.y, matrix.z]
+            .iter()
+            .map(|axis| axis.truncate().magnitude())
+            .fold(0.0, f32::max);
+
+        Self {
+            center: matrix.transform_point(self.center),
+            radius: self.radius * scale,
+        }
+    }
+}
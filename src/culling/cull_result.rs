@@ -0,0 +1,43 @@
+//! Culls the instances of a mesh
+//!
+
+use super::super::vertex_color_shader::Instance;
+use super::{BoundingSphere, Frustum};
+
+/// How many instances were tested and how many of them are visible
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct CullResult {
+    pub total: u32,
+    pub visible: u32,
+}
+
+impl CullResult {
+    pub fn culled(&self) -> u32 {
+        self.total - self.visible
+    }
+}
+
+/// Writes the indices of the instances whose bounding sphere intersects the frustum
+/// into `visible`
+pub fn cull_instances(
+    frustum: &Frustum,
+    bounding_sphere: &BoundingSphere,
+    instances: &[Instance],
+    visible: &mut Vec<u32>,
+) -> CullResult {
+    visible.clear();
+    visible.extend(
+        instances
+            .iter()
+            .enumerate()
+            .filter(|(_, instance)| {
+                frustum.intersects_sphere(&bounding_sphere.transform(&instance.model_matrix()))
+            })
+            .map(|(index, _)| index as u32),
+    );
+
+    CullResult {
+        total: instances.len() as u32,
+        visible: visible.len() as u32,
+    }
+}
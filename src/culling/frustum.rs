@@ -0,0 +1,72 @@
+//! The six planes bounding the view of a camera
+//!
+
+use cgmath::*;
+
+use super::super::wgpu_renderer::camera::{Camera, Projection};
+use super::{Aabb, BoundingSphere};
+
+#[derive(Copy, Clone, Debug)]
+pub struct Frustum {
+    /// Normals point inside, a point p is inside of a plane if dot(normal, p) + w >= 0
+    planes: [Vector4<f32>; 6],
+}
+
+impl Frustum {
+    pub fn new(camera: &Camera, projection: &Projection) -> Self {
+        Self::from_view_proj(&(projection.calc_matrix() * camera.calc_matrix()))
+    }
+
+    /// Extracts the planes from a view projection matrix with a depth range of 0 to 1
+    pub fn from_view_proj(view_proj: &Matrix4<f32>) -> Self {
+        let row = |index: usize| view_proj.row(index);
+
+        let planes = [
+            row(3) + row(0), // left
+            row(3) - row(0), // right
+            row(3) + row(1), // bottom
+            row(3) - row(1), // top
+            row(2),          // near
+            row(3) - row(2), // far
+        ]
+        .map(|plane| plane / plane.truncate().magnitude());
+
+        Self { planes }
+    }
+
+    /// Left, right, bottom, top, near and far, e.g. to upload them for culling on the GPU
+    pub fn planes(&self) -> &[Vector4<f32>; 6] {
+        &self.planes
+    }
+
+    fn distance(plane: &Vector4<f32>, point: Point3<f32>) -> f32 {
+        plane.truncate().dot(point.to_vec()) + plane.w
+    }
+
+    pub fn contains_point(&self, point: Point3<f32>) -> bool {
+        self.planes
+            .iter()
+            .all(|plane| Self::distance(plane, point) >= 0.0)
+    }
+
+    /// True if the sphere is at least partly inside
+    pub fn intersects_sphere(&self, sphere: &BoundingSphere) -> bool {
+        self.planes
+            .iter()
+            .all(|plane| Self::distance(plane, sphere.center) >= -sphere.radius)
+    }
+
+    /// True if the box is at least partly inside, or close to a corner of the frustum
+    pub fn intersects_aabb(&self, aabb: &Aabb) -> bool {
+        let center = aabb.center();
+        let half_extents = aabb.half_extents();
+
+        self.planes.iter().all(|plane| {
+            let normal = plane.truncate();
+            let radius = half_extents.x * normal.x.abs()
+                + half_extents.y * normal.y.abs()
+                + half_extents.z * normal.z.abs();
+            Self::distance(plane, center) >= -radius
+        })
+    }
+}
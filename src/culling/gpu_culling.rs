@@ -0,0 +1,474 @@
+//! Frustum culling of large instance counts, with a compute pass and indirect drawing
+//!
+//! The instances are uploaded once. Every frame `cull` records a compute pass that tests
+//! them against the frustum and compacts the visible ones into the buffer that is drawn,
+//! the instance count of the indirect draw is written by the GPU. Adapters without compute
+//! shaders or indirect execution, e.g. WebGL, cull on the CPU instead and draw with a fixed
+//! instance range.
+//!
+//! The compute pass appends the visible instances in no fixed order. The index of each drawn
+//! instance in `instances()` is written to `instance_index_buffer`, e.g. to find the instance
+//! drawn at a position in the buffer. On the CPU `instance_index` returns it directly.
+//!
+
+use cgmath::*;
+use wgpu::util::DeviceExt;
+
+use super::super::vertex_color_shader::{Instance, InstanceRaw};
+use super::super::wgpu_renderer::GpuContext;
+use super::{cull_instances, BoundingSphere, CullResult, Frustum};
+
+const WORKGROUP_SIZE: u32 = 64;
+
+/// Where the instances are culled
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum CullingMode {
+    /// A compute pass and `draw_indexed_indirect`
+    Compute,
+    /// On the CPU, for adapters without compute shaders or indirect drawing
+    Cpu,
+}
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+struct CullingUniform {
+    planes: [[f32; 4]; 6],
+    bounding_sphere: [f32; 4],
+    counts: [u32; 4],
+}
+
+/// The resources of the compute pass, only created in `CullingMode::Compute`
+struct ComputeCulling {
+    pipeline: wgpu::ComputePipeline,
+    bind_group: wgpu::BindGroup,
+    uniform_buffer: wgpu::Buffer,
+    instance_buffer: wgpu::Buffer,
+    indirect_buffer: wgpu::Buffer,
+    /// The index in the instances of each visible instance
+    instance_index_buffer: wgpu::Buffer,
+}
+
+/// The byte offset of `instance_count` in `wgpu::util::DrawIndexedIndirectArgs`
+const INSTANCE_COUNT_OFFSET: u64 = 4;
+
+/// The number of storage buffers the compute pass binds
+const STORAGE_BUFFERS: u32 = 4;
+
+pub struct GpuCulling {
+    mode: CullingMode,
+    index_count: u32,
+    bounding_sphere: BoundingSphere,
+    instances: Vec<Instance>,
+    /// The visible instances, drawn as the instance buffer of the mesh
+    visible_buffer: wgpu::Buffer,
+    /// The number of visible instances after culling on the CPU
+    visible_count: u32,
+    visible_indices: Vec<u32>,
+    compute: Option<ComputeCulling>,
+}
+
+impl GpuCulling {
+    /// The adapter needs compute shaders and indirect execution, the device storage buffers
+    ///
+    /// Downlevel adapters like WebGL have neither.
+    pub fn is_supported(context: &GpuContext) -> bool {
+        let flags = context.adapter().get_downlevel_capabilities().flags;
+        flags.contains(
+            wgpu::DownlevelFlags::COMPUTE_SHADERS | wgpu::DownlevelFlags::INDIRECT_EXECUTION,
+        ) && context
+            .device()
+            .limits()
+            .max_storage_buffers_per_shader_stage
+            >= STORAGE_BUFFERS
+    }
+
+    /// Culls on the GPU if the adapter supports it, otherwise on the CPU
+    ///
+    /// `index_count` and `bounding_sphere` are the ones of the mesh that is drawn.
+    /// Nothing is drawn before the first `cull`.
+    pub fn new(
+        context: &GpuContext,
+        index_count: u32,
+        bounding_sphere: BoundingSphere,
+        instances: &[Instance],
+    ) -> Self {
+        Self::new_with_mode(
+            context,
+            CullingMode::Compute,
+            index_count,
+            bounding_sphere,
+            instances,
+        )
+    }
+
+    /// `CullingMode::Compute` falls back to the CPU if the adapter does not support it
+    pub fn new_with_mode(
+        context: &GpuContext,
+        mode: CullingMode,
+        index_count: u32,
+        bounding_sphere: BoundingSphere,
+        instances: &[Instance],
+    ) -> Self {
+        let mode = match mode {
+            CullingMode::Compute if !Self::is_supported(context) => {
+                log::info!("Compute culling not supported, instances are culled on the CPU");
+                CullingMode::Cpu
+            }
+            mode => mode,
+        };
+
+        Self::create(
+            context.device(),
+            mode,
+            index_count,
+            bounding_sphere,
+            instances,
+        )
+    }
+
+    fn create(
+        device: &wgpu::Device,
+        mode: CullingMode,
+        index_count: u32,
+        bounding_sphere: BoundingSphere,
+        instances: &[Instance],
+    ) -> Self {
+        let visible_buffer = Self::create_visible_buffer(device, mode, instances.len());
+        let compute = (mode == CullingMode::Compute)
+            .then(|| ComputeCulling::new(device, index_count, instances, &visible_buffer));
+
+        Self {
+            mode,
+            index_count,
+            bounding_sphere,
+            instances: instances.to_vec(),
+            visible_buffer,
+            visible_count: 0,
+            visible_indices: Vec::new(),
+            compute,
+        }
+    }
+
+    fn create_visible_buffer(
+        device: &wgpu::Device,
+        mode: CullingMode,
+        instance_count: usize,
+    ) -> wgpu::Buffer {
+        let usage = match mode {
+            CullingMode::Compute => wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::STORAGE,
+            CullingMode::Cpu => wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+        };
+
+        // Bindings can not be empty
+        device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Visible Instance Buffer"),
+            size: (instance_count.max(1) * std::mem::size_of::<InstanceRaw>()) as u64,
+            usage,
+            mapped_at_creation: false,
+        })
+    }
+
+    pub fn mode(&self) -> CullingMode {
+        self.mode
+    }
+
+    pub fn instances(&self) -> &[Instance] {
+        &self.instances
+    }
+
+    pub fn set_bounding_sphere(&mut self, bounding_sphere: BoundingSphere) {
+        self.bounding_sphere = bounding_sphere;
+    }
+
+    /// Replaces the instances, they are drawn after the next `cull`
+    ///
+    /// The buffers are recreated if there are more instances than before.
+    pub fn update_instances(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        instances: &[Instance],
+    ) {
+        let capacity = self.visible_buffer.size() as usize / std::mem::size_of::<InstanceRaw>();
+        if instances.len() > capacity {
+            *self = Self::create(
+                device,
+                self.mode,
+                self.index_count,
+                self.bounding_sphere,
+                instances,
+            );
+            return;
+        }
+
+        if let Some(compute) = &self.compute {
+            let instance_data = instances.iter().map(Instance::to_raw).collect::<Vec<_>>();
+            queue.write_buffer(
+                &compute.instance_buffer,
+                0,
+                bytemuck::cast_slice(&instance_data),
+            );
+        }
+        self.instances.clear();
+        self.instances.extend_from_slice(instances);
+        self.visible_count = 0;
+        self.visible_indices.clear();
+    }
+
+    /// Records the culling into the encoder, before the pass that draws the mesh
+    ///
+    /// Call it at most once per submit, the frustum is written by the queue.
+    /// The visible count is only known on the CPU fallback, the compute pass returns None.
+    pub fn cull(
+        &mut self,
+        encoder: &mut wgpu::CommandEncoder,
+        queue: &wgpu::Queue,
+        frustum: &Frustum,
+    ) -> Option<CullResult> {
+        match &self.compute {
+            Some(compute) => {
+                compute.record(
+                    encoder,
+                    queue,
+                    frustum,
+                    &self.bounding_sphere,
+                    self.instances.len() as u32,
+                );
+                None
+            }
+            None => {
+                let result = cull_instances(
+                    frustum,
+                    &self.bounding_sphere,
+                    &self.instances,
+                    &mut self.visible_indices,
+                );
+                let instance_data = self
+                    .visible_indices
+                    .iter()
+                    .map(|index| self.instances[*index as usize].to_raw())
+                    .collect::<Vec<_>>();
+                queue.write_buffer(
+                    &self.visible_buffer,
+                    0,
+                    bytemuck::cast_slice(&instance_data),
+                );
+                self.visible_count = result.visible;
+                Some(result)
+            }
+        }
+    }
+
+    /// The index in `instances()` of each drawn instance, in the order they are drawn
+    ///
+    /// Written by the compute pass, e.g. to be read back for picking. None on the CPU,
+    /// use `instance_index` then.
+    pub fn instance_index_buffer(&self) -> Option<&wgpu::Buffer> {
+        self.compute
+            .as_ref()
+            .map(|compute| &compute.instance_index_buffer)
+    }
+
+    /// The index in `instances()` of a drawn instance, after culling on the CPU
+    ///
+    /// The compute pass draws in no fixed order, use `instance_index_buffer` then.
+    pub fn instance_index(&self, buffer_index: u32) -> Option<usize> {
+        if self.compute.is_some() {
+            return None;
+        }
+        self.visible_indices
+            .get(buffer_index as usize)
+            .map(|index| *index as usize)
+    }
+
+    /// Binds the visible instances and draws them, after the buffers of the mesh are bound
+    pub fn draw_indexed<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>, slot: u32) {
+        render_pass.set_vertex_buffer(slot, self.visible_buffer.slice(..));
+
+        match &self.compute {
+            Some(compute) => render_pass.draw_indexed_indirect(&compute.indirect_buffer, 0),
+            None => render_pass.draw_indexed(0..self.index_count, 0, 0..self.visible_count),
+        }
+    }
+}
+
+impl ComputeCulling {
+    fn new(
+        device: &wgpu::Device,
+        index_count: u32,
+        instances: &[Instance],
+        visible_buffer: &wgpu::Buffer,
+    ) -> Self {
+        let instance_data = instances.iter().map(Instance::to_raw).collect::<Vec<_>>();
+        let instance_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Culling Instance Buffer"),
+            size: visible_buffer.size(),
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: true,
+        });
+        {
+            let bytes: &[u8] = bytemuck::cast_slice(&instance_data);
+            instance_buffer.slice(..).get_mapped_range_mut()[..bytes.len()].copy_from_slice(bytes);
+        }
+        instance_buffer.unmap();
+
+        let instance_index_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Visible Instance Index Buffer"),
+            size: (instances.len().max(1) * std::mem::size_of::<u32>()) as u64,
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC,
+            mapped_at_creation: false,
+        });
+
+        let indirect_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Culling Indirect Buffer"),
+            contents: Self::draw_args(index_count).as_bytes(),
+            usage: wgpu::BufferUsages::INDIRECT
+                | wgpu::BufferUsages::STORAGE
+                | wgpu::BufferUsages::COPY_DST,
+        });
+
+        let uniform_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Culling Uniform Buffer"),
+            size: std::mem::size_of::<CullingUniform>() as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let storage_entry = |binding, read_only| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::COMPUTE,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Storage { read_only },
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        };
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                storage_entry(1, true),
+                storage_entry(2, false),
+                storage_entry(3, false),
+                storage_entry(4, false),
+            ],
+            label: Some("culling_bind_group_layout"),
+        });
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: uniform_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: instance_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: visible_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 3,
+                    resource: indirect_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 4,
+                    resource: instance_index_buffer.as_entire_binding(),
+                },
+            ],
+            label: Some("culling_bind_group"),
+        });
+
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Culling Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("gpu_culling.wgsl").into()),
+        });
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Culling Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            immediate_size: 0,
+        });
+        let instance_words = (std::mem::size_of::<InstanceRaw>() / 4) as f64;
+        let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("Culling Pipeline"),
+            layout: Some(&pipeline_layout),
+            module: &shader,
+            entry_point: Some("cs_main"),
+            compilation_options: wgpu::PipelineCompilationOptions {
+                constants: &[("instance_words", instance_words)],
+                ..Default::default()
+            },
+            cache: None,
+        });
+
+        Self {
+            pipeline,
+            bind_group,
+            uniform_buffer,
+            instance_buffer,
+            indirect_buffer,
+            instance_index_buffer,
+        }
+    }
+
+    /// No instances yet, the compute pass counts the visible ones
+    fn draw_args(index_count: u32) -> wgpu::util::DrawIndexedIndirectArgs {
+        wgpu::util::DrawIndexedIndirectArgs {
+            index_count,
+            instance_count: 0,
+            first_index: 0,
+            base_vertex: 0,
+            first_instance: 0,
+        }
+    }
+
+    fn record(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        queue: &wgpu::Queue,
+        frustum: &Frustum,
+        bounding_sphere: &BoundingSphere,
+        instance_count: u32,
+    ) {
+        let culling_uniform = CullingUniform {
+            planes: frustum.planes().map(Into::into),
+            bounding_sphere: bounding_sphere
+                .center
+                .to_vec()
+                .extend(bounding_sphere.radius)
+                .into(),
+            counts: [instance_count, 0, 0, 0],
+        };
+        queue.write_buffer(
+            &self.uniform_buffer,
+            0,
+            bytemuck::cast_slice(&[culling_uniform]),
+        );
+        // Only the instance count of the indirect arguments is counted up by the compute pass
+        encoder.clear_buffer(
+            &self.indirect_buffer,
+            INSTANCE_COUNT_OFFSET,
+            Some(std::mem::size_of::<u32>() as u64),
+        );
+
+        let mut compute_pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+            label: Some("Culling Compute Pass"),
+            timestamp_writes: None,
+        });
+        compute_pass.set_pipeline(&self.pipeline);
+        compute_pass.set_bind_group(0, &self.bind_group, &[]);
+        compute_pass.dispatch_workgroups(instance_count.div_ceil(WORKGROUP_SIZE), 1, 1);
+    }
+}
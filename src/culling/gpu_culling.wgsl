@@ -0,0 +1,83 @@
+// Tests every instance against the frustum and appends the visible ones to the output
+// The order is not fixed, the index of each appended instance is written as well
+
+// Matches culling::gpu_culling::CullingUniform
+struct CullingUniform {
+    // Normals point inside, a point p is inside of a plane if dot(normal, p) + w >= 0
+    planes: array<vec4<f32>, 6>,
+    // Center and radius of the bounding sphere of the mesh
+    bounding_sphere: vec4<f32>,
+    // Number of instances in x
+    counts: vec4<u32>,
+};
+
+// Matches wgpu::util::DrawIndexedIndirectArgs
+struct DrawIndexedIndirectArgs {
+    index_count: u32,
+    instance_count: atomic<u32>,
+    first_index: u32,
+    base_vertex: i32,
+    first_instance: u32,
+};
+
+// The words of a vertex_color_shader::InstanceRaw, set by the pipeline
+override instance_words: u32;
+
+@group(0) @binding(0)
+var<uniform> culling: CullingUniform;
+@group(0) @binding(1)
+var<storage, read> instances: array<u32>;
+@group(0) @binding(2)
+var<storage, read_write> visible_instances: array<u32>;
+@group(0) @binding(3)
+var<storage, read_write> draw_args: DrawIndexedIndirectArgs;
+@group(0) @binding(4)
+var<storage, read_write> instance_indices: array<u32>;
+
+// The model matrix is stored first, column by column
+fn model_column(base: u32, column: u32) -> vec4<f32> {
+    let offset = base + column * 4u;
+    return vec4<f32>(
+        bitcast<f32>(instances[offset]),
+        bitcast<f32>(instances[offset + 1u]),
+        bitcast<f32>(instances[offset + 2u]),
+        bitcast<f32>(instances[offset + 3u]),
+    );
+}
+
+@compute @workgroup_size(64)
+fn cs_main(@builtin(global_invocation_id) global_id: vec3<u32>) {
+    let index = global_id.x;
+    if (index >= culling.counts.x) {
+        return;
+    }
+
+    let base = index * instance_words;
+    let model_matrix = mat4x4<f32>(
+        model_column(base, 0u),
+        model_column(base, 1u),
+        model_column(base, 2u),
+        model_column(base, 3u),
+    );
+
+    let center = (model_matrix * vec4<f32>(culling.bounding_sphere.xyz, 1.0)).xyz;
+    let scale = max(
+        length(model_matrix[0].xyz),
+        max(length(model_matrix[1].xyz), length(model_matrix[2].xyz)),
+    );
+    let radius = culling.bounding_sphere.w * scale;
+
+    for (var i = 0u; i < 6u; i++) {
+        let plane = culling.planes[i];
+        if (dot(plane.xyz, center) + plane.w < -radius) {
+            return;
+        }
+    }
+
+    let slot = atomicAdd(&draw_args.instance_count, 1u);
+    instance_indices[slot] = index;
+    let target_base = slot * instance_words;
+    for (var word = 0u; word < instance_words; word++) {
+        visible_instances[target_base + word] = instances[base + word];
+    }
+}
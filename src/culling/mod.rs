@@ -0,0 +1,29 @@
+//! Frustum culling on the CPU, skips instances that are outside of the view of the camera
+//!
+//! Meshes store a `BoundingSphere` around their vertices. Before drawing, `Mesh::cull`
+//! tests every instance against the `Frustum` of the camera and writes only the visible
+//! ones into the instance buffer. The returned `CullResult` can be collected with
+//! `performance_monitor::CullingStats`.
+//!
+//! For very large instance counts, `GpuCulling` culls in a compute pass and draws the
+//! visible instances with an indirect draw. It falls back to the CPU where compute shaders
+//! are not available.
+//!
+//! Only `vertex_color_shader::Mesh` keeps its instances and bounding sphere for culling.
+//! The meshes of the texture and heightmap shaders always draw all of their instances.
+//!
+
+mod aabb;
+mod bounding_sphere;
+mod cull_result;
+mod frustum;
+mod gpu_culling;
+
+#[cfg(test)]
+mod tests;
+
+pub use aabb::Aabb;
+pub use bounding_sphere::BoundingSphere;
+pub use cull_result::{cull_instances, CullResult};
+pub use frustum::Frustum;
+pub use gpu_culling::{CullingMode, GpuCulling};
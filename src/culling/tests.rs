@@ -0,0 +1,254 @@
+//! Unit tests
+
+use cgmath::*;
+
+use super::super::golden_image::test_support::{
+    assert_golden, finish, perspective_camera, CLEAR_COLOR, HEIGHT, WIDTH,
+};
+use super::super::golden_image::{is_gpu_required, test_renderer};
+use super::super::shape::{self, MeshDataInterface};
+use super::super::vertex_color_shader::{self, Instance};
+use super::super::wgpu_renderer::camera::{Camera, Projection};
+use super::super::wgpu_renderer::{FramePass, OffscreenRenderer, WgpuRendererInterface};
+use super::*;
+
+fn frustum() -> Frustum {
+    // At the origin, looking along the x axis
+    let camera = Camera::new((0.0, 0.0, 0.0), Deg(0.0), Deg(0.0));
+    let projection = Projection::new(800, 600, Deg(45.0), 0.1, 100.0);
+    Frustum::new(&camera, &projection)
+}
+
+#[test]
+fn frustum_contains_what_the_camera_sees() {
+    let frustum = frustum();
+
+    assert!(frustum.contains_point(Point3::new(10.0, 0.0, 0.0)));
+    assert!(!frustum.contains_point(Point3::new(-10.0, 0.0, 0.0)));
+    assert!(!frustum.contains_point(Point3::new(0.05, 0.0, 0.0)));
+    assert!(!frustum.contains_point(Point3::new(10.0, 10.0, 0.0)));
+
+    // Partly inside of the left plane, the camera looks along x with y to the left
+    let sphere = BoundingSphere::new(Point3::new(10.0, 6.0, 0.0), 2.0);
+    assert!(frustum.intersects_sphere(&sphere));
+    let sphere = BoundingSphere::new(Point3::new(10.0, 12.0, 0.0), 2.0);
+    assert!(!frustum.intersects_sphere(&sphere));
+
+    let aabb = Aabb::new(Point3::new(-5.0, -1.0, -1.0), Point3::new(0.5, 1.0, 1.0));
+    assert!(frustum.intersects_aabb(&aabb));
+    let aabb = Aabb::new(Point3::new(-5.0, -1.0, -1.0), Point3::new(-1.0, 1.0, 1.0));
+    assert!(!frustum.intersects_aabb(&aabb));
+}
+
+#[test]
+fn culls_instances_outside() {
+    let frustum = frustum();
+    let bounding_sphere =
+        BoundingSphere::from_points([Point3::new(-1.0, -1.0, -1.0), Point3::new(1.0, 1.0, 1.0)])
+            .unwrap();
+    assert_eq!(bounding_sphere.center, Point3::new(0.0, 0.0, 0.0));
+
+    let instances = [
+        Instance::new(Vector3::new(10.0, 0.0, 0.0), Quaternion::one()),
+        Instance::new(Vector3::new(-10.0, 0.0, 0.0), Quaternion::one()),
+        Instance::new(Vector3::new(10.0, 12.0, 0.0), Quaternion::one()),
+        // Only the scaled sphere reaches into the frustum
+        Instance::new(Vector3::new(10.0, 12.0, 0.0), Quaternion::one())
+            .with_scale(Vector3::new(1.0, 3.0, 1.0)),
+    ];
+
+    let mut visible = Vec::new();
+    let result = cull_instances(&frustum, &bounding_sphere, &instances, &mut visible);
+
+    assert_eq!(
+        result,
+        CullResult {
+            total: 4,
+            visible: 2
+        }
+    );
+    assert_eq!(result.culled(), 2);
+    assert_eq!(visible, [0, 3]);
+}
+
+#[test]
+fn mesh_grows_instance_buffer() {
+    let Some(mut renderer) = test_renderer(16, 16) else {
+        return;
+    };
+    let device = renderer.device().clone();
+    let queue = renderer.queue().clone();
+
+    let vertices = [
+        vertex_color_shader::Vertex {
+            position: [-1.0, -1.0, 0.0],
+        },
+        vertex_color_shader::Vertex {
+            position: [1.0, 1.0, 0.0],
+        },
+        vertex_color_shader::Vertex {
+            position: [1.0, -1.0, 0.0],
+        },
+    ];
+    let colors = [vertex_color_shader::Color::white(); 3];
+    let instance = |x: f32| Instance::new(Vector3::new(x, 0.0, 0.0), Quaternion::one());
+    let mut mesh =
+        vertex_color_shader::Mesh::new(&device, &vertices, &colors, &[0, 1, 2], &[instance(10.0)]);
+
+    let instances = [instance(10.0), instance(-10.0), instance(20.0)];
+    mesh.update_instance_buffer_resize(&device, &queue, &instances);
+    let result = mesh.cull(&queue, &frustum());
+
+    assert_eq!(
+        result,
+        CullResult {
+            total: 3,
+            visible: 2
+        }
+    );
+    assert_eq!(mesh.instance_index(1), Some(2));
+    assert_eq!(mesh.instance_index(2), None);
+}
+
+#[test]
+fn gpu_culling_writes_instance_indices() {
+    let Some(renderer) = test_renderer(16, 16) else {
+        return;
+    };
+    let context = renderer.context().clone();
+    let device = context.device();
+    let queue = context.queue();
+
+    // every second instance is behind the camera
+    let instances: Vec<_> = (0..64)
+        .map(|index| {
+            let x = if index % 2 == 0 { -10.0 } else { 10.0 };
+            Instance::new(Vector3::new(x, 0.0, 0.0), Quaternion::one())
+        })
+        .collect();
+    let bounding_sphere = BoundingSphere {
+        center: Point3::new(0.0, 0.0, 0.0),
+        radius: 1.0,
+    };
+    let mut expected = Vec::new();
+    let result = cull_instances(&frustum(), &bounding_sphere, &instances, &mut expected);
+    assert_eq!(result.visible, 32);
+
+    let mut gpu_culling = GpuCulling::new_with_mode(
+        &context,
+        CullingMode::Compute,
+        3,
+        bounding_sphere,
+        &instances,
+    );
+    let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+    let cpu_result = gpu_culling.cull(&mut encoder, queue, &frustum());
+
+    let Some(instance_index_buffer) = gpu_culling.instance_index_buffer() else {
+        assert!(
+            !is_gpu_required(),
+            "Compute culling is not supported by the adapter"
+        );
+        assert_eq!(cpu_result, Some(result));
+        let indices: Vec<_> = (0..result.visible)
+            .map(|buffer_index| gpu_culling.instance_index(buffer_index).unwrap() as u32)
+            .collect();
+        assert_eq!(indices, expected);
+        return;
+    };
+    assert_eq!(cpu_result, None);
+
+    let size = result.visible as u64 * 4;
+    let readback_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+        label: None,
+        size,
+        usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    });
+    encoder.copy_buffer_to_buffer(instance_index_buffer, 0, &readback_buffer, 0, size);
+    queue.submit([encoder.finish()]);
+
+    readback_buffer.map_async(wgpu::MapMode::Read, .., |result| result.unwrap());
+    device.poll(wgpu::PollType::wait_indefinitely()).unwrap();
+    let mut indices: Vec<u32> =
+        bytemuck::cast_slice(&readback_buffer.slice(..).get_mapped_range()).to_vec();
+    indices.sort_unstable();
+
+    assert_eq!(indices, expected);
+}
+
+#[test]
+fn gpu_culling_compute_and_cpu() {
+    let Some(mut renderer) = test_renderer(WIDTH, HEIGHT) else {
+        return;
+    };
+
+    let compute = render_gpu_culling(&mut renderer, CullingMode::Compute);
+    assert_golden("gpu_culling_instances", &compute);
+
+    let cpu = render_gpu_culling(&mut renderer, CullingMode::Cpu);
+    assert_golden("gpu_culling_instances", &cpu);
+}
+
+fn render_gpu_culling(renderer: &mut OffscreenRenderer, mode: CullingMode) -> image::RgbaImage {
+    let surface_format = renderer.surface_format();
+    let sample_count = renderer.sample_count();
+
+    let camera_bind_group_layout =
+        vertex_color_shader::CameraBindGroupLayout::new(renderer.device());
+    let pipeline = vertex_color_shader::Pipeline::new(
+        renderer.device(),
+        &camera_bind_group_layout,
+        surface_format,
+        sample_count,
+    );
+
+    let camera = Camera::new((-8.0, 0.0, 0.0), cgmath::Deg(0.0), cgmath::Deg(0.0));
+    let projection = Projection::new(WIDTH, HEIGHT, cgmath::Deg(45.0), 0.1, 100.0);
+    let camera_buffer = perspective_camera(renderer, &camera_bind_group_layout, &camera);
+
+    // A row of spheres, the outer ones are beyond the sides of the view
+    let instances: Vec<_> = (0..9)
+        .map(|index| {
+            let offset = index as f32 - 4.0;
+            vertex_color_shader::Instance::new(
+                cgmath::Vector3::new(0.0, 2.0 * offset, 0.0),
+                cgmath::Quaternion::one(),
+            )
+            .with_scale(cgmath::Vector3::new(0.6, 0.6, 0.6))
+            .with_color(cgmath::Vector4::new(
+                0.5 + offset / 8.0,
+                0.8,
+                0.5 - offset / 8.0,
+                1.0,
+            ))
+        })
+        .collect();
+
+    let sphere = shape::UVSphere::new(1.0, 16);
+    let mesh = vertex_color_shader::Mesh::from_shape(
+        renderer.device(),
+        sphere.triangles(),
+        &cgmath::Vector3::new(1.0, 1.0, 1.0),
+        &instances,
+    );
+    let mut gpu_culling = mesh.create_gpu_culling(renderer.context(), mode);
+
+    let frustum = Frustum::new(&camera, &projection);
+    let queue = renderer.queue().clone();
+    let mut frame = renderer.begin_frame().unwrap();
+    let result = gpu_culling.cull(frame.encoder(), &queue, &frustum);
+    match gpu_culling.mode() {
+        CullingMode::Compute => assert_eq!(result, None),
+        CullingMode::Cpu => {
+            let result = result.unwrap();
+            assert_eq!(result.total, 9);
+            assert!(result.culled() > 0);
+        }
+    }
+    {
+        let mut render_pass = frame.begin_pass(&FramePass::scene(CLEAR_COLOR));
+        pipeline.draw_culled(&mut render_pass, &camera_buffer, &gpu_culling, &mesh);
+    }
+    finish(renderer, frame)
+}
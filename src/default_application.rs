@@ -1,10 +1,14 @@
 //! Using an event loop to call functions of a basic wgpu renderer application
 
+use std::collections::HashMap;
 use std::sync::Arc;
 
 use winit::{dpi::LogicalSize, window};
 
-use crate::wgpu_renderer::{WgpuRenderer, WgpuRendererInterface};
+use crate::wgpu_renderer::{GpuContext, RendererConfig, WgpuRenderer, WgpuRendererInterface};
+
+/// The kind of the main window, further windows are identified by the kind they were opened with
+pub const MAIN_WINDOW_KIND: usize = 0;
 
 pub trait DefaultApplicationInterface {
     fn create(
@@ -30,23 +34,133 @@ pub trait DefaultApplicationInterface {
         &mut self,
         renderer_interface: &mut dyn WgpuRendererInterface,
     ) -> Result<(), wgpu::SurfaceError>;
+
+    /// Creates the application of a window, every window has its own instance
+    ///
+    /// The kind is `MAIN_WINDOW_KIND` for the main window, or the one passed to
+    /// `DefaultApplication::add_window` or returned by `requested_windows`.
+    fn create_for_window(
+        renderer_interface: &mut dyn WgpuRendererInterface,
+        size: winit::dpi::PhysicalSize<u32>,
+        scale_factor: f32,
+        _window_kind: usize,
+    ) -> Self
+    where
+        Self: Sized,
+    {
+        Self::create(renderer_interface, size, scale_factor)
+    }
+
+    /// Windows to open, e.g. a detached inspector, polled after every update
+    fn requested_windows(&mut self) -> Vec<(usize, window::WindowAttributes)> {
+        Vec::new()
+    }
+
+    /// Called after the device was lost and the renderer was recreated
+    ///
+    /// All GPU resources of the old device are invalid. By default the application
+    /// is created again, override this to rebuild only the GPU resources and keep the state.
+    fn device_recreated(
+        &mut self,
+        renderer_interface: &mut dyn WgpuRendererInterface,
+        size: winit::dpi::PhysicalSize<u32>,
+        scale_factor: f32,
+        window_kind: usize,
+    ) where
+        Self: Sized,
+    {
+        *self = Self::create_for_window(renderer_interface, size, scale_factor, window_kind);
+    }
+}
+
+/// A window with its own surface and application, all windows share the device
+struct WindowState<ConcreteApplication: DefaultApplicationInterface> {
+    window: Arc<winit::window::Window>,
+    window_kind: usize,
+    wgpu_renderer: Option<WgpuRenderer>,
+    app: Option<ConcreteApplication>,
+    last_render_time: instant::Instant,
+}
+
+impl<ConcreteApplication: DefaultApplicationInterface> WindowState<ConcreteApplication> {
+    fn new(window: Arc<winit::window::Window>, window_kind: usize) -> Self {
+        Self {
+            window,
+            window_kind,
+            wgpu_renderer: None,
+            app: None,
+            last_render_time: instant::Instant::now(),
+        }
+    }
+
+    /// Creates the application, or lets it rebuild its resources if it already exists
+    fn attach_renderer(&mut self, mut wgpu_renderer: WgpuRenderer) {
+        let scale_factor = self.window.scale_factor();
+        let size = self.window.inner_size();
+        let mut app = match self.app.take() {
+            // the renderer was recreated after a device loss
+            Some(mut app) => {
+                app.device_recreated(
+                    &mut wgpu_renderer,
+                    size,
+                    scale_factor as f32,
+                    self.window_kind,
+                );
+                log::info!("App resources recreated");
+                app
+            }
+            // create app
+            None => {
+                let app = ConcreteApplication::create_for_window(
+                    &mut wgpu_renderer,
+                    size,
+                    scale_factor as f32,
+                    self.window_kind,
+                );
+                log::info!("App created");
+                app
+            }
+        };
+        // log::info!("size original: {} {}", size.width, size.height);
+        wgpu_renderer.resize(size);
+        app.resize(&mut wgpu_renderer, size);
+
+        self.wgpu_renderer = Some(wgpu_renderer);
+        self.app = Some(app);
+
+        self.last_render_time = instant::Instant::now();
+
+        self.window.request_redraw();
+    }
 }
 
 pub struct DefaultApplication<ConcreteApplication: DefaultApplicationInterface> {
     // state
     initial_size: Option<LogicalSize<u32>>,
-    window: Option<Arc<winit::window::Window>>,
-    wgpu_renderer: Option<WgpuRenderer>,
-    app: Option<ConcreteApplication>,
+    renderer_config: RendererConfig,
+    main_window_id: Option<winit::window::WindowId>,
+    windows: HashMap<winit::window::WindowId, WindowState<ConcreteApplication>>,
+    // Opened together with the main window
+    pending_windows: Vec<(usize, window::WindowAttributes)>,
+    // Shared by all windows, None until the first renderer was created
+    context: Option<GpuContext>,
 
     // Used to send cosutome events to the event loop
     proxy: winit::event_loop::EventLoopProxy<WgpuRenderer>,
-
-    last_render_time: instant::Instant,
 }
 
 impl<ConcreteApplication: DefaultApplicationInterface> DefaultApplication<ConcreteApplication> {
     pub fn new(event_loop: &winit::event_loop::EventLoop<WgpuRenderer>) -> Self {
+        let renderer_config =
+            RendererConfig::default().with_present_mode(wgpu::PresentMode::Immediate);
+
+        Self::new_with_config(event_loop, renderer_config)
+    }
+
+    pub fn new_with_config(
+        event_loop: &winit::event_loop::EventLoop<WgpuRenderer>,
+        renderer_config: RendererConfig,
+    ) -> Self {
         #[cfg(target_arch = "wasm32")]
         {
             console_error_panic_hook::set_once();
@@ -68,13 +182,132 @@ impl<ConcreteApplication: DefaultApplicationInterface> DefaultApplication<Concre
 
         Self {
             initial_size: None,
-            window: None,
-            wgpu_renderer: None,
-            app: None,
-            last_render_time: instant::Instant::now(),
+            renderer_config,
+            main_window_id: None,
+            windows: HashMap::new(),
+            pending_windows: Vec::new(),
+            context: None,
             proxy,
         }
     }
+
+    /// Opens another window together with the main window, e.g. an inspector
+    ///
+    /// Its application is created by `create_for_window` with the given kind.
+    pub fn add_window(&mut self, window_kind: usize, window_attributes: window::WindowAttributes) {
+        self.pending_windows.push((window_kind, window_attributes));
+    }
+}
+
+/// Creates the renderer and sends it to the event loop, where it is received by `user_event`
+fn spawn_wgpu_renderer(
+    window: Arc<winit::window::Window>,
+    renderer_config: RendererConfig,
+    proxy: winit::event_loop::EventLoopProxy<WgpuRenderer>,
+) {
+    let create_wgpu_renderer = async move {
+        match WgpuRenderer::new(window.clone(), &renderer_config).await {
+            Ok(wgpu_renderer) => {
+                log::info!("WgpuRenderer created");
+                assert!(proxy.send_event(wgpu_renderer).is_ok())
+            }
+            Err(err) => {
+                // The window stays open without content, so it can still be closed
+                log::error!("Failed to create WgpuRenderer: {}", err);
+            }
+        }
+    };
+
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        // If we are not on web we can use pollster to
+        // await the
+        pollster::block_on(create_wgpu_renderer);
+    }
+    #[cfg(target_arch = "wasm32")]
+    {
+        // Run the future asynchronously and use the
+        // proxy to send the results to the event loop
+        wasm_bindgen_futures::spawn_local(create_wgpu_renderer);
+    }
+}
+
+impl<ConcreteApplication: DefaultApplicationInterface> DefaultApplication<ConcreteApplication> {
+    /// Drops the lost device with all surfaces and requests a new renderer for the main window
+    ///
+    /// The renderers of the other windows are recreated with its context in `user_event`.
+    fn recreate_wgpu_renderers(&mut self) {
+        log::warn!("Recreating the renderers");
+        self.context = None;
+        for window_state in self.windows.values_mut() {
+            window_state.wgpu_renderer = None;
+        }
+
+        let main_window = self
+            .main_window_id
+            .and_then(|window_id| self.windows.get(&window_id));
+        if let Some(main_window) = main_window {
+            spawn_wgpu_renderer(
+                main_window.window.clone(),
+                self.renderer_config.clone(),
+                self.proxy.clone(),
+            );
+        }
+    }
+
+    /// Creates the renderers of all windows that have none, using the shared device
+    fn attach_renderers(&mut self) {
+        let Some(context) = self.context.as_ref() else {
+            return;
+        };
+
+        for window_state in self.windows.values_mut() {
+            if window_state.wgpu_renderer.is_some() {
+                continue;
+            }
+
+            match WgpuRenderer::new_with_context(
+                window_state.window.clone(),
+                context,
+                &self.renderer_config,
+            ) {
+                Ok(wgpu_renderer) => window_state.attach_renderer(wgpu_renderer),
+                Err(err) => log::error!("Failed to create WgpuRenderer: {}", err),
+            }
+        }
+    }
+
+    fn open_window(
+        &mut self,
+        event_loop: &winit::event_loop::ActiveEventLoop,
+        window_kind: usize,
+        window_attributes: window::WindowAttributes,
+    ) {
+        match event_loop.create_window(window_attributes) {
+            Ok(window) => {
+                let window = Arc::new(window);
+                log::info!("Window created: {:?}", window.id());
+                self.windows
+                    .insert(window.id(), WindowState::new(window, window_kind));
+                self.attach_renderers();
+            }
+            Err(err) => log::error!("Failed to create window: {}", err),
+        }
+    }
+
+    /// Closing the main window exits the application
+    fn close_window(
+        &mut self,
+        event_loop: &winit::event_loop::ActiveEventLoop,
+        window_id: winit::window::WindowId,
+    ) {
+        if self.main_window_id == Some(window_id) {
+            event_loop.exit();
+        } else {
+            self.windows.remove(&window_id);
+            log::info!("Window closed: {:?}", window_id);
+        }
+    }
 }
 
 impl<ConcreteApplication: DefaultApplicationInterface>
@@ -82,6 +315,10 @@ impl<ConcreteApplication: DefaultApplicationInterface>
     for DefaultApplication<ConcreteApplication>
 {
     fn resumed(&mut self, event_loop: &winit::event_loop::ActiveEventLoop) {
+        if self.main_window_id.is_some() {
+            return;
+        }
+
         // create window
         #[allow(unused_mut)]
         let mut window_attributes: window::WindowAttributes = window::Window::default_attributes();
@@ -109,36 +346,24 @@ impl<ConcreteApplication: DefaultApplicationInterface>
 
         let window = Arc::new(event_loop.create_window(window_attributes.clone()).unwrap());
 
-        self.window = Some(window.clone());
+        self.main_window_id = Some(window.id());
+        self.windows.insert(
+            window.id(),
+            WindowState::new(window.clone(), MAIN_WINDOW_KIND),
+        );
         log::info!("Window created");
 
+        // the renderers of these windows are created once the device exists
+        for (window_kind, window_attributes) in std::mem::take(&mut self.pending_windows) {
+            self.open_window(event_loop, window_kind, window_attributes);
+        }
+
         // ControlFlow::Poll continuously runs the event loop, even if the OS hasn't
         // dispatched any events. This is ideal for games and similar applications.
         event_loop.set_control_flow(winit::event_loop::ControlFlow::Wait);
 
         // Init wgpu
-        let present_mode = Some(wgpu::PresentMode::Immediate);
-        let proxy = self.proxy.clone();
-
-        let create_wgpu_renderer = async move {
-            let wgpu_renderer = WgpuRenderer::new(window.clone(), present_mode).await;
-            log::info!("WgpuRenderer created");
-
-            assert!(proxy.send_event(wgpu_renderer).is_ok())
-        };
-
-        #[cfg(not(target_arch = "wasm32"))]
-        {
-            // If we are not on web we can use pollster to
-            // await the
-            pollster::block_on(create_wgpu_renderer);
-        }
-        #[cfg(target_arch = "wasm32")]
-        {
-            // Run the future asynchronously and use the
-            // proxy to send the results to the event loop
-            wasm_bindgen_futures::spawn_local(create_wgpu_renderer);
-        }
+        spawn_wgpu_renderer(window, self.renderer_config.clone(), self.proxy.clone());
     }
 
     fn user_event(
@@ -146,33 +371,28 @@ impl<ConcreteApplication: DefaultApplicationInterface>
         _event_loop: &winit::event_loop::ActiveEventLoop,
         event: WgpuRenderer,
     ) {
-        let window = self.window.as_ref().unwrap();
-        let mut wgpu_renderer = event;
+        let wgpu_renderer = event;
+        let window_id = wgpu_renderer.window().id();
+        self.context = Some(wgpu_renderer.context().clone());
 
-        // create app
-        let scale_factor = window.scale_factor();
-        let size = window.inner_size();
-        let mut app = ConcreteApplication::create(&mut wgpu_renderer, size, scale_factor as f32);
-        // log::info!("size original: {} {}", size.width, size.height);
-        wgpu_renderer.resize(size);
-        app.resize(&mut wgpu_renderer, size);
-
-        self.wgpu_renderer = Some(wgpu_renderer);
-        self.app = Some(app);
-        log::info!("App created");
-
-        self.last_render_time = instant::Instant::now();
+        let Some(window_state) = self.windows.get_mut(&window_id) else {
+            return;
+        };
+        window_state.attach_renderer(wgpu_renderer);
 
-        window.request_redraw();
         if let Some(initial_size) = self.initial_size {
             // warkaround for webgl
-            let _res = window.request_inner_size(initial_size);
+            let _res = window_state.window.request_inner_size(initial_size);
         }
+
+        // windows opened before the device existed or after it was lost
+        self.attach_renderers();
     }
 
     fn about_to_wait(&mut self, _event_loop: &winit::event_loop::ActiveEventLoop) {
-        let window = self.window.as_ref().unwrap();
-        window.request_redraw();
+        for window_state in self.windows.values() {
+            window_state.window.request_redraw();
+        }
     }
 
     fn window_event(
@@ -181,132 +401,165 @@ impl<ConcreteApplication: DefaultApplicationInterface>
         window_id: winit::window::WindowId,
         event: winit::event::WindowEvent,
     ) {
-        if self.window.is_none() || self.wgpu_renderer.is_none() || self.app.is_none() {
+        let Some(window_state) = self.windows.get_mut(&window_id) else {
+            return;
+        };
+
+        let (Some(wgpu_renderer), Some(app)) = (
+            window_state.wgpu_renderer.as_mut(),
+            window_state.app.as_mut(),
+        ) else {
             log::info!("not yet initialized: {:?}", event);
-            // event_loop.exit();
+            if event == winit::event::WindowEvent::CloseRequested {
+                self.close_window(event_loop, window_id);
+            }
             return;
-        }
+        };
+        let window = &window_state.window;
 
-        let window = self.window.as_ref().unwrap();
-        let wgpu_renderer = self.wgpu_renderer.as_mut().unwrap();
-        let app = self.app.as_mut().unwrap();
+        let mut is_close_requested = false;
+        let mut is_device_lost = false;
+        let mut requested_windows = Vec::new();
 
-        if window.id() == window_id {
-            if app.input(&event) {
-                // event consumed directly by the application
-                return;
-            }
+        if app.input(&event) {
+            // event consumed directly by the application
+            return;
+        }
 
-            match event {
-                winit::event::WindowEvent::ActivationTokenDone {
-                    serial: _,
-                    token: _,
-                } => {}
-                winit::event::WindowEvent::Resized(physical_size) => {
-                    log::info!("resize: {} {}", physical_size.width, physical_size.height);
-                    wgpu_renderer.resize(physical_size);
-                    app.resize(wgpu_renderer, physical_size);
-                }
-                winit::event::WindowEvent::Moved(_physical_position) => {}
-                winit::event::WindowEvent::CloseRequested => {
-                    event_loop.exit();
-                }
-                winit::event::WindowEvent::Destroyed => {}
-                winit::event::WindowEvent::DroppedFile(_path_buf) => {}
-                winit::event::WindowEvent::HoveredFile(_path_buf) => {}
-                winit::event::WindowEvent::HoveredFileCancelled => {}
-                winit::event::WindowEvent::Focused(_) => {}
-                winit::event::WindowEvent::KeyboardInput {
-                    device_id: _,
-                    event:
-                        winit::event::KeyEvent {
-                            physical_key:
-                                winit::keyboard::PhysicalKey::Code(winit::keyboard::KeyCode::Escape),
-                            logical_key: _,
-                            text: _,
-                            location: _,
-                            state: winit::event::ElementState::Pressed,
-                            repeat: _,
-                            ..
-                        },
-                    is_synthetic: _,
-                } => {
-                    event_loop.exit();
-                }
-                winit::event::WindowEvent::ModifiersChanged(_modifiers) => {}
-                winit::event::WindowEvent::Ime(_ime) => {}
-                winit::event::WindowEvent::CursorMoved {
-                    device_id: _,
-                    position: _,
-                } => {}
-                winit::event::WindowEvent::CursorEntered { device_id: _ } => {}
-                winit::event::WindowEvent::CursorLeft { device_id: _ } => {}
-                winit::event::WindowEvent::MouseWheel {
-                    device_id: _,
-                    delta: _,
-                    phase: _,
-                } => {}
-                winit::event::WindowEvent::MouseInput {
-                    device_id: _,
-                    state: _,
-                    button: _,
-                } => {}
-                winit::event::WindowEvent::PinchGesture {
-                    device_id: _,
-                    delta: _,
-                    phase: _,
-                } => {}
-                winit::event::WindowEvent::PanGesture {
-                    device_id: _,
-                    delta: _,
-                    phase: _,
-                } => {}
-                winit::event::WindowEvent::DoubleTapGesture { device_id: _ } => {}
-                winit::event::WindowEvent::RotationGesture {
-                    device_id: _,
-                    delta: _,
-                    phase: _,
-                } => {}
-                winit::event::WindowEvent::TouchpadPressure {
-                    device_id: _,
-                    pressure: _,
-                    stage: _,
-                } => {}
-                winit::event::WindowEvent::AxisMotion {
-                    device_id: _,
-                    axis: _,
-                    value: _,
-                } => {}
-                winit::event::WindowEvent::Touch(_touch) => {}
-                winit::event::WindowEvent::ScaleFactorChanged {
-                    scale_factor,
-                    inner_size_writer: _,
-                } => {
-                    log::info!("rescale: {}", scale_factor);
-                    app.update_scale_factor(wgpu_renderer, scale_factor as f32);
-                }
-                winit::event::WindowEvent::ThemeChanged(_theme) => {}
-                winit::event::WindowEvent::Occluded(_) => {}
-                winit::event::WindowEvent::RedrawRequested => {
+        match event {
+            winit::event::WindowEvent::ActivationTokenDone {
+                serial: _,
+                token: _,
+            } => {}
+            winit::event::WindowEvent::Resized(physical_size) => {
+                log::info!("resize: {} {}", physical_size.width, physical_size.height);
+                wgpu_renderer.resize(physical_size);
+                app.resize(wgpu_renderer, physical_size);
+            }
+            winit::event::WindowEvent::Moved(_physical_position) => {}
+            winit::event::WindowEvent::CloseRequested => {
+                is_close_requested = true;
+            }
+            winit::event::WindowEvent::Destroyed => {}
+            winit::event::WindowEvent::DroppedFile(_path_buf) => {}
+            winit::event::WindowEvent::HoveredFile(_path_buf) => {}
+            winit::event::WindowEvent::HoveredFileCancelled => {}
+            winit::event::WindowEvent::Focused(_) => {}
+            winit::event::WindowEvent::KeyboardInput {
+                device_id: _,
+                event:
+                    winit::event::KeyEvent {
+                        physical_key:
+                            winit::keyboard::PhysicalKey::Code(winit::keyboard::KeyCode::Escape),
+                        logical_key: _,
+                        text: _,
+                        location: _,
+                        state: winit::event::ElementState::Pressed,
+                        repeat: _,
+                        ..
+                    },
+                is_synthetic: _,
+            } => {
+                is_close_requested = true;
+            }
+            winit::event::WindowEvent::ModifiersChanged(_modifiers) => {}
+            winit::event::WindowEvent::Ime(_ime) => {}
+            winit::event::WindowEvent::CursorMoved {
+                device_id: _,
+                position: _,
+            } => {}
+            winit::event::WindowEvent::CursorEntered { device_id: _ } => {}
+            winit::event::WindowEvent::CursorLeft { device_id: _ } => {}
+            winit::event::WindowEvent::MouseWheel {
+                device_id: _,
+                delta: _,
+                phase: _,
+            } => {}
+            winit::event::WindowEvent::MouseInput {
+                device_id: _,
+                state: _,
+                button: _,
+            } => {}
+            winit::event::WindowEvent::PinchGesture {
+                device_id: _,
+                delta: _,
+                phase: _,
+            } => {}
+            winit::event::WindowEvent::PanGesture {
+                device_id: _,
+                delta: _,
+                phase: _,
+            } => {}
+            winit::event::WindowEvent::DoubleTapGesture { device_id: _ } => {}
+            winit::event::WindowEvent::RotationGesture {
+                device_id: _,
+                delta: _,
+                phase: _,
+            } => {}
+            winit::event::WindowEvent::TouchpadPressure {
+                device_id: _,
+                pressure: _,
+                stage: _,
+            } => {}
+            winit::event::WindowEvent::AxisMotion {
+                device_id: _,
+                axis: _,
+                value: _,
+            } => {}
+            winit::event::WindowEvent::Touch(_touch) => {}
+            winit::event::WindowEvent::ScaleFactorChanged {
+                scale_factor,
+                inner_size_writer: _,
+            } => {
+                log::info!("rescale: {}", scale_factor);
+                app.update_scale_factor(wgpu_renderer, scale_factor as f32);
+            }
+            winit::event::WindowEvent::ThemeChanged(_theme) => {}
+            winit::event::WindowEvent::Occluded(_) => {}
+            winit::event::WindowEvent::RedrawRequested => {
+                if wgpu_renderer.is_device_lost() {
+                    is_device_lost = true;
+                } else {
                     let now = instant::Instant::now();
-                    let dt = now - self.last_render_time;
-                    self.last_render_time = now;
+                    let dt = now - window_state.last_render_time;
+                    window_state.last_render_time = now;
 
                     app.update(wgpu_renderer, dt);
+                    requested_windows = app.requested_windows();
                     match app.render(wgpu_renderer) {
                         Ok(_) => window.request_redraw(),
-                        // Reconfigure the surface if lost
-                        Err(wgpu::SurfaceError::Lost) => {
+                        // Reconfigure the surface if lost or outdated, e.g. after a resize
+                        Err(wgpu::SurfaceError::Lost | wgpu::SurfaceError::Outdated) => {
                             let new_size = app.get_size();
                             wgpu_renderer.resize(new_size);
                             app.resize(wgpu_renderer, new_size);
+                            window.request_redraw();
+                        }
+                        // Skip the frame, the next one usually succeeds
+                        Err(wgpu::SurfaceError::Timeout) => {
+                            log::warn!("Surface timeout, frame skipped");
+                            window.request_redraw();
                         }
                         Err(wgpu::SurfaceError::OutOfMemory) => event_loop.exit(),
-                        Err(e) => eprintln!("{:?}", e),
+                        Err(wgpu::SurfaceError::Other) => {
+                            // The device lost callback might not have been called yet
+                            log::error!("Surface error, checking the device on the next frame");
+                            window.request_redraw();
+                        }
                     }
                 }
-                _ => {}
             }
+            _ => {}
+        }
+
+        if is_close_requested {
+            self.close_window(event_loop, window_id);
+        }
+        if is_device_lost {
+            self.recreate_wgpu_renderers();
+        }
+        for (window_kind, window_attributes) in requested_windows {
+            self.open_window(event_loop, window_kind, window_attributes);
         }
 
         // event_loop.set_control_flow(winit::event_loop::ControlFlow::Wait);
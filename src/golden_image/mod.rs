@@ -0,0 +1,214 @@
+//! Compares rendered images against checked-in reference images
+//!
+//! A missing reference is an error. Set the environment variable
+//! `WGPU_RENDERER_UPDATE_GOLDEN=1` to create missing references and to overwrite all
+//! others with the current output.
+//!
+//! The GPU tests are skipped on machines without an adapter. Set
+//! `WGPU_RENDERER_REQUIRE_GPU=1`, e.g. on CI, to let them fail instead.
+//!
+
+#[cfg(test)]
+pub(crate) mod test_support;
+#[cfg(test)]
+mod tests;
+
+use anyhow::*;
+use std::path::{Path, PathBuf};
+
+#[cfg(test)]
+use crate::wgpu_renderer::{OffscreenRenderer, RendererConfig};
+
+pub const UPDATE_ENV_VAR: &str = "WGPU_RENDERER_UPDATE_GOLDEN";
+pub const REQUIRE_GPU_ENV_VAR: &str = "WGPU_RENDERER_REQUIRE_GPU";
+
+fn is_env_var_set(name: &str) -> bool {
+    std::env::var(name).is_ok_and(|value| value == "1")
+}
+
+/// True if GPU tests have to fail instead of being skipped, see `REQUIRE_GPU_ENV_VAR`
+#[cfg(test)]
+pub(crate) fn is_gpu_required() -> bool {
+    is_env_var_set(REQUIRE_GPU_ENV_VAR)
+}
+
+/// A renderer for GPU tests, None if there is no adapter and the test is skipped
+///
+/// Panics without an adapter if `REQUIRE_GPU_ENV_VAR` is set.
+#[cfg(test)]
+pub(crate) fn test_renderer(width: u32, height: u32) -> Option<OffscreenRenderer> {
+    test_renderer_with_config(
+        width,
+        height,
+        OffscreenRenderer::DEFAULT_FORMAT,
+        &RendererConfig::default(),
+    )
+}
+
+#[cfg(test)]
+pub(crate) fn test_renderer_with_config(
+    width: u32,
+    height: u32,
+    format: wgpu::TextureFormat,
+    config: &RendererConfig,
+) -> Option<OffscreenRenderer> {
+    match pollster::block_on(OffscreenRenderer::new(width, height, format, config)) {
+        std::result::Result::Ok(renderer) => Some(renderer),
+        Err(err) => {
+            if is_gpu_required() {
+                panic!("No adapter available, but {REQUIRE_GPU_ENV_VAR} is set: {err}");
+            }
+            log::warn!("No adapter available, skipping GPU test: {}", err);
+            None
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct Tolerance {
+    /// Maximum difference of a single channel before a pixel counts as mismatched
+    pub per_channel: u8,
+    /// Fraction of mismatched pixels (0.0 - 1.0) that is still accepted
+    pub max_mismatch_ratio: f32,
+}
+
+impl Default for Tolerance {
+    fn default() -> Self {
+        Self {
+            per_channel: 3,
+            max_mismatch_ratio: 0.005,
+        }
+    }
+}
+
+pub struct Comparison {
+    pub mismatched_pixels: u32,
+    pub total_pixels: u32,
+    /// Mismatched pixels are red, matching pixels are a dimmed version of the reference
+    pub diff_image: image::RgbaImage,
+}
+
+impl Comparison {
+    pub fn mismatch_ratio(&self) -> f32 {
+        if self.total_pixels == 0 {
+            0.0
+        } else {
+            self.mismatched_pixels as f32 / self.total_pixels as f32
+        }
+    }
+}
+
+pub fn compare(
+    actual: &image::RgbaImage,
+    expected: &image::RgbaImage,
+    per_channel: u8,
+) -> Result<Comparison> {
+    if actual.dimensions() != expected.dimensions() {
+        bail!(
+            "image size {:?} does not match reference size {:?}",
+            actual.dimensions(),
+            expected.dimensions()
+        );
+    }
+
+    let mut diff_image = image::RgbaImage::new(actual.width(), actual.height());
+    let mut mismatched_pixels = 0;
+
+    for ((a, e), d) in actual
+        .pixels()
+        .zip(expected.pixels())
+        .zip(diff_image.pixels_mut())
+    {
+        let is_mismatch =
+            a.0.iter()
+                .zip(e.0.iter())
+                .any(|(a, e)| a.abs_diff(*e) > per_channel);
+
+        if is_mismatch {
+            mismatched_pixels += 1;
+            *d = image::Rgba([255, 0, 0, 255]);
+        } else {
+            *d = image::Rgba([e[0] / 4, e[1] / 4, e[2] / 4, 255]);
+        }
+    }
+
+    Ok(Comparison {
+        mismatched_pixels,
+        total_pixels: actual.width() * actual.height(),
+        diff_image,
+    })
+}
+
+/// Checks an image against `reference_dir/name.png`
+///
+/// On failure the actual and the diff image are written to `output_dir`.
+pub fn check(
+    name: &str,
+    actual: &image::RgbaImage,
+    reference_dir: &Path,
+    output_dir: &Path,
+    tolerance: Tolerance,
+) -> Result<()> {
+    let reference_path = reference_dir.join(format!("{name}.png"));
+
+    if is_env_var_set(UPDATE_ENV_VAR) {
+        std::fs::create_dir_all(reference_dir)?;
+        actual.save_with_format(&reference_path, image::ImageFormat::Png)?;
+        log::warn!("Reference image written: {}", reference_path.display());
+        return Ok(());
+    }
+    if !reference_path.exists() {
+        bail!(
+            "{}: reference {} is missing, set {}=1 to create it",
+            name,
+            reference_path.display(),
+            UPDATE_ENV_VAR
+        );
+    }
+
+    let expected = image::open(&reference_path)?.to_rgba8();
+    let comparison = compare(actual, &expected, tolerance.per_channel);
+
+    let comparison = match comparison {
+        std::result::Result::Ok(comparison) => comparison,
+        Err(err) => {
+            write_output(output_dir, name, actual, None)?;
+            return Err(err);
+        }
+    };
+
+    if comparison.mismatch_ratio() > tolerance.max_mismatch_ratio {
+        let (actual_path, diff_path) =
+            write_output(output_dir, name, actual, Some(&comparison.diff_image))?;
+        bail!(
+            "{}: {} of {} pixels differ (allowed ratio {}), see {} and {}",
+            name,
+            comparison.mismatched_pixels,
+            comparison.total_pixels,
+            tolerance.max_mismatch_ratio,
+            actual_path.display(),
+            diff_path.display()
+        );
+    }
+
+    Ok(())
+}
+
+fn write_output(
+    output_dir: &Path,
+    name: &str,
+    actual: &image::RgbaImage,
+    diff: Option<&image::RgbaImage>,
+) -> Result<(PathBuf, PathBuf)> {
+    std::fs::create_dir_all(output_dir)?;
+
+    let actual_path = output_dir.join(format!("{name}.actual.png"));
+    let diff_path = output_dir.join(format!("{name}.diff.png"));
+
+    actual.save_with_format(&actual_path, image::ImageFormat::Png)?;
+    if let Some(diff) = diff {
+        diff.save_with_format(&diff_path, image::ImageFormat::Png)?;
+    }
+
+    Ok((actual_path, diff_path))
+}
@@ -0,0 +1,150 @@
+//! Scenes and helpers shared by the golden image tests of the modules
+
+use std::path::PathBuf;
+
+use crate::shape::{self, MeshDataInterface};
+use crate::vertex_color_shader;
+use crate::wgpu_renderer::camera::{Camera, Projection};
+use crate::wgpu_renderer::{Frame, FramePass, OffscreenRenderer, WgpuRendererInterface};
+
+use super::Tolerance;
+
+pub(crate) const WIDTH: u32 = 160;
+pub(crate) const HEIGHT: u32 = 120;
+
+pub(crate) const CLEAR_COLOR: wgpu::Color = wgpu::Color {
+    r: 0.1,
+    g: 0.2,
+    b: 0.3,
+    a: 1.0,
+};
+
+pub(crate) fn reference_dir() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("src/golden_image/reference")
+}
+
+pub(crate) fn output_dir() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("target/golden_image")
+}
+
+/// Checks the image against its reference in `reference_dir`
+pub(crate) fn check_golden(name: &str, actual: &image::RgbaImage) -> anyhow::Result<()> {
+    check_golden_with_tolerance(name, actual, Tolerance::default())
+}
+
+pub(crate) fn check_golden_with_tolerance(
+    name: &str,
+    actual: &image::RgbaImage,
+    tolerance: Tolerance,
+) -> anyhow::Result<()> {
+    super::check(name, actual, &reference_dir(), &output_dir(), tolerance)
+}
+
+pub(crate) fn assert_golden(name: &str, actual: &image::RgbaImage) {
+    assert_golden_with_tolerance(name, actual, Tolerance::default());
+}
+
+pub(crate) fn assert_golden_with_tolerance(
+    name: &str,
+    actual: &image::RgbaImage,
+    tolerance: Tolerance,
+) {
+    if let Err(err) = check_golden_with_tolerance(name, actual, tolerance) {
+        panic!("{err}");
+    }
+}
+
+pub(crate) fn finish(renderer: &mut OffscreenRenderer, frame: Frame) -> image::RgbaImage {
+    frame.present();
+    renderer.capture().unwrap()
+}
+
+pub(crate) fn identity_instance() -> vertex_color_shader::Instance {
+    vertex_color_shader::Instance::identity()
+}
+
+pub(crate) fn perspective_camera(
+    renderer: &mut OffscreenRenderer,
+    camera_bind_group_layout: &vertex_color_shader::CameraBindGroupLayout,
+    camera: &Camera,
+) -> vertex_color_shader::CameraUniformBuffer {
+    let projection = Projection::new(WIDTH, HEIGHT, cgmath::Deg(45.0), 0.1, 100.0);
+    let mut camera_uniform = vertex_color_shader::CameraUniform::new();
+    camera_uniform.update_view_proj(camera, &projection);
+
+    let mut camera_buffer =
+        vertex_color_shader::CameraUniformBuffer::new(renderer.device(), camera_bind_group_layout);
+    camera_buffer.update(renderer.queue(), camera_uniform);
+    camera_buffer
+}
+
+pub(crate) fn orthographic_camera(
+    renderer: &mut OffscreenRenderer,
+    camera_bind_group_layout: &vertex_color_shader::CameraBindGroupLayout,
+) -> vertex_color_shader::CameraUniformBuffer {
+    let camera_uniform = vertex_color_shader::CameraUniform::new_orthographic(WIDTH, HEIGHT);
+
+    let mut camera_buffer =
+        vertex_color_shader::CameraUniformBuffer::new(renderer.device(), camera_bind_group_layout);
+    camera_buffer.update(renderer.queue(), camera_uniform);
+    camera_buffer
+}
+
+pub(crate) fn checkerboard(size: u32, tile: u32) -> image::RgbaImage {
+    image::RgbaImage::from_fn(size, size, |x, y| {
+        if ((x / tile) + (y / tile)).is_multiple_of(2) {
+            image::Rgba([230, 200, 40, 255])
+        } else {
+            image::Rgba([40, 90, 200, 255])
+        }
+    })
+}
+
+/// A sphere colored by its positions, the scene of the renderer and post processing tests
+pub(crate) fn render_sphere(renderer: &mut OffscreenRenderer) -> image::RgbaImage {
+    let surface_format = renderer.scene_format();
+    let sample_count = renderer.sample_count();
+
+    let camera_bind_group_layout =
+        vertex_color_shader::CameraBindGroupLayout::new(renderer.device());
+    let pipeline = vertex_color_shader::Pipeline::new(
+        renderer.device(),
+        &camera_bind_group_layout,
+        surface_format,
+        sample_count,
+    );
+
+    let camera = Camera::new((-5.0, 0.0, 0.0), cgmath::Deg(0.0), cgmath::Deg(0.0));
+    let camera_buffer = perspective_camera(renderer, &camera_bind_group_layout, &camera);
+
+    let sphere = shape::UVSphere::new(1.0, 16);
+    let triangles = sphere.triangles();
+    let vertices: Vec<_> = triangles
+        .positions
+        .iter()
+        .map(|position| vertex_color_shader::Vertex {
+            position: (*position).into(),
+        })
+        .collect();
+    let colors: Vec<_> = triangles
+        .positions
+        .iter()
+        .map(|position| vertex_color_shader::Color {
+            color: (position * 0.5 + cgmath::Vector3::new(0.5, 0.5, 0.5)).into(),
+        })
+        .collect();
+    let mesh = vertex_color_shader::Mesh::new(
+        renderer.device(),
+        &vertices,
+        &colors,
+        &triangles.indices,
+        &[identity_instance()],
+    );
+
+    let mut frame = renderer.begin_frame().unwrap();
+    {
+        let mut render_pass = frame.begin_pass(&FramePass::scene(CLEAR_COLOR));
+        pipeline.draw(&mut render_pass, &camera_buffer, &mesh);
+    }
+    finish(renderer, frame)
+}
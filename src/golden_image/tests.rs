@@ -0,0 +1,43 @@
+//! Unit tests
+
+use std::path::PathBuf;
+
+use super::test_support::output_dir;
+use super::Tolerance;
+
+#[test]
+fn compare_reports_mismatches() {
+    let expected = image::RgbaImage::from_pixel(4, 4, image::Rgba([100, 100, 100, 255]));
+    let mut actual = expected.clone();
+    actual.put_pixel(0, 0, image::Rgba([102, 100, 100, 255]));
+    actual.put_pixel(1, 0, image::Rgba([110, 100, 100, 255]));
+
+    let comparison = super::compare(&actual, &expected, 3).unwrap();
+    assert_eq!(comparison.mismatched_pixels, 1);
+    assert_eq!(comparison.total_pixels, 16);
+    assert_eq!(
+        *comparison.diff_image.get_pixel(1, 0),
+        image::Rgba([255, 0, 0, 255])
+    );
+
+    let smaller = image::RgbaImage::new(2, 2);
+    assert!(super::compare(&smaller, &expected, 3).is_err());
+}
+
+#[test]
+fn missing_reference_fails() {
+    let dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("target/golden_image_missing");
+    let image = image::RgbaImage::new(4, 4);
+
+    let result = super::check(
+        "does_not_exist",
+        &image,
+        &dir,
+        &output_dir(),
+        Tolerance::default(),
+    );
+    if std::env::var(super::UPDATE_ENV_VAR).is_err() {
+        assert!(result.is_err());
+        assert!(!dir.join("does_not_exist.png").exists());
+    }
+}
@@ -82,88 +82,82 @@ fn mouse_event() -> Result<(), String> {
     let res = gui
         .gui
         .mouse_event(gui::MouseEvent::Moved { x: 800 - 10, y: 10 });
-    assert_eq!(res.consumed, false);
-    assert_eq!(res.released_event.is_none(), true);
+    assert!(!res.consumed);
+    assert!(res.released_event.is_none());
 
     let res = gui.gui.mouse_event(gui::MouseEvent::Pressed);
-    assert_eq!(res.consumed, false);
-    assert_eq!(res.released_event.is_none(), true);
+    assert!(!res.consumed);
+    assert!(res.released_event.is_none());
 
     let res = gui.gui.mouse_event(gui::MouseEvent::Released);
-    assert_eq!(res.consumed, false);
-    assert_eq!(res.released_event.is_none(), true);
+    assert!(!res.consumed);
+    assert!(res.released_event.is_none());
 
     // left bottom boarder of the 4 th button
     let res = gui
         .gui
         .mouse_event(gui::MouseEvent::Moved { x: 800 - 14, y: 14 });
-    assert_eq!(res.consumed, false);
-    assert_eq!(res.released_event.is_none(), true);
+    assert!(!res.consumed);
+    assert!(res.released_event.is_none());
 
     let res = gui.gui.mouse_event(gui::MouseEvent::Pressed);
-    assert_eq!(res.consumed, false);
-    assert_eq!(res.released_event.is_none(), true);
+    assert!(!res.consumed);
+    assert!(res.released_event.is_none());
 
     let res = gui.gui.mouse_event(gui::MouseEvent::Released);
-    assert_eq!(res.consumed, false);
-    assert_eq!(res.released_event.is_none(), true);
+    assert!(!res.consumed);
+    assert!(res.released_event.is_none());
 
     // left bottom of the 4 th button
     let res = gui
         .gui
         .mouse_event(gui::MouseEvent::Moved { x: 800 - 15, y: 15 });
-    assert_eq!(res.consumed, true);
-    assert_eq!(res.released_event.is_none(), true);
+    assert!(res.consumed);
+    assert!(res.released_event.is_none());
 
     let res = gui.gui.mouse_event(gui::MouseEvent::Pressed);
-    assert_eq!(res.consumed, true);
-    assert_eq!(res.released_event.is_none(), true);
+    assert!(res.consumed);
+    assert!(res.released_event.is_none());
 
     let res = gui.gui.mouse_event(gui::MouseEvent::Released);
-    assert_eq!(res.consumed, true);
-    assert_eq!(res.released_event.is_some(), true);
-    match res.released_event {
-        Some(event) => {
-            assert_eq!(event, RectangleId::PerformanceGraph);
-        }
-        None => {}
+    assert!(res.consumed);
+    assert!(res.released_event.is_some());
+    if let Some(event) = res.released_event {
+        assert_eq!(event, RectangleId::PerformanceGraph);
     }
 
     // right top of the 4 th button
     let res = gui
         .gui
         .mouse_event(gui::MouseEvent::Moved { x: 800 - 55, y: 45 });
-    assert_eq!(res.consumed, true);
-    assert_eq!(res.released_event.is_none(), true);
+    assert!(res.consumed);
+    assert!(res.released_event.is_none());
 
     let res = gui.gui.mouse_event(gui::MouseEvent::Pressed);
-    assert_eq!(res.consumed, true);
-    assert_eq!(res.released_event.is_none(), true);
+    assert!(res.consumed);
+    assert!(res.released_event.is_none());
 
     let res = gui.gui.mouse_event(gui::MouseEvent::Released);
-    assert_eq!(res.consumed, true);
-    assert_eq!(res.released_event.is_some(), true);
-    match res.released_event {
-        Some(event) => {
-            assert_eq!(event, RectangleId::PerformanceGraph);
-        }
-        None => {}
+    assert!(res.consumed);
+    assert!(res.released_event.is_some());
+    if let Some(event) = res.released_event {
+        assert_eq!(event, RectangleId::PerformanceGraph);
     }
 
     // right top boarder of the 4 th button
     let res = gui
         .gui
         .mouse_event(gui::MouseEvent::Moved { x: 800 - 60, y: 50 });
-    assert_eq!(res.consumed, false);
-    assert_eq!(res.released_event.is_none(), true);
+    assert!(!res.consumed);
+    assert!(res.released_event.is_none());
 
     let res = gui.gui.mouse_event(gui::MouseEvent::Pressed);
-    assert_eq!(res.consumed, false);
-    assert_eq!(res.released_event.is_none(), true);
+    assert!(!res.consumed);
+    assert!(res.released_event.is_none());
 
     let res = gui.gui.mouse_event(gui::MouseEvent::Released);
-    assert_eq!(res.consumed, false);
-    assert_eq!(res.released_event.is_none(), true);
+    assert!(!res.consumed);
+    assert!(res.released_event.is_none());
 
     Ok(())
 }
@@ -185,7 +179,7 @@ fn call_resize() -> Result<(), String> {
             }
             RectangleId::SwitchViewPoint => {
                 assert_eq!(event.x, gui_width - 55);
-                assert_eq!(event.y, 15 + 1 * 40);
+                assert_eq!(event.y, 15 + 40);
             }
             RectangleId::SwitchTexture => {
                 assert_eq!(event.x, gui_width - 55);
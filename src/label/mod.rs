@@ -2,6 +2,9 @@
 
 mod label_mesh;
 
+#[cfg(test)]
+mod tests;
+
 pub use label_mesh::LabelMesh;
 
 use image;
@@ -0,0 +1,78 @@
+//! Unit tests
+
+use cgmath::One;
+
+use crate::golden_image::test_renderer;
+use crate::golden_image::test_support::{
+    assert_golden, finish, orthographic_camera, CLEAR_COLOR, HEIGHT, WIDTH,
+};
+use crate::label;
+use crate::vertex_texture_shader;
+use crate::wgpu_renderer::{FramePass, OffscreenRenderer, WgpuRendererInterface};
+
+#[test]
+fn label() {
+    let Some(mut renderer) = test_renderer(WIDTH, HEIGHT) else {
+        return;
+    };
+    let image = render_label(&mut renderer, false);
+
+    assert_golden("label", &image);
+}
+
+/// An overlay pass keeps the result of the scene pass, so it has to look like a single pass
+#[test]
+fn frame_overlay_pass() {
+    let Some(mut renderer) = test_renderer(WIDTH, HEIGHT) else {
+        return;
+    };
+    let image = render_label(&mut renderer, true);
+
+    assert_golden("label", &image);
+}
+
+fn render_label(renderer: &mut OffscreenRenderer, use_overlay: bool) -> image::RgbaImage {
+    let surface_format = renderer.surface_format();
+    let sample_count = renderer.sample_count();
+
+    let camera_bind_group_layout =
+        vertex_texture_shader::CameraBindGroupLayout::new(renderer.device());
+    let texture_bind_group_layout =
+        vertex_texture_shader::TextureBindGroupLayout::new(renderer.device());
+    let pipeline = vertex_texture_shader::Pipeline::new_gui(
+        renderer.device(),
+        &camera_bind_group_layout,
+        &texture_bind_group_layout,
+        surface_format,
+        sample_count,
+    );
+
+    let camera_buffer = orthographic_camera(renderer, &camera_bind_group_layout);
+
+    let font = crate::freefont::create_font_free_mono();
+    let text = label::Label::new(&font, 32.0, "wgpu 42");
+    let instance = vertex_texture_shader::Instance::new(
+        cgmath::Vector3::new(10.0, 40.0, 0.0),
+        cgmath::Quaternion::one(),
+    );
+    let label_mesh = label::LabelMesh::new(
+        renderer,
+        text.get_image(),
+        &texture_bind_group_layout,
+        &instance,
+    );
+
+    let mut frame = renderer.begin_frame().unwrap();
+    {
+        let pass = if use_overlay {
+            // empty scene, the overlay has to load its clear color
+            frame.begin_pass(&FramePass::scene(CLEAR_COLOR));
+            FramePass::overlay()
+        } else {
+            FramePass::scene(CLEAR_COLOR)
+        };
+        let mut render_pass = frame.begin_pass(&pass);
+        pipeline.draw(&mut render_pass, &camera_buffer, &label_mesh);
+    }
+    finish(renderer, frame)
+}
@@ -1,12 +1,21 @@
 // #![deny(unused_crate_dependencies)]
 
+pub mod asset_manager;
+pub mod culling;
 pub mod default_application;
 pub mod freefont;
+pub mod golden_image;
 pub mod gui;
 pub mod label;
 pub mod performance_monitor;
+pub mod picking;
+pub mod post_processing;
+pub mod ray_cast;
+pub mod shadow_map;
 pub mod shape;
+pub mod skybox;
 pub mod vertex_color_shader;
 pub mod vertex_heightmap_shader;
 pub mod vertex_texture_shader;
 pub mod wgpu_renderer;
+pub mod wgsl_preprocessor;
@@ -0,0 +1,34 @@
+//! Counts the instances drawn and skipped by frustum culling, per frame
+//!
+//! Shown as a bar by `Graph::update_from_culling_stats`.
+
+use crate::culling::CullResult;
+
+#[derive(Copy, Clone, Debug, Default)]
+pub struct CullingStats {
+    current: CullResult,
+    last_frame: CullResult,
+}
+
+impl CullingStats {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the result of culling one mesh in the current frame
+    pub fn add(&mut self, result: CullResult) {
+        self.current.total += result.total;
+        self.current.visible += result.visible;
+    }
+
+    /// Finishes the current frame, call it once per frame like `Watch::update`
+    pub fn update(&mut self) {
+        self.last_frame = self.current;
+        self.current = CullResult::default();
+    }
+
+    /// The counts of the last finished frame
+    pub fn get(&self) -> CullResult {
+        self.last_frame
+    }
+}
@@ -0,0 +1,307 @@
+//! Measures the time render and compute passes take on the GPU with timestamp queries
+//!
+//! Requires `wgpu::Features::TIMESTAMP_QUERY`, e.g. requested with
+//! `RendererConfig::with_optional_features`. Without it, no data is recorded.
+//! The results are read back asynchronously a few frames later and converted to
+//! `WatchViewerData`, so they are drawn by `Graph::update_from_gpu_viewer_data` next to
+//! the CPU times.
+//!
+
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::Arc;
+
+use super::watch::{WatchPoint, WatchViewerData};
+
+const NR_READBACK_BUFFERS: usize = 3;
+
+const MAP_PENDING: u8 = 0;
+const MAP_DONE: u8 = 1;
+const MAP_FAILED: u8 = 2;
+
+#[derive(Copy, Clone, PartialEq)]
+enum ReadbackState {
+    Idle,
+    /// The copy was recorded, the buffer is mapped after the commands were submitted
+    Copied,
+    Mapping,
+}
+
+struct ReadbackBuffer {
+    buffer: wgpu::Buffer,
+    state: ReadbackState,
+    map_result: Arc<AtomicU8>,
+    written: u64,
+    /// The frame the timestamps were resolved in, to read back the oldest one first
+    frame: u64,
+}
+
+struct Queries {
+    query_set: wgpu::QuerySet,
+    resolve_buffer: wgpu::Buffer,
+    readback_buffers: Vec<ReadbackBuffer>,
+    /// Counts the resolved frames
+    frame_counter: u64,
+}
+
+pub struct GpuWatch<const SIZE: usize> {
+    queries: Option<Queries>,
+    timestamp_period: f32,
+    names: [&'static str; SIZE],
+    /// Bit i is set if watch point i was written in the current frame
+    written: u64,
+}
+
+impl<const SIZE: usize> GpuWatch<SIZE> {
+    pub const FEATURES: wgpu::Features = wgpu::Features::TIMESTAMP_QUERY;
+
+    pub fn new(device: &wgpu::Device, queue: &wgpu::Queue) -> Self {
+        assert!(SIZE <= 64, "at most 64 watch points are supported");
+
+        let queries = if device.features().contains(Self::FEATURES) {
+            Some(Self::create_queries(device))
+        } else {
+            log::warn!("Timestamp queries not supported, no GPU times are measured");
+            None
+        };
+
+        Self {
+            queries,
+            timestamp_period: queue.get_timestamp_period(),
+            names: [""; SIZE],
+            written: 0,
+        }
+    }
+
+    fn create_queries(device: &wgpu::Device) -> Queries {
+        let nr_queries = (SIZE * 2) as u32;
+        let size = nr_queries as u64 * wgpu::QUERY_SIZE as u64;
+
+        let query_set = device.create_query_set(&wgpu::QuerySetDescriptor {
+            label: Some("GPU Watch Query Set"),
+            ty: wgpu::QueryType::Timestamp,
+            count: nr_queries,
+        });
+
+        let resolve_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("GPU Watch Resolve Buffer"),
+            size,
+            usage: wgpu::BufferUsages::QUERY_RESOLVE | wgpu::BufferUsages::COPY_SRC,
+            mapped_at_creation: false,
+        });
+
+        let readback_buffers = (0..NR_READBACK_BUFFERS)
+            .map(|_| ReadbackBuffer {
+                buffer: device.create_buffer(&wgpu::BufferDescriptor {
+                    label: Some("GPU Watch Readback Buffer"),
+                    size,
+                    usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+                    mapped_at_creation: false,
+                }),
+                state: ReadbackState::Idle,
+                map_result: Arc::new(AtomicU8::new(MAP_PENDING)),
+                written: 0,
+                frame: 0,
+            })
+            .collect();
+
+        Queries {
+            query_set,
+            resolve_buffer,
+            readback_buffers,
+            frame_counter: 0,
+        }
+    }
+
+    pub fn is_supported(&self) -> bool {
+        self.queries.is_some()
+    }
+
+    /// Measures a render pass, pass the result to its descriptor
+    pub fn render_pass_timestamp_writes(
+        &mut self,
+        index: usize,
+        name: &'static str,
+    ) -> Option<wgpu::RenderPassTimestampWrites<'_>> {
+        let (beginning, end) = self.start(index, name)?;
+
+        Some(wgpu::RenderPassTimestampWrites {
+            query_set: &self.queries.as_ref()?.query_set,
+            beginning_of_pass_write_index: Some(beginning),
+            end_of_pass_write_index: Some(end),
+        })
+    }
+
+    /// Measures a compute pass, pass the result to its descriptor
+    pub fn compute_pass_timestamp_writes(
+        &mut self,
+        index: usize,
+        name: &'static str,
+    ) -> Option<wgpu::ComputePassTimestampWrites<'_>> {
+        let (beginning, end) = self.start(index, name)?;
+
+        Some(wgpu::ComputePassTimestampWrites {
+            query_set: &self.queries.as_ref()?.query_set,
+            beginning_of_pass_write_index: Some(beginning),
+            end_of_pass_write_index: Some(end),
+        })
+    }
+
+    fn start(&mut self, index: usize, name: &'static str) -> Option<(u32, u32)> {
+        if index >= SIZE || self.queries.is_none() {
+            return None;
+        }
+
+        self.names[index] = name;
+        self.written |= 1 << index;
+
+        Some((index as u32 * 2, index as u32 * 2 + 1))
+    }
+
+    /// Copies the timestamps of this frame, call it after the last measured pass
+    pub fn resolve(&mut self, encoder: &mut wgpu::CommandEncoder) {
+        let written = std::mem::take(&mut self.written);
+        let Some(queries) = self.queries.as_mut() else {
+            return;
+        };
+        if written == 0 {
+            return;
+        }
+
+        // all buffers are still in use if the GPU is far behind, this frame is skipped then
+        let Some(readback) = queries
+            .readback_buffers
+            .iter_mut()
+            .find(|readback| readback.state == ReadbackState::Idle)
+        else {
+            return;
+        };
+
+        encoder.resolve_query_set(
+            &queries.query_set,
+            0..(SIZE * 2) as u32,
+            &queries.resolve_buffer,
+            0,
+        );
+        encoder.copy_buffer_to_buffer(
+            &queries.resolve_buffer,
+            0,
+            &readback.buffer,
+            0,
+            queries.resolve_buffer.size(),
+        );
+
+        readback.state = ReadbackState::Copied;
+        readback.written = written;
+        readback.frame = queries.frame_counter;
+        queries.frame_counter += 1;
+    }
+
+    /// Starts reading back the timestamps, call it after the frame was submitted
+    pub fn map(&mut self) {
+        let Some(queries) = self.queries.as_mut() else {
+            return;
+        };
+
+        for readback in queries
+            .readback_buffers
+            .iter_mut()
+            .filter(|readback| readback.state == ReadbackState::Copied)
+        {
+            readback.map_result.store(MAP_PENDING, Ordering::SeqCst);
+            let map_result = readback.map_result.clone();
+            readback
+                .buffer
+                .slice(..)
+                .map_async(wgpu::MapMode::Read, move |result| {
+                    let value = if result.is_ok() { MAP_DONE } else { MAP_FAILED };
+                    map_result.store(value, Ordering::SeqCst);
+                });
+            readback.state = ReadbackState::Mapping;
+        }
+    }
+
+    /// Returns the times of the oldest frame that has been read back, without waiting for the GPU
+    ///
+    /// The times are relative to the start of the first measured pass.
+    pub fn get_viewer_data(&mut self, device: &wgpu::Device) -> Option<WatchViewerData<SIZE>> {
+        let queries = self.queries.as_mut()?;
+        let _res = device.poll(wgpu::PollType::Poll);
+
+        let readback = queries
+            .readback_buffers
+            .iter_mut()
+            .filter(|readback| {
+                readback.state == ReadbackState::Mapping
+                    && readback.map_result.load(Ordering::SeqCst) != MAP_PENDING
+            })
+            .min_by_key(|readback| readback.frame)?;
+
+        let is_mapped = readback.map_result.load(Ordering::SeqCst) == MAP_DONE;
+        let data = if is_mapped {
+            let timestamps: Vec<u64> = {
+                let view = readback.buffer.slice(..).get_mapped_range();
+                bytemuck::cast_slice(&view).to_vec()
+            };
+            readback.buffer.unmap();
+
+            Some(viewer_data_from_timestamps(
+                &timestamps,
+                readback.written,
+                &self.names,
+                self.timestamp_period,
+                instant::Instant::now(),
+            ))
+        } else {
+            None
+        };
+        readback.state = ReadbackState::Idle;
+
+        data
+    }
+}
+
+/// Converts pairs of start and stop timestamps to watch points, starting at `base_time`
+pub(super) fn viewer_data_from_timestamps<const SIZE: usize>(
+    timestamps: &[u64],
+    written: u64,
+    names: &[&'static str; SIZE],
+    timestamp_period: f32,
+    base_time: instant::Instant,
+) -> WatchViewerData<SIZE> {
+    let is_written = |index: usize| written & (1 << index) != 0;
+    let first_timestamp = (0..SIZE)
+        .filter(|index| is_written(*index))
+        .map(|index| timestamps[index * 2])
+        .min()
+        .unwrap_or(0);
+    let to_time = |timestamp: u64| {
+        let nanos = timestamp.saturating_sub(first_timestamp) as f64 * timestamp_period as f64;
+        base_time + instant::Duration::from_nanos(nanos as u64)
+    };
+
+    let mut watch_points = [WatchPoint {
+        start: base_time,
+        stop: base_time,
+        name: "",
+    }; SIZE];
+    let mut update_time = base_time;
+
+    for (index, watch_point) in watch_points.iter_mut().enumerate() {
+        watch_point.name = names[index];
+        if !is_written(index) {
+            continue;
+        }
+
+        let start = timestamps[index * 2];
+        let stop = timestamps[index * 2 + 1].max(start);
+        watch_point.start = to_time(start);
+        watch_point.stop = to_time(stop);
+        update_time = update_time.max(watch_point.stop);
+    }
+
+    WatchViewerData {
+        last_update_time: base_time,
+        update_time,
+        watch_points,
+    }
+}
@@ -1,9 +1,14 @@
 //! Draws a performance graph of the application
 //!
+//! With `with_gpu_times` the GPU times of a `GpuWatch` are drawn in a second plot right
+//! next to the CPU times. With `with_culling_stats` a bar below the plots shows the visible
+//! and the culled instances of the `CullingStats`.
+//!
 
 use super::super::vertex_color_shader::Color;
 use super::super::vertex_color_shader::Vertex;
 use super::watch;
+use super::CullingStats;
 
 struct Geometry {
     len_per_micro: f32,
@@ -28,6 +33,13 @@ pub struct Graph<const SIZE: usize> {
     color_gradient: colorous::Gradient,
 
     geometry: Geometry,
+
+    /// End of the CPU plot in the vertices
+    cpu_vertices_end: usize,
+    /// Start of the fps lines of the GPU plot in the vertices
+    gpu_vertices_start: Option<usize>,
+    /// Start of the lines of the visible and the culled instances in the vertices
+    culling_vertices_start: Option<usize>,
 }
 
 impl<const SIZE: usize> Graph<SIZE> {
@@ -154,7 +166,7 @@ impl<const SIZE: usize> Graph<SIZE> {
         // colors
         let color_gradient_vec = Self::color_gradient_vec(&color_gradient, watch_points_size);
 
-        for i in 0..colors.len() / line_nr_vertices {
+        for i in 0..geometry.nr_lines {
             for j in 0..watch_points_size {
                 let color = color_gradient_vec[j];
 
@@ -177,6 +189,8 @@ impl<const SIZE: usize> Graph<SIZE> {
             *item = i as u32;
         }
 
+        let cpu_vertices_end = vertices.len();
+
         Self {
             vertices,
             colors,
@@ -186,9 +200,67 @@ impl<const SIZE: usize> Graph<SIZE> {
             color_gradient,
 
             geometry,
+
+            cpu_vertices_end,
+            gpu_vertices_start: None,
+            culling_vertices_start: None,
         }
     }
 
+    /// Adds a plot for the GPU times right of the CPU times, see `update_from_gpu_viewer_data`
+    pub fn with_gpu_times(mut self) -> Self {
+        if self.gpu_vertices_start.is_some() {
+            return self;
+        }
+
+        let cpu_len = self.cpu_vertices_end;
+        let gpu_start = self.vertices.len();
+        let gpu_offset_x = self.gpu_offset_x();
+
+        let fps_lines = self.geometry.fps_lines.map(|mut vertex| {
+            vertex.position[0] += (gpu_offset_x - self.geometry.offset_x) as f32;
+            vertex
+        });
+
+        // the GPU plot has the same layout and colors as the CPU plot
+        self.vertices.extend_from_within(..cpu_len);
+        self.vertices[gpu_start..gpu_start + fps_lines.len()].copy_from_slice(&fps_lines);
+        self.colors.extend_from_within(..cpu_len);
+        self.indices
+            .extend((gpu_start..gpu_start + cpu_len).map(|i| i as u32));
+
+        self.geometry.width = gpu_offset_x + self.geometry.nr_lines;
+        self.gpu_vertices_start = Some(gpu_start);
+
+        self
+    }
+
+    /// Adds a bar below the CPU plot, see `update_from_culling_stats`
+    pub fn with_culling_stats(mut self) -> Self {
+        if self.culling_vertices_start.is_some() {
+            return self;
+        }
+
+        let culling_start = self.vertices.len();
+        let visible = [0.0, 0.8, 0.0];
+        let culled = [0.5, 0.5, 0.5];
+        for color in [visible, visible, culled, culled] {
+            self.vertices.push(Vertex::zero());
+            self.colors.push(Color { color });
+        }
+        self.indices
+            .extend((culling_start..culling_start + 4).map(|i| i as u32));
+
+        self.culling_vertices_start = Some(culling_start);
+        self.update_from_culling_stats(&CullingStats::new());
+
+        self
+    }
+
+    fn gpu_offset_x(&self) -> usize {
+        self.geometry.offset_x + self.geometry.nr_lines + self.geometry.offset_x
+    }
+
     fn create_line(
         geometry: &Geometry,
         last_update_time: instant::Instant,
@@ -229,7 +301,12 @@ impl<const SIZE: usize> Graph<SIZE> {
         line
     }
 
-    fn update_vertices(geometry: &Geometry, vertices: &mut [Vertex], line: &[f32]) {
+    fn update_vertices(
+        geometry: &Geometry,
+        vertices: &mut [Vertex],
+        line: &[f32],
+        offset_x: usize,
+    ) {
         assert!(!vertices.is_empty());
         assert!(vertices.len().is_multiple_of(line.len()));
 
@@ -238,7 +315,7 @@ impl<const SIZE: usize> Graph<SIZE> {
         // set x pos of all lines
         for i in 0..vertices.len() / line.len() {
             for j in 0..line.len() {
-                vertices[i * line.len() + j].position[0] = i as f32 + geometry.offset_x as f32;
+                vertices[i * line.len() + j].position[0] = i as f32 + offset_x as f32;
             }
         }
 
@@ -286,7 +363,68 @@ impl<const SIZE: usize> Graph<SIZE> {
         }
 
         let fps_lines_les = self.geometry.fps_lines.len();
+        let cpu_end = self.cpu_vertices_end;
         let line = Self::create_line(&self.geometry, last_update_time, update_time, watch_points);
-        Self::update_vertices(&self.geometry, &mut self.vertices[fps_lines_les..], &line);
+        Self::update_vertices(
+            &self.geometry,
+            &mut self.vertices[fps_lines_les..cpu_end],
+            &line,
+            self.geometry.offset_x,
+        );
+    }
+
+    /// The data of a `GpuWatch` with the same `SIZE`, passes that were not written stay empty
+    ///
+    /// Does nothing without `with_gpu_times`.
+    pub fn update_from_gpu_viewer_data(&mut self, data: &watch::WatchViewerData<SIZE>) {
+        let Some(gpu_vertices_start) = self.gpu_vertices_start else {
+            return;
+        };
+
+        if data.watch_points.len() != self.watch_points_size {
+            return;
+        }
+
+        let fps_lines_les = self.geometry.fps_lines.len();
+        let gpu_offset_x = self.gpu_offset_x();
+        let line = Self::create_line(
+            &self.geometry,
+            data.last_update_time,
+            data.update_time,
+            &data.watch_points,
+        );
+        Self::update_vertices(
+            &self.geometry,
+            &mut self.vertices
+                [gpu_vertices_start + fps_lines_les..gpu_vertices_start + self.cpu_vertices_end],
+            &line,
+            gpu_offset_x,
+        );
+    }
+
+    /// The visible and the culled instances of the last frame, as parts of the bar
+    ///
+    /// Does nothing without `with_culling_stats`.
+    pub fn update_from_culling_stats(&mut self, culling_stats: &CullingStats) {
+        let Some(culling_vertices_start) = self.culling_vertices_start else {
+            return;
+        };
+
+        let result = culling_stats.get();
+        let visible_ratio = if result.total > 0 {
+            result.visible as f32 / result.total as f32
+        } else {
+            0.0
+        };
+
+        let start_x = self.geometry.offset_x as f32;
+        let end_x = start_x + self.geometry.nr_lines as f32;
+        let visible_x = start_x + visible_ratio * self.geometry.nr_lines as f32;
+        let y = self.geometry.offset_y as f32 / 2.0;
+
+        let bar = &mut self.vertices[culling_vertices_start..culling_vertices_start + 4];
+        for (vertex, x) in bar.iter_mut().zip([start_x, visible_x, visible_x, end_x]) {
+            vertex.position = [x, y, 0.0];
+        }
     }
 }
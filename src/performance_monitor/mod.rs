@@ -1,10 +1,17 @@
 //! Draws a performance graph of the application
 //!
 
+mod culling_stats;
 mod fps;
+mod gpu_watch;
 mod graph;
 mod orthographic_uniform;
 pub mod watch;
 
+#[cfg(test)]
+mod tests;
+
+pub use culling_stats::CullingStats;
 pub use fps::Fps;
+pub use gpu_watch::GpuWatch;
 pub use graph::Graph;
@@ -0,0 +1,198 @@
+//! Unit tests
+
+use super::gpu_watch::viewer_data_from_timestamps;
+use super::watch::{WatchPoint, WatchViewerData};
+use super::{CullingStats, GpuWatch, Graph};
+use crate::culling::CullResult;
+use crate::golden_image::test_support::{
+    assert_golden, finish, identity_instance, orthographic_camera, CLEAR_COLOR, HEIGHT, WIDTH,
+};
+use crate::golden_image::{is_gpu_required, test_renderer, test_renderer_with_config};
+use crate::vertex_color_shader;
+use crate::wgpu_renderer::{FramePass, OffscreenRenderer, RendererConfig, WgpuRendererInterface};
+
+#[test]
+fn gpu_timestamps_to_viewer_data() {
+    let base_time = instant::Instant::now();
+    // ticks of 2 ns, watch point 1 was not written in this frame
+    let timestamps = [1000, 1500, 0, 0, 2000, 4000];
+    let names = ["shadow", "unused", "scene"];
+
+    let data = viewer_data_from_timestamps::<3>(&timestamps, 0b101, &names, 2.0, base_time);
+
+    let micros = |time: instant::Instant| (time - base_time).as_micros();
+    assert_eq!(micros(data.watch_points[0].start), 0);
+    assert_eq!(micros(data.watch_points[0].stop), 1);
+    assert_eq!(micros(data.watch_points[1].start), 0);
+    assert_eq!(micros(data.watch_points[1].stop), 0);
+    assert_eq!(micros(data.watch_points[2].start), 2);
+    assert_eq!(micros(data.watch_points[2].stop), 6);
+    assert_eq!(micros(data.update_time), 6);
+    assert_eq!(data.watch_points[2].name, "scene");
+}
+
+#[test]
+fn graph_draws_gpu_times_next_to_cpu_times() {
+    let start = instant::Instant::now();
+    let micros = |value: u64| start + instant::Duration::from_micros(value);
+    let data = |stop: u64| WatchViewerData::<1> {
+        last_update_time: micros(0),
+        update_time: micros(stop),
+        watch_points: [WatchPoint {
+            start: micros(0),
+            stop: micros(stop),
+            name: "scene",
+        }],
+    };
+
+    let cpu_graph = Graph::<1>::new(colorous::RAINBOW, 0.2);
+    let mut graph = Graph::<1>::new(colorous::RAINBOW, 0.2).with_gpu_times();
+    let cpu_len = cpu_graph.vertices.len();
+    assert_eq!(graph.vertices.len(), cpu_len * 2);
+    assert_eq!(graph.indices.len(), cpu_len * 2);
+    assert!(graph.get_width() > cpu_graph.get_width() * 2 - 20);
+
+    graph.update_from_viewer_data(&data(1000));
+    graph.update_from_gpu_viewer_data(&data(5000));
+
+    // newest lines come first after the fps lines of each plot
+    let cpu_line = &graph.vertices[6..10];
+    let gpu_line = &graph.vertices[cpu_len + 6..cpu_len + 10];
+    assert!(gpu_line[1].position[1] > cpu_line[1].position[1]);
+    assert!(gpu_line[0].position[0] > cpu_graph.get_width() as f32);
+    assert!(cpu_line[0].position[0] < cpu_graph.get_width() as f32);
+}
+
+#[test]
+fn graph_shows_culling_stats() {
+    let mut graph = Graph::<1>::new(colorous::RAINBOW, 0.2)
+        .with_culling_stats()
+        .with_gpu_times();
+    let mut culling_stats = CullingStats::new();
+    culling_stats.add(CullResult {
+        total: 4,
+        visible: 1,
+    });
+    culling_stats.update();
+    graph.update_from_culling_stats(&culling_stats);
+
+    let nr_lines = graph.get_nr_lines() as f32;
+    let culling_start = Graph::<1>::new(colorous::RAINBOW, 0.2).vertices.len();
+    let bar = &graph.vertices[culling_start..culling_start + 4];
+    assert_eq!(bar[1].position[0] - bar[0].position[0], nr_lines / 4.0);
+    assert_eq!(
+        bar[3].position[0] - bar[2].position[0],
+        nr_lines * 3.0 / 4.0
+    );
+    assert_eq!(graph.indices.len(), graph.vertices.len());
+}
+
+#[test]
+fn gpu_watch_reads_back_pass_times() {
+    let config = RendererConfig::default().with_optional_features(GpuWatch::<2>::FEATURES);
+    let Some(mut renderer) =
+        test_renderer_with_config(16, 16, OffscreenRenderer::DEFAULT_FORMAT, &config)
+    else {
+        return;
+    };
+    let device = renderer.device().clone();
+    let mut gpu_watch = GpuWatch::<2>::new(&device, renderer.queue());
+    let is_supported = gpu_watch.is_supported();
+    assert!(
+        is_supported || !is_gpu_required(),
+        "Timestamp queries are not supported by the adapter"
+    );
+
+    let mut frame = renderer.begin_frame().unwrap();
+    let clear_color = wgpu::Color::BLACK;
+    let timestamp_writes = gpu_watch.render_pass_timestamp_writes(0, "scene");
+    assert_eq!(timestamp_writes.is_some(), is_supported);
+    frame.begin_pass_with_timestamps(&FramePass::scene(clear_color), timestamp_writes);
+    frame.begin_pass_with_timestamps(
+        &FramePass::overlay(),
+        gpu_watch.render_pass_timestamp_writes(1, "overlay"),
+    );
+    gpu_watch.resolve(frame.encoder());
+    frame.present();
+    gpu_watch.map();
+
+    device.poll(wgpu::PollType::wait_indefinitely()).unwrap();
+    let data = gpu_watch.get_viewer_data(&device);
+    if !is_supported {
+        assert!(data.is_none());
+        return;
+    }
+    let data = data.unwrap();
+
+    assert_eq!(data.watch_points[0].name, "scene");
+    assert_eq!(data.watch_points[1].name, "overlay");
+    assert!(data.watch_points[0].start <= data.watch_points[0].stop);
+    assert!(data.watch_points[1].stop <= data.update_time);
+}
+
+#[test]
+fn performance_monitor_graph() {
+    let Some(mut renderer) = test_renderer(WIDTH, HEIGHT) else {
+        return;
+    };
+    let surface_format = renderer.surface_format();
+    let sample_count = renderer.sample_count();
+
+    let camera_bind_group_layout =
+        vertex_color_shader::CameraBindGroupLayout::new(renderer.device());
+    let pipeline = vertex_color_shader::Pipeline::new_lines(
+        renderer.device(),
+        &camera_bind_group_layout,
+        surface_format,
+        sample_count,
+    );
+
+    let camera_buffer = orthographic_camera(&mut renderer, &camera_bind_group_layout);
+
+    // feed the graph with fixed durations, so every run draws the same lines
+    let mut graph = Graph::<3>::new(colorous::RAINBOW, 0.2);
+    let start = instant::Instant::now();
+    let micros = |value: u64| start + instant::Duration::from_micros(value);
+    for i in 0..graph.get_nr_lines() as u64 {
+        let offset = (i % 7) * 1000;
+        let data = WatchViewerData::<3> {
+            last_update_time: micros(0),
+            update_time: micros(14000 + offset),
+            watch_points: [
+                WatchPoint {
+                    start: micros(0),
+                    stop: micros(3000),
+                    name: "update",
+                },
+                WatchPoint {
+                    start: micros(3000),
+                    stop: micros(8000 + offset),
+                    name: "draw",
+                },
+                WatchPoint {
+                    start: micros(8000 + offset),
+                    stop: micros(12000 + offset),
+                    name: "present",
+                },
+            ],
+        };
+        graph.update_from_viewer_data(&data);
+    }
+
+    let mesh = vertex_color_shader::Mesh::new(
+        renderer.device(),
+        &graph.vertices,
+        &graph.colors,
+        &graph.indices,
+        &[identity_instance()],
+    );
+
+    let mut frame = renderer.begin_frame().unwrap();
+    {
+        let mut render_pass = frame.begin_pass(&FramePass::scene(CLEAR_COLOR));
+        pipeline.draw_lines(&mut render_pass, &camera_buffer, &mesh);
+    }
+    let image = finish(&mut renderer, frame);
+
+    assert_golden("performance_monitor_graph", &image);
+}
@@ -0,0 +1,38 @@
+//! Picking objects under the cursor, by rendering ids into a `Rg32Uint` target
+//!
+//! The picking variants of the pipelines, e.g. `vertex_color_shader::Pipeline::new_picking`,
+//! write the id of the mesh, set with a `PickingId`, and the id of the instance into a
+//! `PickingTarget`, depth tested against its own single sampled `DepthTexture`. The bits of
+//! the depth are written into a second `R32Uint` target, so the world position of the hit
+//! can be reconstructed with `PickResult::world_position`.
+//!
+//! The depth texture of the renderer is not reused. With MSAA it is multisampled, while the
+//! integer ids can only be rendered single sampled. Without MSAA it is cleared again by
+//! `FramePass::overlay`, and the meshes drawn a second time would fail the `Less` depth test
+//! against their own depth from the scene pass.
+//!
+//! A frame records the picking pass with `PickingTarget::begin_pass`, followed by
+//! `request_pick` for the pixel under the cursor. After the frame was submitted, `map`
+//! starts the readback and `get_result` returns the result once it arrived, without waiting.
+//!
+//! The picking pass is a separate pass, the meshes are drawn a second time with their vertex
+//! work, and the target needs 16 bytes per pixel for the ids, the depth bits and its depth
+//! texture. Record it only in frames that request a pick. The textures are kept when the
+//! target shrinks and only recreated when it grows.
+//!
+
+mod pick_result;
+mod picking_id;
+mod picking_id_bind_group_layout;
+mod picking_target;
+
+#[cfg(test)]
+mod tests;
+
+pub use pick_result::{PickHit, PickResult};
+pub use picking_id::PickingId;
+pub use picking_id_bind_group_layout::PickingIdBindGroupLayout;
+pub use picking_target::PickingTarget;
+
+/// The value of pixels without an object, in both channels of the id target
+pub const NO_ID: u32 = u32::MAX;
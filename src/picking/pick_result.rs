@@ -0,0 +1,65 @@
+//! The object under a pixel, read back from a `PickingTarget`
+//!
+
+use cgmath::*;
+
+/// The mesh and the instance drawn at a pixel
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct PickHit {
+    /// Set with `PickingId`
+    pub mesh_id: u32,
+    /// Set with `Instance::with_id`, it does not change when the instances are culled
+    pub instance_id: u32,
+    /// Depth of the hit in normalized device coordinates
+    pub depth: f32,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct PickResult {
+    pub x: u32,
+    pub y: u32,
+    /// The size of the target when the pixel was picked
+    pub width: u32,
+    pub height: u32,
+    /// None if no object was drawn at the pixel
+    pub hit: Option<PickHit>,
+}
+
+impl PickResult {
+    pub(super) fn from_readback(
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+        ids: [u32; 2],
+        depth: f32,
+    ) -> Self {
+        let hit = (ids[0] != super::NO_ID).then_some(PickHit {
+            mesh_id: ids[0],
+            instance_id: ids[1],
+            depth,
+        });
+
+        Self {
+            x,
+            y,
+            width,
+            height,
+            hit,
+        }
+    }
+
+    /// The position of the hit in the world, with the view projection matrix the
+    /// picking pass was drawn with
+    pub fn world_position(&self, view_proj: &Matrix4<f32>) -> Option<Point3<f32>> {
+        let hit = self.hit?;
+        let inverse = view_proj.invert()?;
+
+        // The center of the pixel, y points down in the texture and up in NDC
+        let ndc_x = (self.x as f32 + 0.5) / self.width as f32 * 2.0 - 1.0;
+        let ndc_y = 1.0 - (self.y as f32 + 0.5) / self.height as f32 * 2.0;
+        let world = inverse * Vector4::new(ndc_x, ndc_y, hit.depth, 1.0);
+
+        Some(Point3::from_homogeneous(world))
+    }
+}
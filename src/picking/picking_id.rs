@@ -0,0 +1,73 @@
+//! Contains a buffer with the id of a mesh, one per mesh that can be picked
+//!
+
+use wgpu::util::DeviceExt;
+
+use super::PickingIdBindGroupLayout;
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+struct PickingIdUniform {
+    mesh_id: u32,
+    _padding: [u32; 3],
+}
+
+pub struct PickingId {
+    mesh_id: u32,
+    buffer: wgpu::Buffer,
+    bind_group: wgpu::BindGroup,
+}
+
+impl PickingId {
+    /// `picking::NO_ID` is reserved for pixels without an object
+    pub fn new(
+        device: &wgpu::Device,
+        picking_id_bind_group_layout: &PickingIdBindGroupLayout,
+        mesh_id: u32,
+    ) -> Self {
+        let buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Picking Id Buffer"),
+            contents: bytemuck::cast_slice(&[PickingIdUniform {
+                mesh_id,
+                _padding: [0; 3],
+            }]),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: picking_id_bind_group_layout.get(),
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: buffer.as_entire_binding(),
+            }],
+            label: Some("picking_id_bind_group"),
+        });
+
+        Self {
+            mesh_id,
+            buffer,
+            bind_group,
+        }
+    }
+
+    pub fn mesh_id(&self) -> u32 {
+        self.mesh_id
+    }
+
+    pub fn update(&mut self, queue: &wgpu::Queue, mesh_id: u32) {
+        self.mesh_id = mesh_id;
+        queue.write_buffer(
+            &self.buffer,
+            0,
+            bytemuck::cast_slice(&[PickingIdUniform {
+                mesh_id,
+                _padding: [0; 3],
+            }]),
+        );
+    }
+
+    /// The group is the one after the groups of the pipeline, see its `new_picking`
+    pub fn bind<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>, group: u32) {
+        render_pass.set_bind_group(group, &self.bind_group, &[]);
+    }
+}
@@ -0,0 +1,33 @@
+//! A bind group layout for the id of the mesh written by the picking pipelines
+//!
+
+pub struct PickingIdBindGroupLayout {
+    picking_id_bind_group_layout: wgpu::BindGroupLayout,
+}
+
+impl PickingIdBindGroupLayout {
+    pub fn new(device: &wgpu::Device) -> Self {
+        let picking_id_bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                entries: &[wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                }],
+                label: Some("picking_id_bind_group_layout"),
+            });
+
+        Self {
+            picking_id_bind_group_layout,
+        }
+    }
+
+    pub fn get(&self) -> &wgpu::BindGroupLayout {
+        &self.picking_id_bind_group_layout
+    }
+}
@@ -0,0 +1,289 @@
+//! The textures of the picking pass and the readback of single pixels
+//!
+
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::Arc;
+
+use super::super::wgpu_renderer::depth_texture::DepthTexture;
+use super::PickResult;
+use super::NO_ID;
+
+const MAP_PENDING: u8 = 0;
+const MAP_DONE: u8 = 1;
+const MAP_FAILED: u8 = 2;
+
+/// Copies into buffers are aligned to rows of this size
+const ROW_ALIGNMENT: u64 = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT as u64;
+
+#[derive(Copy, Clone, PartialEq)]
+enum ReadbackState {
+    Idle,
+    /// The copy was recorded, the buffer is mapped after the commands were submitted
+    Copied,
+    Mapping,
+}
+
+pub struct PickingTarget {
+    id_texture: wgpu::Texture,
+    id_view: wgpu::TextureView,
+    depth_value_texture: wgpu::Texture,
+    depth_value_view: wgpu::TextureView,
+    depth_texture: DepthTexture,
+    width: u32,
+    height: u32,
+
+    readback_buffer: wgpu::Buffer,
+    state: ReadbackState,
+    map_result: Arc<AtomicU8>,
+    /// The pixel and the size of the target of the pending readback
+    requested: [u32; 4],
+}
+
+impl PickingTarget {
+    pub const ID_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rg32Uint;
+    /// The bits of the depth, float targets can not be rendered to on all backends
+    pub const DEPTH_VALUE_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::R32Uint;
+
+    /// The size should match the frame, so the pixel under the cursor can be picked
+    pub fn new(device: &wgpu::Device, width: u32, height: u32) -> Self {
+        let width = width.max(1);
+        let height = height.max(1);
+
+        let id_texture =
+            Self::create_texture(device, width, height, Self::ID_FORMAT, "picking_ids");
+        let depth_value_texture = Self::create_texture(
+            device,
+            width,
+            height,
+            Self::DEPTH_VALUE_FORMAT,
+            "picking_depth",
+        );
+        let depth_texture = DepthTexture::new(device, width, height, "picking_depth_texture");
+
+        // The ids in the first row, the depth in the second
+        let readback_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Picking Readback Buffer"),
+            size: ROW_ALIGNMENT * 2,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        Self {
+            id_view: id_texture.create_view(&wgpu::TextureViewDescriptor::default()),
+            id_texture,
+            depth_value_view: depth_value_texture
+                .create_view(&wgpu::TextureViewDescriptor::default()),
+            depth_value_texture,
+            depth_texture,
+            width,
+            height,
+
+            readback_buffer,
+            state: ReadbackState::Idle,
+            map_result: Arc::new(AtomicU8::new(MAP_PENDING)),
+            requested: [0; 4],
+        }
+    }
+
+    fn create_texture(
+        device: &wgpu::Device,
+        width: u32,
+        height: u32,
+        format: wgpu::TextureFormat,
+        label: &str,
+    ) -> wgpu::Texture {
+        device.create_texture(&wgpu::TextureDescriptor {
+            label: Some(label),
+            size: wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
+        })
+    }
+
+    /// Keeps the textures if they are large enough, the pass only draws into the used part
+    ///
+    /// They are only recreated to grow, a pending readback still returns its result.
+    pub fn resize(&mut self, device: &wgpu::Device, width: u32, height: u32) {
+        let width = width.max(1);
+        let height = height.max(1);
+        if width == self.width && height == self.height {
+            return;
+        }
+
+        let texture_width = self.id_texture.width();
+        let texture_height = self.id_texture.height();
+        if width > texture_width || height > texture_height {
+            let resized = Self::new(device, width.max(texture_width), height.max(texture_height));
+            self.id_texture = resized.id_texture;
+            self.id_view = resized.id_view;
+            self.depth_value_texture = resized.depth_value_texture;
+            self.depth_value_view = resized.depth_value_view;
+            self.depth_texture = resized.depth_texture;
+        }
+        self.width = width;
+        self.height = height;
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Begins the picking pass, cleared to `picking::NO_ID` and the far plane
+    pub fn begin_pass<'a>(&self, encoder: &'a mut wgpu::CommandEncoder) -> wgpu::RenderPass<'a> {
+        let no_id = NO_ID as f64;
+
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Picking Render Pass"),
+            color_attachments: &[
+                Some(wgpu::RenderPassColorAttachment {
+                    view: &self.id_view,
+                    depth_slice: None,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color {
+                            r: no_id,
+                            g: no_id,
+                            b: 0.0,
+                            a: 0.0,
+                        }),
+                        store: wgpu::StoreOp::Store,
+                    },
+                }),
+                Some(wgpu::RenderPassColorAttachment {
+                    view: &self.depth_value_view,
+                    depth_slice: None,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color {
+                            r: 1.0_f32.to_bits() as f64,
+                            g: 0.0,
+                            b: 0.0,
+                            a: 0.0,
+                        }),
+                        store: wgpu::StoreOp::Store,
+                    },
+                }),
+            ],
+            depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                view: &self.depth_texture.view,
+                depth_ops: Some(wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(1.0),
+                    store: wgpu::StoreOp::Discard,
+                }),
+                stencil_ops: None,
+            }),
+            timestamp_writes: None,
+            occlusion_query_set: None,
+            multiview_mask: None,
+        });
+
+        // the textures may be larger after shrinking with `resize`
+        render_pass.set_viewport(0.0, 0.0, self.width as f32, self.height as f32, 0.0, 1.0);
+
+        render_pass
+    }
+
+    /// Copies the pixel for the readback, call it after the picking pass
+    ///
+    /// Returns false if the pixel is outside or a previous pick is still being read back.
+    pub fn request_pick(&mut self, encoder: &mut wgpu::CommandEncoder, x: u32, y: u32) -> bool {
+        if self.state != ReadbackState::Idle || x >= self.width || y >= self.height {
+            return false;
+        }
+
+        let origin = wgpu::Origin3d { x, y, z: 0 };
+        let pixel = wgpu::Extent3d {
+            width: 1,
+            height: 1,
+            depth_or_array_layers: 1,
+        };
+        for (texture, offset) in [
+            (&self.id_texture, 0),
+            (&self.depth_value_texture, ROW_ALIGNMENT),
+        ] {
+            encoder.copy_texture_to_buffer(
+                wgpu::TexelCopyTextureInfo {
+                    texture,
+                    mip_level: 0,
+                    origin,
+                    aspect: wgpu::TextureAspect::All,
+                },
+                wgpu::TexelCopyBufferInfo {
+                    buffer: &self.readback_buffer,
+                    layout: wgpu::TexelCopyBufferLayout {
+                        offset,
+                        bytes_per_row: None,
+                        rows_per_image: None,
+                    },
+                },
+                pixel,
+            );
+        }
+
+        self.state = ReadbackState::Copied;
+        self.requested = [x, y, self.width, self.height];
+        true
+    }
+
+    /// Starts reading back the picked pixel, call it after the frame was submitted
+    pub fn map(&mut self) {
+        if self.state != ReadbackState::Copied {
+            return;
+        }
+
+        self.map_result.store(MAP_PENDING, Ordering::SeqCst);
+        let map_result = self.map_result.clone();
+        self.readback_buffer
+            .slice(..)
+            .map_async(wgpu::MapMode::Read, move |result| {
+                let value = if result.is_ok() { MAP_DONE } else { MAP_FAILED };
+                map_result.store(value, Ordering::SeqCst);
+            });
+        self.state = ReadbackState::Mapping;
+    }
+
+    /// Returns the picked pixel once it has been read back, without waiting for the GPU
+    pub fn get_result(&mut self, device: &wgpu::Device) -> Option<PickResult> {
+        if self.state != ReadbackState::Mapping {
+            return None;
+        }
+        let _res = device.poll(wgpu::PollType::Poll);
+
+        let map_result = self.map_result.load(Ordering::SeqCst);
+        if map_result == MAP_PENDING {
+            return None;
+        }
+
+        let result = if map_result == MAP_DONE {
+            let (ids, depth) = {
+                let view = self.readback_buffer.slice(..).get_mapped_range();
+                let ids: [u32; 2] = bytemuck::pod_read_unaligned(&view[0..8]);
+                let depth_bits: u32 = bytemuck::pod_read_unaligned(
+                    &view[ROW_ALIGNMENT as usize..ROW_ALIGNMENT as usize + 4],
+                );
+                (ids, f32::from_bits(depth_bits))
+            };
+            self.readback_buffer.unmap();
+
+            let [x, y, width, height] = self.requested;
+            Some(PickResult::from_readback(x, y, width, height, ids, depth))
+        } else {
+            None
+        };
+        self.state = ReadbackState::Idle;
+
+        result
+    }
+}
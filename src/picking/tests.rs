@@ -0,0 +1,120 @@
+//! Unit tests
+
+use cgmath::*;
+
+use super::*;
+use crate::golden_image::test_renderer;
+use crate::shape::{self, MeshDataInterface};
+use crate::vertex_color_shader;
+use crate::vertex_heightmap_shader;
+use crate::vertex_texture_shader;
+use crate::wgpu_renderer::camera::{Camera, Projection};
+use crate::wgpu_renderer::WgpuRendererInterface;
+
+const WIDTH: u32 = 64;
+const HEIGHT: u32 = 48;
+
+#[test]
+fn world_position_from_pixel_and_depth() {
+    let view_proj = Matrix4::from_nonuniform_scale(0.5, 0.25, 1.0);
+    let result = PickResult::from_readback(47, 23, 64, 48, [3, 1], 0.5);
+
+    let hit = result.hit.unwrap();
+    assert_eq!((hit.mesh_id, hit.instance_id), (3, 1));
+
+    let position = result.world_position(&view_proj).unwrap();
+    assert!((position.x - (47.5 / 32.0 - 1.0) / 0.5).abs() < 1e-5);
+    assert!((position.y - (1.0 - 23.5 / 24.0) / 0.25).abs() < 1e-5);
+    assert!((position.z - 0.5).abs() < 1e-5);
+
+    assert_eq!(
+        PickResult::from_readback(0, 0, 64, 48, [NO_ID, NO_ID], 1.0).hit,
+        None
+    );
+}
+
+#[test]
+fn picks_instance_under_pixel() {
+    let Some(mut renderer) = test_renderer(WIDTH, HEIGHT) else {
+        return;
+    };
+    let device = renderer.device().clone();
+
+    let camera_bind_group_layout = vertex_color_shader::CameraBindGroupLayout::new(&device);
+    let picking_id_bind_group_layout = PickingIdBindGroupLayout::new(&device);
+    let pipeline = vertex_color_shader::Pipeline::new_picking(
+        &device,
+        &camera_bind_group_layout,
+        &picking_id_bind_group_layout,
+    );
+
+    // The picking variants of the other shaders compile as well
+    let texture_bind_group_layout = vertex_texture_shader::TextureBindGroupLayout::new(&device);
+    vertex_texture_shader::Pipeline::new_picking(
+        &device,
+        &camera_bind_group_layout,
+        &texture_bind_group_layout,
+        &picking_id_bind_group_layout,
+    );
+    vertex_heightmap_shader::Pipeline::new_picking(
+        &device,
+        &camera_bind_group_layout,
+        &texture_bind_group_layout,
+        &vertex_heightmap_shader::HeightmapBindGroupLayout::new(&device),
+        &picking_id_bind_group_layout,
+    );
+
+    let camera = Camera::new((-5.0, 0.0, 0.0), Deg(0.0), Deg(0.0));
+    let projection = Projection::new(WIDTH, HEIGHT, Deg(45.0), 0.1, 100.0);
+    let view_proj = projection.calc_matrix() * camera.calc_matrix();
+    let mut camera_uniform = vertex_color_shader::CameraUniform::new();
+    camera_uniform.update_view_proj(&camera, &projection);
+    let mut camera_buffer =
+        vertex_color_shader::CameraUniformBuffer::new(&device, &camera_bind_group_layout);
+    camera_buffer.update(renderer.queue(), camera_uniform);
+
+    let centers = [Vector3::new(0.0, -1.2, 0.0), Vector3::new(0.0, 1.2, 0.0)];
+    let ids = [10, 11];
+    let instances = [0, 1].map(|index| {
+        vertex_color_shader::Instance::new(centers[index], Quaternion::one()).with_id(ids[index])
+    });
+    let sphere = shape::UVSphere::new(1.0, 16);
+    let mesh = vertex_color_shader::Mesh::from_shape(
+        &device,
+        sphere.triangles(),
+        &Vector3::new(1.0, 1.0, 1.0),
+        &instances,
+    );
+    let picking_id = PickingId::new(&device, &picking_id_bind_group_layout, 7);
+
+    // The pixel in front of the center of the second instance
+    let clip = view_proj * Point3::from_vec(centers[1]).to_homogeneous();
+    let x = ((clip.x / clip.w + 1.0) * 0.5 * WIDTH as f32) as u32;
+    let y = ((1.0 - clip.y / clip.w) * 0.5 * HEIGHT as f32) as u32;
+
+    // the larger textures are kept, only the used part is drawn and picked
+    let mut picking_target = PickingTarget::new(&device, WIDTH * 2, HEIGHT * 2);
+    picking_target.resize(&device, WIDTH, HEIGHT);
+    for (pixel, expected_hit) in [((x, y), true), ((0, 0), false)] {
+        let mut frame = renderer.begin_frame().unwrap();
+        {
+            let mut render_pass = picking_target.begin_pass(frame.encoder());
+            pipeline.draw_picking(&mut render_pass, &camera_buffer, &picking_id, &mesh);
+        }
+        assert!(picking_target.request_pick(frame.encoder(), pixel.0, pixel.1));
+        frame.present();
+        picking_target.map();
+
+        device.poll(wgpu::PollType::wait_indefinitely()).unwrap();
+        let result = picking_target.get_result(&device).unwrap();
+        assert_eq!(result.hit.is_some(), expected_hit);
+
+        if let Some(hit) = result.hit {
+            assert_eq!((hit.mesh_id, hit.instance_id), (7, ids[1]));
+
+            let position = result.world_position(&view_proj).unwrap();
+            let distance = position.distance(Point3::from_vec(centers[1]));
+            assert!((distance - 1.0).abs() < 0.1, "{position:?}");
+        }
+    }
+}
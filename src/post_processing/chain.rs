@@ -0,0 +1,630 @@
+//! Owns the intermediate targets and pipelines and turns the list of effects into render passes
+//!
+
+use std::collections::HashMap;
+
+use wgpu::util::DeviceExt;
+
+use super::super::wgpu_renderer::msaa_texture::MsaaTexture;
+use super::super::wgpu_renderer::surface_format;
+use super::super::wgsl_preprocessor;
+use super::color_lut::ColorLut;
+use super::effect_uniform::EffectUniform;
+use super::Effect;
+use super::Tonemapping;
+
+const COLOR_LUT_SIZE: u32 = 16;
+
+/// A color target that is read by the following pass
+struct Target {
+    view: wgpu::TextureView,
+    width: u32,
+    height: u32,
+}
+
+impl Target {
+    fn new(
+        device: &wgpu::Device,
+        format: wgpu::TextureFormat,
+        width: u32,
+        height: u32,
+        label: &str,
+    ) -> Self {
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some(label),
+            size: wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+        Self {
+            view,
+            width,
+            height,
+        }
+    }
+}
+
+/// The scene, a second full size target to alternate with and two for the bloom
+struct Targets {
+    scene: Target,
+    /// The multisampled target of a HDR scene, others use the one of the frame
+    scene_msaa: Option<MsaaTexture>,
+    ping: Target,
+    bloom: [Target; 2],
+}
+
+impl Targets {
+    fn new(
+        device: &wgpu::Device,
+        format: wgpu::TextureFormat,
+        width: u32,
+        height: u32,
+        msaa_sample_count: u32,
+    ) -> Self {
+        let bloom_width = (width / 2).max(1);
+        let bloom_height = (height / 2).max(1);
+
+        Self {
+            scene: Target::new(device, format, width, height, "post_processing_scene"),
+            scene_msaa: MsaaTexture::create_optional(
+                device,
+                format,
+                width,
+                height,
+                msaa_sample_count,
+            ),
+            ping: Target::new(device, format, width, height, "post_processing_ping"),
+            bloom: [
+                Target::new(device, format, bloom_width, bloom_height, "bloom_0"),
+                Target::new(device, format, bloom_width, bloom_height, "bloom_1"),
+            ],
+        }
+    }
+}
+
+#[derive(Clone)]
+struct Step {
+    pipeline: wgpu::RenderPipeline,
+    bind_group: wgpu::BindGroup,
+    /// None for the last step, that writes into the frame
+    target: Option<wgpu::TextureView>,
+}
+
+/// The render passes of the chain, recorded by `Frame::apply_post_processing`
+#[derive(Clone)]
+pub struct PostProcessingPasses {
+    scene_format: wgpu::TextureFormat,
+    scene_view: wgpu::TextureView,
+    scene_msaa_view: Option<wgpu::TextureView>,
+    steps: Vec<Step>,
+}
+
+impl PostProcessingPasses {
+    pub fn scene_format(&self) -> wgpu::TextureFormat {
+        self.scene_format
+    }
+
+    /// The texture the scene is rendered or resolved into
+    pub fn scene_view(&self) -> &wgpu::TextureView {
+        &self.scene_view
+    }
+
+    /// The multisampled scene target, if it differs from the one of the frame
+    pub fn scene_msaa_view(&self) -> Option<&wgpu::TextureView> {
+        self.scene_msaa_view.as_ref()
+    }
+
+    /// Writes the result into the output, through the multisampled target if there is one
+    ///
+    /// Like this, later passes that load the multisampled target draw on top of the result.
+    pub fn record(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        output_view: &wgpu::TextureView,
+        msaa_view: Option<&wgpu::TextureView>,
+    ) {
+        for step in &self.steps {
+            let (view, resolve_target) = match (&step.target, msaa_view) {
+                (Some(target), _) => (target, None),
+                (None, Some(msaa_view)) => (msaa_view, Some(output_view)),
+                (None, None) => (output_view, None),
+            };
+
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Post Processing Render Pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view,
+                    depth_slice: None,
+                    resolve_target,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: None,
+                timestamp_writes: None,
+                occlusion_query_set: None,
+                multiview_mask: None,
+            });
+
+            render_pass.set_pipeline(&step.pipeline);
+            render_pass.set_bind_group(0, &step.bind_group, &[]);
+            render_pass.draw(0..3, 0..1);
+        }
+    }
+}
+
+/// Applies an ordered list of effects to the scene before it is written to the surface
+///
+/// Owned by the renderers, which resize it together with their other targets.
+/// Without effects the scene is rendered directly into the frame, unless the scene
+/// is a HDR target. Then it is always tonemapped as last step.
+pub struct PostProcessing {
+    effects: Vec<Effect>,
+    exposure: f32,
+    tonemapping: Tonemapping,
+    /// The scene is rendered into a `HDR_FORMAT` target and tonemapped
+    hdr: bool,
+    scene_format: wgpu::TextureFormat,
+    format: wgpu::TextureFormat,
+    sample_count: u32,
+    width: u32,
+    height: u32,
+
+    shader: wgpu::ShaderModule,
+    bind_group_layout: wgpu::BindGroupLayout,
+    pipeline_layout: wgpu::PipelineLayout,
+    sampler: wgpu::Sampler,
+    /// By fragment entry point, sample count and target format
+    pipelines: HashMap<(&'static str, u32, wgpu::TextureFormat), wgpu::RenderPipeline>,
+    color_lut: ColorLut,
+
+    targets: Option<Targets>,
+    /// Rebuilt after the effects or the targets changed
+    passes: Option<PostProcessingPasses>,
+    /// The effects the passes were built with, to notice changes through `effects_mut`
+    passes_effects: Vec<Effect>,
+}
+
+impl PostProcessing {
+    /// The output format has to be the one of the frame, the sample count the one of its MSAA target
+    ///
+    /// With `hdr` the scene is drawn in `HDR_FORMAT`, see `scene_format`.
+    pub fn new(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        format: wgpu::TextureFormat,
+        hdr: bool,
+        sample_count: u32,
+        width: u32,
+        height: u32,
+    ) -> Self {
+        let shader_code = wgsl_preprocessor::preprocess(include_str!("shader.wgsl"));
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Post Processing Shader"),
+            source: wgpu::ShaderSource::Wgsl(shader_code.as_str().into()),
+        });
+
+        let texture_entry = |binding| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Texture {
+                multisampled: false,
+                view_dimension: wgpu::TextureViewDimension::D2,
+                sample_type: wgpu::TextureSampleType::Float { filterable: true },
+            },
+            count: None,
+        };
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("post_processing_bind_group_layout"),
+            entries: &[
+                texture_entry(0),
+                texture_entry(1),
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 3,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+            ],
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Post Processing Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            immediate_size: 0,
+        });
+
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+
+        let color_lut = ColorLut::new(device, queue, &ColorLut::identity_image(COLOR_LUT_SIZE))
+            .expect("identity lookup table is valid");
+
+        Self {
+            effects: Vec::new(),
+            exposure: 1.0,
+            tonemapping: Tonemapping::default(),
+            hdr,
+            scene_format: surface_format::scene_format(format, hdr),
+            format,
+            sample_count,
+            width,
+            height,
+            shader,
+            bind_group_layout,
+            pipeline_layout,
+            sampler,
+            pipelines: HashMap::new(),
+            color_lut,
+            targets: None,
+            passes: None,
+            passes_effects: Vec::new(),
+        }
+    }
+
+    /// The format the scene pipelines have to target
+    pub fn scene_format(&self) -> wgpu::TextureFormat {
+        self.scene_format
+    }
+
+    /// True if the scene is rendered into a HDR target and tonemapped
+    pub fn is_hdr(&self) -> bool {
+        self.hdr
+    }
+
+    pub fn exposure(&self) -> f32 {
+        self.exposure
+    }
+
+    /// Scales the HDR colors before tonemapping, 1.0 keeps them
+    pub fn set_exposure(&mut self, exposure: f32) {
+        self.exposure = exposure;
+        self.passes = None;
+    }
+
+    pub fn tonemapping(&self) -> Tonemapping {
+        self.tonemapping
+    }
+
+    pub fn set_tonemapping(&mut self, tonemapping: Tonemapping) {
+        self.tonemapping = tonemapping;
+        self.passes = None;
+    }
+
+    pub fn effects(&self) -> &[Effect] {
+        &self.effects
+    }
+
+    /// The effects are applied in order, an empty list disables post-processing
+    pub fn set_effects(&mut self, effects: Vec<Effect>) {
+        self.effects = effects;
+    }
+
+    /// To change the parameters of the effects at runtime
+    ///
+    /// The passes are only rebuilt if the effects differ from the ones of the last frame.
+    pub fn effects_mut(&mut self) -> &mut Vec<Effect> {
+        &mut self.effects
+    }
+
+    /// Replaces the lookup table used by `Effect::ColorGrading`, see `ColorLut` for the layout
+    pub fn set_color_grading_lut(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        lut_image: &image::RgbaImage,
+    ) -> anyhow::Result<()> {
+        self.color_lut = ColorLut::new(device, queue, lut_image)?;
+        self.passes = None;
+        Ok(())
+    }
+
+    pub fn resize(&mut self, width: u32, height: u32) {
+        if width > 0 && height > 0 && (width, height) != (self.width, self.height) {
+            self.width = width;
+            self.height = height;
+            self.targets = None;
+            self.passes = None;
+        }
+    }
+
+    /// The passes for the next frame, None if there is nothing to do
+    pub fn passes(&mut self, device: &wgpu::Device) -> Option<PostProcessingPasses> {
+        if self.effects.is_empty() && !self.is_hdr() {
+            self.targets = None;
+            return None;
+        }
+
+        if self.effects != self.passes_effects {
+            self.passes = None;
+        }
+
+        if self.passes.is_none() {
+            if self.targets.is_none() {
+                // The frame's multisampled target can be used, if it has the same format
+                let msaa_sample_count = if self.is_hdr() { self.sample_count } else { 1 };
+                self.targets = Some(Targets::new(
+                    device,
+                    self.scene_format,
+                    self.width,
+                    self.height,
+                    msaa_sample_count,
+                ));
+            }
+            self.passes = Some(self.create_passes(device));
+        }
+
+        self.passes.clone()
+    }
+
+    fn create_passes(&mut self, device: &wgpu::Device) -> PostProcessingPasses {
+        let effects = self.effects.clone();
+        self.passes_effects = effects.clone();
+        let mut steps = Vec::new();
+        // Alternates between the scene and the ping target
+        let mut input_is_scene = true;
+
+        for (index, effect) in effects.iter().enumerate() {
+            let is_last = index + 1 == effects.len() && !self.is_hdr();
+            let targets = self.targets.as_ref().expect("targets are created before");
+            let (input, output) = if input_is_scene {
+                (&targets.scene, &targets.ping)
+            } else {
+                (&targets.ping, &targets.scene)
+            };
+            let output = (!is_last).then(|| output.view.clone());
+            let input_view = input.view.clone();
+            let input_uniform = |params| EffectUniform::new(params, input.width, input.height);
+
+            match *effect {
+                Effect::Fxaa => {
+                    let uniform = input_uniform([0.0; 4]);
+                    steps.push(self.create_step(
+                        device,
+                        "fs_fxaa",
+                        &input_view,
+                        &input_view,
+                        uniform,
+                        output,
+                    ));
+                }
+                Effect::Bloom {
+                    threshold,
+                    intensity,
+                } => {
+                    let [bloom_0, bloom_1] = &targets.bloom;
+                    let bloom_uniform =
+                        |params| EffectUniform::new(params, bloom_0.width, bloom_0.height);
+                    let (bloom_0, bloom_1) = (bloom_0.view.clone(), bloom_1.view.clone());
+
+                    let threshold_uniform = input_uniform([threshold, 0.0, 0.0, 0.0]);
+                    let composite_uniform = input_uniform([intensity, 0.0, 0.0, 0.0]);
+                    let horizontal_uniform = bloom_uniform([1.0, 0.0, 0.0, 0.0]);
+                    let vertical_uniform = bloom_uniform([0.0, 1.0, 0.0, 0.0]);
+
+                    steps.push(self.create_step(
+                        device,
+                        "fs_bloom_threshold",
+                        &input_view,
+                        &input_view,
+                        threshold_uniform,
+                        Some(bloom_0.clone()),
+                    ));
+                    steps.push(self.create_step(
+                        device,
+                        "fs_blur",
+                        &bloom_0,
+                        &bloom_0,
+                        horizontal_uniform,
+                        Some(bloom_1.clone()),
+                    ));
+                    steps.push(self.create_step(
+                        device,
+                        "fs_blur",
+                        &bloom_1,
+                        &bloom_1,
+                        vertical_uniform,
+                        Some(bloom_0.clone()),
+                    ));
+                    steps.push(self.create_step(
+                        device,
+                        "fs_bloom_composite",
+                        &input_view,
+                        &bloom_0,
+                        composite_uniform,
+                        output,
+                    ));
+                }
+                Effect::Vignette { intensity, radius } => {
+                    let uniform = input_uniform([intensity, radius, 0.0, 0.0]);
+                    steps.push(self.create_step(
+                        device,
+                        "fs_vignette",
+                        &input_view,
+                        &input_view,
+                        uniform,
+                        output,
+                    ));
+                }
+                Effect::ColorGrading { intensity } => {
+                    let uniform = input_uniform([intensity, self.color_lut.size as f32, 0.0, 0.0]);
+                    let lut_view = self.color_lut.view.clone();
+                    steps.push(self.create_step(
+                        device,
+                        "fs_color_grading",
+                        &input_view,
+                        &lut_view,
+                        uniform,
+                        output,
+                    ));
+                }
+            }
+
+            input_is_scene = !input_is_scene;
+        }
+
+        if self.is_hdr() {
+            let targets = self.targets.as_ref().expect("targets are created before");
+            let input = if input_is_scene {
+                &targets.scene
+            } else {
+                &targets.ping
+            };
+            let input_view = input.view.clone();
+            let uniform = EffectUniform::new(
+                [self.exposure, self.tonemapping.shader_index(), 0.0, 0.0],
+                input.width,
+                input.height,
+            );
+            steps.push(self.create_step(
+                device,
+                "fs_tonemap",
+                &input_view,
+                &input_view,
+                uniform,
+                None,
+            ));
+        }
+
+        let targets = self.targets.as_ref().expect("targets are created before");
+        PostProcessingPasses {
+            scene_format: self.scene_format,
+            scene_view: targets.scene.view.clone(),
+            scene_msaa_view: targets
+                .scene_msaa
+                .as_ref()
+                .map(|scene_msaa| scene_msaa.view.clone()),
+            steps,
+        }
+    }
+
+    fn create_step(
+        &mut self,
+        device: &wgpu::Device,
+        entry_point: &'static str,
+        input: &wgpu::TextureView,
+        second: &wgpu::TextureView,
+        uniform: EffectUniform,
+        target: Option<wgpu::TextureView>,
+    ) -> Step {
+        let uniform_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Effect Uniform Buffer"),
+            contents: bytemuck::cast_slice(&[uniform]),
+            usage: wgpu::BufferUsages::UNIFORM,
+        });
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("post_processing_bind_group"),
+            layout: &self.bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(input),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::TextureView(second),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: wgpu::BindingResource::Sampler(&self.sampler),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 3,
+                    resource: uniform_buffer.as_entire_binding(),
+                },
+            ],
+        });
+
+        // The last step writes into the multisampled target of the frame
+        let (sample_count, format) = if target.is_some() {
+            (1, self.scene_format)
+        } else {
+            (self.sample_count, self.format)
+        };
+
+        Step {
+            pipeline: self.pipeline(device, entry_point, sample_count, format),
+            bind_group,
+            target,
+        }
+    }
+
+    fn pipeline(
+        &mut self,
+        device: &wgpu::Device,
+        entry_point: &'static str,
+        sample_count: u32,
+        format: wgpu::TextureFormat,
+    ) -> wgpu::RenderPipeline {
+        let key = (entry_point, sample_count, format);
+        if let Some(pipeline) = self.pipelines.get(&key) {
+            return pipeline.clone();
+        }
+
+        let constants = surface_format::surface_constants(format);
+
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some(entry_point),
+            layout: Some(&self.pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &self.shader,
+                entry_point: Some("vs_fullscreen"),
+                buffers: &[],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &self.shader,
+                entry_point: Some(entry_point),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format,
+                    blend: None,
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: wgpu::PipelineCompilationOptions {
+                    constants: &constants,
+                    ..Default::default()
+                },
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState {
+                count: sample_count,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+            multiview_mask: None,
+            cache: None,
+        });
+
+        self.pipelines.insert(key, pipeline.clone());
+        pipeline
+    }
+}
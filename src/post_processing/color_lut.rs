@@ -0,0 +1,76 @@
+//! Lookup table for color grading, stored as a row of slices
+//!
+//! A table with N entries per channel is an image of N * N x N pixels. Red increases
+//! to the right within a slice, green downwards and blue from slice to slice.
+//! Such images are exported by most image editors as "strip" or "unwrapped" LUTs.
+//!
+
+use anyhow::*;
+
+pub struct ColorLut {
+    pub size: u32,
+    pub view: wgpu::TextureView,
+}
+
+impl ColorLut {
+    /// Maps every color to itself
+    pub fn identity_image(size: u32) -> image::RgbaImage {
+        let scale = |value: u32| (value * 255 / (size - 1)) as u8;
+
+        image::RgbaImage::from_fn(size * size, size, |x, y| {
+            image::Rgba([scale(x % size), scale(y), scale(x / size), 255])
+        })
+    }
+
+    pub fn new(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        lut_image: &image::RgbaImage,
+    ) -> Result<Self> {
+        let (width, height) = lut_image.dimensions();
+        if height < 2 || width != height * height {
+            bail!(
+                "Lookup table of {}x{} pixels is not a row of square slices",
+                width,
+                height
+            );
+        }
+
+        let size = wgpu::Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        };
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("color_lut"),
+            size,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            // The values are sRGB encoded, but interpolated like in image editors
+            format: wgpu::TextureFormat::Rgba8Unorm,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        });
+
+        queue.write_texture(
+            wgpu::TexelCopyTextureInfo {
+                texture: &texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            lut_image,
+            wgpu::TexelCopyBufferLayout {
+                offset: 0,
+                bytes_per_row: Some(4 * width),
+                rows_per_image: Some(height),
+            },
+            size,
+        );
+
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+        Ok(Self { size: height, view })
+    }
+}
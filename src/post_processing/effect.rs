@@ -0,0 +1,19 @@
+//! The effects that can be applied after the scene was rendered
+//!
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Effect {
+    /// Fast approximate anti-aliasing, also smooths edges inside of textures
+    Fxaa,
+    /// Adds a glow around areas brighter than the threshold, e.g. 0.8 and 0.6
+    Bloom { threshold: f32, intensity: f32 },
+    /// Darkens the corners, e.g. 0.5 and 0.3
+    ///
+    /// The radius is the distance from the center where the darkening starts,
+    /// relative to the size of the target.
+    Vignette { intensity: f32, radius: f32 },
+    /// Maps the colors with the lookup table of `PostProcessing::set_color_grading_lut`
+    ///
+    /// An intensity of 0.0 keeps the original colors, 1.0 uses the mapped ones.
+    ColorGrading { intensity: f32 },
+}
@@ -0,0 +1,22 @@
+//! The parameters of a single post-processing pass
+//!
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Default, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct EffectUniform {
+    /// Depends on the pass, e.g. threshold or intensity
+    pub params: [f32; 4],
+    /// Size of a texel of the input texture in texture coordinates
+    pub texel_size: [f32; 2],
+    pub _padding: [f32; 2],
+}
+
+impl EffectUniform {
+    pub fn new(params: [f32; 4], width: u32, height: u32) -> Self {
+        Self {
+            params,
+            texel_size: [1.0 / width as f32, 1.0 / height as f32],
+            _padding: [0.0; 2],
+        }
+    }
+}
@@ -0,0 +1,24 @@
+//! Full-screen effects applied after the scene was drawn, e.g. FXAA, bloom, vignette and color grading
+//!
+//! While effects are set, the scene passes of a frame are rendered into an intermediate
+//! texture. `Frame::apply_post_processing` then runs the effects in order and writes the
+//! result into the frame, passes after it like a GUI overlay are not affected by the effects.
+//! `Frame::present` applies them if that was not done before.
+//!
+//! With `RendererConfig::with_hdr` the scene is rendered into a `Rgba16Float` target,
+//! the effects work on the HDR colors and the result is tonemapped to the surface format.
+//!
+
+mod chain;
+mod color_lut;
+mod effect;
+mod effect_uniform;
+mod tonemapping;
+
+#[cfg(test)]
+mod tests;
+
+pub use chain::{PostProcessing, PostProcessingPasses};
+pub use color_lut::ColorLut;
+pub use effect::Effect;
+pub use tonemapping::Tonemapping;
@@ -0,0 +1,212 @@
+// Full-screen effects, every entry point reads the input texture and writes one pass
+//
+// The effects work on linear colors. Their targets have the format of the surface, if that
+// is not sRGB the colors are decoded when they are read and encoded when they are written.
+#include "fullscreen"
+#include "surface_color"
+
+// Matches post_processing::effect_uniform::EffectUniform
+struct EffectUniform {
+    params: vec4<f32>,
+    texel_size: vec2<f32>,
+};
+
+@group(0) @binding(0)
+var t_input: texture_2d<f32>;
+// The blurred bright areas for the bloom composite, the lookup table for color grading
+@group(0) @binding(1)
+var t_second: texture_2d<f32>;
+@group(0) @binding(2)
+var s_linear: sampler;
+@group(0) @binding(3)
+var<uniform> effect: EffectUniform;
+
+fn sample_input(tex_coords: vec2<f32>) -> vec4<f32> {
+    return from_surface_color(textureSampleLevel(t_input, s_linear, tex_coords, 0.0));
+}
+
+fn luma(color: vec3<f32>) -> f32 {
+    return dot(color, vec3<f32>(0.299, 0.587, 0.114));
+}
+
+// Fast approximate anti-aliasing, blurs along edges with a high luma contrast
+@fragment
+fn fs_fxaa(in: FullscreenOutput) -> @location(0) vec4<f32> {
+    let uv = in.tex_coords;
+    let texel = effect.texel_size;
+
+    let center = sample_input(uv);
+    let luma_m = luma(center.rgb);
+    let luma_nw = luma(sample_input(uv + vec2<f32>(-1.0, -1.0) * texel).rgb);
+    let luma_ne = luma(sample_input(uv + vec2<f32>(1.0, -1.0) * texel).rgb);
+    let luma_sw = luma(sample_input(uv + vec2<f32>(-1.0, 1.0) * texel).rgb);
+    let luma_se = luma(sample_input(uv + vec2<f32>(1.0, 1.0) * texel).rgb);
+
+    let luma_min = min(luma_m, min(min(luma_nw, luma_ne), min(luma_sw, luma_se)));
+    let luma_max = max(luma_m, max(max(luma_nw, luma_ne), max(luma_sw, luma_se)));
+    if (luma_max - luma_min < max(0.0312, luma_max * 0.125)) {
+        return to_surface_color(center);
+    }
+
+    var dir = vec2<f32>(
+        -((luma_nw + luma_ne) - (luma_sw + luma_se)),
+        (luma_nw + luma_sw) - (luma_ne + luma_se),
+    );
+    let dir_reduce = max((luma_nw + luma_ne + luma_sw + luma_se) * 0.25 * 0.125, 1.0 / 128.0);
+    let rcp_dir_min = 1.0 / (min(abs(dir.x), abs(dir.y)) + dir_reduce);
+    dir = clamp(dir * rcp_dir_min, vec2<f32>(-8.0), vec2<f32>(8.0)) * texel;
+
+    let rgb_a = 0.5 * (sample_input(uv + dir * (1.0 / 3.0 - 0.5)).rgb
+        + sample_input(uv + dir * (2.0 / 3.0 - 0.5)).rgb);
+    let rgb_b = rgb_a * 0.5 + 0.25 * (sample_input(uv - dir * 0.5).rgb
+        + sample_input(uv + dir * 0.5).rgb);
+
+    let luma_b = luma(rgb_b);
+    if (luma_b < luma_min || luma_b > luma_max) {
+        return to_surface_color(vec4<f32>(rgb_a, center.a));
+    }
+    return to_surface_color(vec4<f32>(rgb_b, center.a));
+}
+
+// Keeps the areas brighter than params.x, with a soft transition
+@fragment
+fn fs_bloom_threshold(in: FullscreenOutput) -> @location(0) vec4<f32> {
+    let color = sample_input(in.tex_coords).rgb;
+    let brightness = max(color.r, max(color.g, color.b));
+    let threshold = effect.params.x;
+    let knee = threshold * 0.5;
+
+    var soft = clamp(brightness - threshold + knee, 0.0, 2.0 * knee);
+    soft = soft * soft / (4.0 * knee + 0.00001);
+    let contribution = max(soft, brightness - threshold) / max(brightness, 0.00001);
+
+    return to_surface_color(vec4<f32>(color * contribution, 1.0));
+}
+
+// Gaussian blur along params.xy, 9 taps with linear filtering
+@fragment
+fn fs_blur(in: FullscreenOutput) -> @location(0) vec4<f32> {
+    let uv = in.tex_coords;
+    let step = effect.params.xy * effect.texel_size;
+
+    var color = sample_input(uv).rgb * 0.2270270270;
+    color += (sample_input(uv + step * 1.3846153846).rgb
+        + sample_input(uv - step * 1.3846153846).rgb) * 0.3162162162;
+    color += (sample_input(uv + step * 3.2307692308).rgb
+        + sample_input(uv - step * 3.2307692308).rgb) * 0.0702702703;
+
+    return to_surface_color(vec4<f32>(color, 1.0));
+}
+
+// Adds the blurred bright areas, scaled by params.x
+@fragment
+fn fs_bloom_composite(in: FullscreenOutput) -> @location(0) vec4<f32> {
+    let color = sample_input(in.tex_coords);
+    let bloom = from_surface_color(textureSampleLevel(t_second, s_linear, in.tex_coords, 0.0)).rgb;
+
+    return to_surface_color(vec4<f32>(color.rgb + bloom * effect.params.x, color.a));
+}
+
+// Darkens by params.x towards the corners, starting at the distance params.y from the center
+@fragment
+fn fs_vignette(in: FullscreenOutput) -> @location(0) vec4<f32> {
+    let color = sample_input(in.tex_coords);
+    let distance = length(in.tex_coords - vec2<f32>(0.5));
+    let radius = effect.params.y;
+    let factor = 1.0 - effect.params.x * smoothstep(radius, radius + 0.4, distance);
+
+    return to_surface_color(vec4<f32>(color.rgb * factor, color.a));
+}
+
+// Looks up a color in a lookup table with params.y entries per channel
+//
+// The table is stored as a row of slices, one per blue value.
+fn lut_lookup(color: vec3<f32>) -> vec3<f32> {
+    let size = effect.params.y;
+    let c = clamp(color, vec3<f32>(0.0), vec3<f32>(1.0));
+
+    let slice = c.b * (size - 1.0);
+    let slice_0 = floor(slice);
+    let slice_1 = min(slice_0 + 1.0, size - 1.0);
+
+    let x = (c.r * (size - 1.0) + 0.5) / (size * size);
+    let y = (c.g * (size - 1.0) + 0.5) / size;
+    let a = textureSampleLevel(t_second, s_linear, vec2<f32>(x + slice_0 / size, y), 0.0).rgb;
+    let b = textureSampleLevel(t_second, s_linear, vec2<f32>(x + slice_1 / size, y), 0.0).rgb;
+
+    return mix(a, b, slice - slice_0);
+}
+
+// Maps the colors with the lookup table, blended with the original by params.x
+@fragment
+fn fs_color_grading(in: FullscreenOutput) -> @location(0) vec4<f32> {
+    let color = sample_input(in.tex_coords);
+
+    // Lookup tables are indexed with sRGB encoded colors
+    let graded = srgb_to_linear(lut_lookup(linear_to_srgb(color.rgb)));
+
+    return to_surface_color(vec4<f32>(mix(color.rgb, graded, effect.params.x), color.a));
+}
+
+fn tonemap_reinhard(color: vec3<f32>) -> vec3<f32> {
+    return color / (1.0 + color);
+}
+
+fn tonemap_aces(color: vec3<f32>) -> vec3<f32> {
+    let mapped = (color * (2.51 * color + 0.03)) / (color * (2.43 * color + 0.59) + 0.14);
+    return clamp(mapped, vec3<f32>(0.0), vec3<f32>(1.0));
+}
+
+// Polynomial approximation of the AgX base contrast curve
+fn agx_contrast(x: vec3<f32>) -> vec3<f32> {
+    let x2 = x * x;
+    let x4 = x2 * x2;
+    return 15.5 * x4 * x2 - 40.14 * x4 * x + 31.96 * x4 - 6.868 * x2 * x + 0.4298 * x2
+        + 0.1191 * x - 0.00232;
+}
+
+fn tonemap_agx(color: vec3<f32>) -> vec3<f32> {
+    let agx_inset = mat3x3<f32>(
+        0.842479062253094, 0.0423282422610123, 0.0423756549057051,
+        0.0784335999999992, 0.878468636469772, 0.0784336,
+        0.0792237451477643, 0.0791661274605434, 0.879142973793104,
+    );
+    let agx_outset = mat3x3<f32>(
+        1.19687900512017, -0.0528968517574562, -0.0529716355144438,
+        -0.0980208811401368, 1.15190312990417, -0.0980434501171241,
+        -0.0990297440797205, -0.0989611768448433, 1.15107367264116,
+    );
+    let min_ev = -12.47393;
+    let max_ev = 4.026069;
+
+    var value = agx_inset * color;
+    value = clamp(log2(max(value, vec3<f32>(1e-10))), vec3<f32>(min_ev), vec3<f32>(max_ev));
+    value = agx_contrast((value - min_ev) / (max_ev - min_ev));
+    value = agx_outset * value;
+
+    // The curve results in display encoded values
+    return pow(clamp(value, vec3<f32>(0.0), vec3<f32>(1.0)), vec3<f32>(2.2));
+}
+
+// Scales the HDR colors by the exposure params.x and maps them with the operator params.y
+@fragment
+fn fs_tonemap(in: FullscreenOutput) -> @location(0) vec4<f32> {
+    // The HDR target is linear, only the output may need the encoding
+    let color = textureSampleLevel(t_input, s_linear, in.tex_coords, 0.0);
+    let exposed = max(color.rgb * effect.params.x, vec3<f32>(0.0));
+
+    var mapped: vec3<f32>;
+    switch u32(effect.params.y) {
+        case 1u: {
+            mapped = tonemap_aces(exposed);
+        }
+        case 2u: {
+            mapped = tonemap_agx(exposed);
+        }
+        default: {
+            mapped = tonemap_reinhard(exposed);
+        }
+    }
+
+    return to_surface_color(vec4<f32>(mapped, clamp(color.a, 0.0, 1.0)));
+}
@@ -0,0 +1,120 @@
+//! Unit tests
+
+use crate::golden_image::test_renderer_with_config;
+use crate::golden_image::test_support::{
+    assert_golden, assert_golden_with_tolerance, render_sphere, HEIGHT, WIDTH,
+};
+use crate::golden_image::Tolerance;
+use crate::post_processing;
+use crate::wgpu_renderer::{OffscreenRenderer, RendererConfig, WgpuRendererInterface};
+
+/// The last effect writes through the multisampled target, so the overlay is drawn on top of it
+#[test]
+fn post_processing_effects() {
+    // The effects work on linear colors, a surface without sRGB encoding gives the same image
+    // up to the edges, where the samples are resolved and rounded in the encoded space
+    for format in [
+        OffscreenRenderer::DEFAULT_FORMAT,
+        wgpu::TextureFormat::Rgba8Unorm,
+    ] {
+        let Some(mut renderer) = test_renderer_with_config(
+            WIDTH,
+            HEIGHT,
+            format,
+            &RendererConfig::default().with_sample_count(4),
+        ) else {
+            return;
+        };
+
+        // Swaps red and blue
+        let mut lut_image = post_processing::ColorLut::identity_image(16);
+        for pixel in lut_image.pixels_mut() {
+            pixel.0.swap(0, 2);
+        }
+        let device = renderer.device().clone();
+        let queue = renderer.queue().clone();
+        let post_processing = renderer.post_processing().unwrap();
+        post_processing
+            .set_color_grading_lut(&device, &queue, &lut_image)
+            .unwrap();
+        post_processing.set_effects(vec![
+            post_processing::Effect::Fxaa,
+            post_processing::Effect::Bloom {
+                threshold: 0.6,
+                intensity: 0.8,
+            },
+            post_processing::Effect::Vignette {
+                intensity: 0.8,
+                radius: 0.3,
+            },
+            post_processing::Effect::ColorGrading { intensity: 1.0 },
+        ]);
+        let image = render_sphere(&mut renderer);
+
+        let tolerance = if format.is_srgb() {
+            Tolerance::default()
+        } else {
+            Tolerance {
+                per_channel: 20,
+                ..Tolerance::default()
+            }
+        };
+        assert_golden_with_tolerance(
+            &format!("post_processing_effects_msaa{}", renderer.sample_count()),
+            &image,
+            tolerance,
+        );
+    }
+}
+
+/// The scene pipelines target the HDR buffer, which is tonemapped into the multisampled target
+#[test]
+fn hdr_tonemapping() {
+    let Some(mut renderer) = test_renderer_with_config(
+        WIDTH,
+        HEIGHT,
+        OffscreenRenderer::DEFAULT_FORMAT,
+        &RendererConfig::default()
+            .with_sample_count(4)
+            .with_hdr(true),
+    ) else {
+        return;
+    };
+    assert_eq!(renderer.scene_format(), wgpu::TextureFormat::Rgba16Float);
+
+    for (name, tonemapping) in [
+        ("reinhard", post_processing::Tonemapping::Reinhard),
+        ("aces", post_processing::Tonemapping::Aces),
+        ("agx", post_processing::Tonemapping::AgX),
+    ] {
+        let post_processing = renderer.post_processing().unwrap();
+        post_processing.set_exposure(3.0);
+        post_processing.set_tonemapping(tonemapping);
+        let image = render_sphere(&mut renderer);
+
+        assert_golden(
+            &format!("hdr_tonemapping_{name}_msaa{}", renderer.sample_count()),
+            &image,
+        );
+    }
+}
+
+#[test]
+fn hdr_with_float_output_format() {
+    for hdr in [false, true] {
+        let Some(mut renderer) = test_renderer_with_config(
+            16,
+            16,
+            wgpu::TextureFormat::Rgba16Float,
+            &RendererConfig::default().with_hdr(hdr),
+        ) else {
+            return;
+        };
+
+        // the scene and the output have the same format, the tonemapping is still applied
+        let device = renderer.device().clone();
+        let post_processing = renderer.post_processing().unwrap();
+        assert_eq!(post_processing.is_hdr(), hdr);
+        assert_eq!(post_processing.passes(&device).is_some(), hdr);
+    }
+}
@@ -0,0 +1,24 @@
+//! Operators that map the colors of the HDR scene target to the range of the surface
+//!
+
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub enum Tonemapping {
+    /// Compresses every channel with c / (1 + c), keeps the colors but looks flat
+    Reinhard,
+    /// The filmic curve of the Academy Color Encoding System, in the fit by Krzysztof Narkowicz
+    #[default]
+    Aces,
+    /// Desaturates very bright colors towards white, as in Blender
+    AgX,
+}
+
+impl Tonemapping {
+    /// The value selecting the operator in the shader
+    pub(super) fn shader_index(&self) -> f32 {
+        match self {
+            Tonemapping::Reinhard => 0.0,
+            Tonemapping::Aces => 1.0,
+            Tonemapping::AgX => 2.0,
+        }
+    }
+}
@@ -0,0 +1,15 @@
+//! Ray casting on the CPU, e.g. to find the object under the cursor without a GPU readback
+//!
+//! `Ray::from_cursor` unprojects a cursor position with the matrices of the camera and the
+//! projection. The ray can be intersected with triangles, boxes and spheres, or with the
+//! triangles of a shape drawn with several instances by `Ray::cast_triangles`.
+//!
+
+mod ray;
+mod ray_hit;
+
+#[cfg(test)]
+mod tests;
+
+pub use ray::Ray;
+pub use ray_hit::RayHit;
@@ -0,0 +1,180 @@
+//! A ray with an origin and a direction, and its intersections
+//!
+
+use cgmath::*;
+
+use super::super::culling::{Aabb, BoundingSphere};
+use super::super::shape::MeshDataTriangles;
+use super::super::vertex_color_shader::Instance;
+use super::super::wgpu_renderer::camera::{Camera, Projection};
+use super::RayHit;
+
+/// Determinants below this are treated as rays parallel to a triangle
+const PARALLEL_EPSILON: f32 = 1e-8;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Ray {
+    pub origin: Point3<f32>,
+    pub direction: Vector3<f32>,
+}
+
+impl Ray {
+    pub fn new(origin: Point3<f32>, direction: Vector3<f32>) -> Self {
+        Self { origin, direction }
+    }
+
+    /// The ray from the camera through a cursor position in pixels, from the top left corner
+    ///
+    /// The size of the window is taken from the projection, the direction is normalized.
+    pub fn from_cursor(camera: &Camera, projection: &Projection, x: f32, y: f32) -> Option<Self> {
+        let view_proj = projection.calc_matrix() * camera.calc_matrix();
+        let ndc_x = x / projection.width as f32 * 2.0 - 1.0;
+        let ndc_y = 1.0 - y / projection.height as f32 * 2.0;
+
+        Self::from_ndc(&view_proj, camera.position, ndc_x, ndc_y)
+    }
+
+    /// The ray from the eye through a point in normalized device coordinates
+    pub fn from_ndc(
+        view_proj: &Matrix4<f32>,
+        eye: Point3<f32>,
+        ndc_x: f32,
+        ndc_y: f32,
+    ) -> Option<Self> {
+        // Any depth gives a point on the line through the eye, the near plane lies in front of it
+        let point = view_proj.invert()? * Vector4::new(ndc_x, ndc_y, 0.0, 1.0);
+        if point.w.abs() < f32::EPSILON {
+            return None;
+        }
+        let direction = (Point3::from_homogeneous(point) - eye).normalize();
+
+        Some(Self::new(eye, direction))
+    }
+
+    pub fn at(&self, distance: f32) -> Point3<f32> {
+        self.origin + self.direction * distance
+    }
+
+    /// Distances stay the same, as the direction is not normalized again
+    pub fn transform(&self, matrix: &Matrix4<f32>) -> Self {
+        Self {
+            origin: matrix.transform_point(self.origin),
+            direction: matrix.transform_vector(self.direction),
+        }
+    }
+
+    /// Möller-Trumbore, hits both sides of the triangle
+    pub fn intersect_triangle(
+        &self,
+        a: Point3<f32>,
+        b: Point3<f32>,
+        c: Point3<f32>,
+    ) -> Option<f32> {
+        let edge_1 = b - a;
+        let edge_2 = c - a;
+        let p = self.direction.cross(edge_2);
+        let determinant = edge_1.dot(p);
+        if determinant.abs() < PARALLEL_EPSILON {
+            return None;
+        }
+
+        let inverse_determinant = 1.0 / determinant;
+        let to_origin = self.origin - a;
+        let u = to_origin.dot(p) * inverse_determinant;
+        if !(0.0..=1.0).contains(&u) {
+            return None;
+        }
+
+        let q = to_origin.cross(edge_1);
+        let v = self.direction.dot(q) * inverse_determinant;
+        if v < 0.0 || u + v > 1.0 {
+            return None;
+        }
+
+        let distance = edge_2.dot(q) * inverse_determinant;
+        (distance >= 0.0).then_some(distance)
+    }
+
+    /// The distance where the ray enters the box, 0.0 if it starts inside
+    pub fn intersect_aabb(&self, aabb: &Aabb) -> Option<f32> {
+        let mut near = 0.0_f32;
+        let mut far = f32::INFINITY;
+
+        for axis in 0..3 {
+            let inverse = 1.0 / self.direction[axis];
+            let t_0 = (aabb.min[axis] - self.origin[axis]) * inverse;
+            let t_1 = (aabb.max[axis] - self.origin[axis]) * inverse;
+
+            // NaN if the ray lies in a side of the box, the comparisons keep the bounds then
+            near = near.max(t_0.min(t_1));
+            far = far.min(t_0.max(t_1));
+        }
+
+        (near <= far).then_some(near)
+    }
+
+    /// The distance where the ray enters the sphere, 0.0 if it starts inside
+    pub fn intersect_sphere(&self, sphere: &BoundingSphere) -> Option<f32> {
+        let to_center = sphere.center - self.origin;
+        let a = self.direction.magnitude2();
+        let half_b = self.direction.dot(to_center);
+        let c = to_center.magnitude2() - sphere.radius * sphere.radius;
+
+        let discriminant = half_b * half_b - a * c;
+        if discriminant < 0.0 || a == 0.0 {
+            return None;
+        }
+
+        let root = discriminant.sqrt();
+        let far = (half_b + root) / a;
+        if far < 0.0 {
+            return None;
+        }
+        Some(((half_b - root) / a).max(0.0))
+    }
+
+    /// The closest hit of the triangles of a shape, drawn with the instances
+    pub fn cast_triangles(
+        &self,
+        triangles: &MeshDataTriangles,
+        instances: &[Instance],
+    ) -> Option<RayHit> {
+        let bounding_sphere = triangles.bounding_sphere()?;
+        let positions = &triangles.positions;
+        let mut closest: Option<RayHit> = None;
+
+        for (instance_index, instance) in instances.iter().enumerate() {
+            let model_matrix = instance.model_matrix();
+            let max_distance = closest.map_or(f32::INFINITY, |hit| hit.distance);
+            match self.intersect_sphere(&bounding_sphere.transform(&model_matrix)) {
+                Some(distance) if distance <= max_distance => {}
+                _ => continue,
+            }
+
+            // In the space of the shape, where the distances are the same as in the world
+            let Some(inverse) = model_matrix.invert() else {
+                continue;
+            };
+            let local_ray = self.transform(&inverse);
+
+            for (triangle, indices) in triangles.indices.chunks_exact(3).enumerate() {
+                let [a, b, c] =
+                    [0, 1, 2].map(|corner| Point3::from_vec(positions[indices[corner] as usize]));
+                let Some(distance) = local_ray.intersect_triangle(a, b, c) else {
+                    continue;
+                };
+
+                if closest.is_none_or(|hit| distance < hit.distance) {
+                    closest = Some(RayHit {
+                        distance,
+                        position: self.at(distance),
+                        triangle_index: triangle * 3,
+                        instance_index,
+                    });
+                }
+            }
+        }
+
+        closest
+    }
+}
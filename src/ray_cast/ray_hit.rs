@@ -0,0 +1,14 @@
+//! The closest intersection of a ray with the triangles of a shape
+//!
+
+use cgmath::*;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct RayHit {
+    /// Along the ray, in lengths of its direction
+    pub distance: f32,
+    pub position: Point3<f32>,
+    /// The index of the first of the three indices of the triangle
+    pub triangle_index: usize,
+    pub instance_index: usize,
+}
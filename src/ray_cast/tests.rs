@@ -0,0 +1,86 @@
+//! Unit tests
+
+use cgmath::*;
+
+use super::super::culling::{Aabb, BoundingSphere};
+use super::super::shape::MeshDataTriangles;
+use super::super::vertex_color_shader::Instance;
+use super::super::wgpu_renderer::camera::{Camera, Projection};
+use super::*;
+
+#[test]
+fn cursor_ray_follows_the_view() {
+    // At the origin, looking along the x axis with y to the left
+    let camera = Camera::new((0.0, 0.0, 0.0), Deg(0.0), Deg(0.0));
+    let projection = Projection::new(800, 600, Deg(45.0), 0.1, 100.0);
+
+    let ray = Ray::from_cursor(&camera, &projection, 400.0, 300.0).unwrap();
+    assert_eq!(ray.origin, camera.position);
+    assert!((ray.direction - Vector3::unit_x()).magnitude() < 1e-4);
+
+    let ray = Ray::from_cursor(&camera, &projection, 0.0, 300.0).unwrap();
+    assert!(ray.direction.x > 0.0 && ray.direction.y > 0.0);
+    assert!(ray.direction.z.abs() < 1e-4);
+}
+
+#[test]
+fn intersects_primitives() {
+    let ray = Ray::new(Point3::new(-5.0, 0.0, 0.0), Vector3::unit_x());
+
+    let distance = ray.intersect_triangle(
+        Point3::new(0.0, -1.0, -1.0),
+        Point3::new(0.0, 1.0, -1.0),
+        Point3::new(0.0, 0.0, 1.0),
+    );
+    assert_eq!(distance, Some(5.0));
+    let behind = ray.intersect_triangle(
+        Point3::new(-6.0, -1.0, -1.0),
+        Point3::new(-6.0, 1.0, -1.0),
+        Point3::new(-6.0, 0.0, 1.0),
+    );
+    assert_eq!(behind, None);
+
+    let aabb = Aabb::new(Point3::new(-1.0, -1.0, -1.0), Point3::new(1.0, 1.0, 1.0));
+    assert_eq!(ray.intersect_aabb(&aabb), Some(4.0));
+    let aabb = Aabb::new(Point3::new(-1.0, 2.0, -1.0), Point3::new(1.0, 3.0, 1.0));
+    assert_eq!(ray.intersect_aabb(&aabb), None);
+
+    let sphere = BoundingSphere::new(Point3::new(0.0, 0.0, 0.0), 2.0);
+    assert_eq!(ray.intersect_sphere(&sphere), Some(3.0));
+    let sphere = BoundingSphere::new(Point3::new(-5.0, 0.0, 0.0), 1.0);
+    assert_eq!(ray.intersect_sphere(&sphere), Some(0.0));
+}
+
+#[test]
+fn casts_the_closest_instance() {
+    // A quad in the yz plane
+    let quad = MeshDataTriangles {
+        positions: vec![
+            Vector3::new(0.0, -1.0, -1.0),
+            Vector3::new(0.0, 1.0, -1.0),
+            Vector3::new(0.0, 1.0, 1.0),
+            Vector3::new(0.0, -1.0, 1.0),
+        ],
+        normals: vec![Vector3::unit_x(); 4],
+        indices: vec![0, 1, 2, 0, 2, 3],
+    };
+    let instances = [
+        Instance::new(Vector3::new(8.0, 0.0, 0.0), Quaternion::one()),
+        Instance::new(Vector3::new(4.0, 0.0, 0.0), Quaternion::one())
+            .with_scale(Vector3::new(1.0, 0.5, 0.5)),
+        Instance::new(Vector3::new(2.0, 5.0, 0.0), Quaternion::one()),
+    ];
+
+    let ray = Ray::new(Point3::new(0.0, 0.0, 0.25), Vector3::unit_x());
+    let hit = ray.cast_triangles(&quad, &instances).unwrap();
+    assert_eq!(hit.instance_index, 1);
+    assert_eq!(hit.triangle_index, 3);
+    assert!((hit.distance - 4.0).abs() < 1e-5);
+    assert!((hit.position - Point3::new(4.0, 0.0, 0.25)).magnitude() < 1e-5);
+
+    // Outside of the scaled quad
+    let ray = Ray::new(Point3::new(0.0, 0.75, -0.5), Vector3::unit_x());
+    let hit = ray.cast_triangles(&quad, &instances).unwrap();
+    assert_eq!(hit.instance_index, 0);
+    assert_eq!(hit.triangle_index, 0);
+}
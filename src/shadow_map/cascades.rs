@@ -0,0 +1,85 @@
+//! Splits the view frustum and fits the projections of the light around the parts
+//!
+
+use cgmath::*;
+
+use super::super::wgpu_renderer::camera::projection::OPENGL_TO_WGPU_MATRIX;
+use super::super::wgpu_renderer::camera::{Camera, Projection};
+
+/// Depth the light projection reaches beyond a cascade towards the light, in cascade radii
+///
+/// Objects outside of the view frustum still cast shadows into it.
+const CASTER_MARGIN: f32 = 4.0;
+
+/// The far distances of the cascades
+///
+/// Lambda blends between uniform (0.0) and logarithmic (1.0) splits.
+pub fn cascade_splits(near: f32, far: f32, count: usize, lambda: f32) -> Vec<f32> {
+    (1..=count)
+        .map(|index| {
+            let part = index as f32 / count as f32;
+            let logarithmic = near * (far / near).powf(part);
+            let uniform = near + (far - near) * part;
+            lambda * logarithmic + (1.0 - lambda) * uniform
+        })
+        .collect()
+}
+
+/// The corners of the view frustum between two distances from the camera
+pub fn frustum_corners(
+    camera: &Camera,
+    projection: &Projection,
+    near: f32,
+    far: f32,
+) -> [Point3<f32>; 8] {
+    let view_proj = OPENGL_TO_WGPU_MATRIX
+        * perspective(projection.fovy, projection.aspect(), near, far)
+        * camera.calc_matrix();
+    let inverse = view_proj.invert().unwrap_or(Matrix4::identity());
+
+    let mut corners = [Point3::origin(); 8];
+    for (index, corner) in corners.iter_mut().enumerate() {
+        let ndc = Vector4::new(
+            if index & 1 == 0 { -1.0 } else { 1.0 },
+            if index & 2 == 0 { -1.0 } else { 1.0 },
+            if index & 4 == 0 { 0.0 } else { 1.0 },
+            1.0,
+        );
+        let world = inverse * ndc;
+        *corner = Point3::from_homogeneous(world);
+    }
+    corners
+}
+
+/// An orthographic projection along the light, that contains a sphere around the corners
+///
+/// The sphere and the snapping to texels keep the shadows from flickering when the camera moves.
+pub fn light_view_proj(
+    corners: &[Point3<f32>; 8],
+    light_direction: Vector3<f32>,
+    resolution: u32,
+) -> Matrix4<f32> {
+    let center = Point3::centroid(corners);
+    let radius = corners
+        .iter()
+        .map(|corner| corner.distance(center))
+        .fold(0.0, f32::max);
+    let radius = (radius * 16.0).ceil() / 16.0;
+
+    let light_direction = light_direction.normalize();
+    let up = if light_direction.z.abs() > 0.99 {
+        Vector3::unit_y()
+    } else {
+        Vector3::unit_z()
+    };
+    let view = Matrix4::look_to_rh(Point3::origin(), light_direction, up);
+
+    let center = view.transform_point(center);
+    let texel = 2.0 * radius / resolution as f32;
+    let x = (center.x / texel).floor() * texel;
+    let y = (center.y / texel).floor() * texel;
+    let near = -center.z - radius * (1.0 + CASTER_MARGIN);
+    let far = -center.z + radius;
+
+    OPENGL_TO_WGPU_MATRIX * ortho(x - radius, x + radius, y - radius, y + radius, near, far) * view
+}
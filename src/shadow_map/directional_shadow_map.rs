@@ -0,0 +1,211 @@
+//! Owns the cascades of the shadow map and the uniforms to render and look them up
+//!
+
+use cgmath::*;
+use wgpu::util::DeviceExt;
+
+use super::super::vertex_color_shader::{
+    CameraBindGroupLayout, CameraUniform, CameraUniformBuffer,
+};
+use super::super::wgpu_renderer::camera::{Camera, Projection};
+use super::super::wgpu_renderer::depth_texture::DepthTexture;
+use super::cascades;
+use super::ShadowBindGroupLayout;
+use super::ShadowUniform;
+use super::MAX_CASCADES;
+
+pub struct DirectionalShadowMap {
+    depth_texture: DepthTexture,
+    cascade_views: Vec<wgpu::TextureView>,
+    cascade_cameras: Vec<CameraUniformBuffer>,
+    shadow_buffer: wgpu::Buffer,
+    shadow_bind_group: wgpu::BindGroup,
+
+    resolution: u32,
+    max_distance: f32,
+    split_lambda: f32,
+    depth_bias: f32,
+    ambient: f32,
+}
+
+impl DirectionalShadowMap {
+    /// Every cascade has `resolution` x `resolution` texels, at most `MAX_CASCADES` are used
+    pub fn new(
+        device: &wgpu::Device,
+        camera_bind_group_layout: &CameraBindGroupLayout,
+        shadow_bind_group_layout: &ShadowBindGroupLayout,
+        resolution: u32,
+        cascade_count: usize,
+    ) -> Self {
+        let cascade_count = cascade_count.clamp(1, MAX_CASCADES);
+
+        // The GL backend can not create array views of textures with a single layer
+        let layers = cascade_count.max(2) as u32;
+        let depth_texture = DepthTexture::new_array(device, resolution, layers, "shadow_map");
+        let cascade_views = (0..cascade_count as u32)
+            .map(|layer| depth_texture.layer_view(layer))
+            .collect();
+        let cascade_cameras = (0..cascade_count)
+            .map(|_| CameraUniformBuffer::new(device, camera_bind_group_layout))
+            .collect();
+
+        let shadow_uniform = ShadowUniform {
+            light_view_proj: [Matrix4::identity().into(); MAX_CASCADES],
+            split_distances: [0.0; MAX_CASCADES],
+            camera_position: [0.0; 4],
+            camera_direction: [0.0; 4],
+            params: [0.0; 4],
+        };
+        let shadow_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Shadow Buffer"),
+            contents: bytemuck::cast_slice(&[shadow_uniform]),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+
+        let shadow_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: shadow_bind_group_layout.get(),
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: shadow_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::TextureView(&depth_texture.view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: wgpu::BindingResource::Sampler(&depth_texture.sampler),
+                },
+            ],
+            label: Some("shadow_bind_group"),
+        });
+
+        Self {
+            depth_texture,
+            cascade_views,
+            cascade_cameras,
+            shadow_buffer,
+            shadow_bind_group,
+
+            resolution,
+            max_distance: 100.0,
+            split_lambda: 0.75,
+            depth_bias: 0.002,
+            ambient: 0.3,
+        }
+    }
+
+    /// All cascades as layers of one texture, e.g. to show them for debugging
+    pub fn depth_texture(&self) -> &DepthTexture {
+        &self.depth_texture
+    }
+
+    pub fn cascade_count(&self) -> usize {
+        self.cascade_views.len()
+    }
+
+    /// Distance from the camera up to which shadows are drawn, limited by the far plane
+    pub fn set_max_distance(&mut self, max_distance: f32) {
+        self.max_distance = max_distance;
+    }
+
+    /// Blends between uniform (0.0) and logarithmic (1.0) cascade splits
+    pub fn set_split_lambda(&mut self, split_lambda: f32) {
+        self.split_lambda = split_lambda;
+    }
+
+    /// Subtracted from the depth before the comparison, against shadow acne
+    pub fn set_depth_bias(&mut self, depth_bias: f32) {
+        self.depth_bias = depth_bias;
+    }
+
+    /// Brightness in shadow, 0.0 is black
+    pub fn set_ambient(&mut self, ambient: f32) {
+        self.ambient = ambient;
+    }
+
+    /// Fits the cascades to the view of the camera, call it when the camera or the light moved
+    ///
+    /// The light direction points from the light towards the scene.
+    pub fn update(
+        &mut self,
+        queue: &wgpu::Queue,
+        light_direction: Vector3<f32>,
+        camera: &Camera,
+        projection: &Projection,
+    ) {
+        let near = projection.znear();
+        let far = self.max_distance.min(projection.zfar());
+        let splits = cascades::cascade_splits(near, far, self.cascade_count(), self.split_lambda);
+
+        let mut shadow_uniform = ShadowUniform {
+            light_view_proj: [Matrix4::identity().into(); MAX_CASCADES],
+            split_distances: [0.0; MAX_CASCADES],
+            camera_position: camera.position.to_homogeneous().into(),
+            camera_direction: camera.get_view_direction().extend(0.0).into(),
+            params: [
+                self.cascade_count() as f32,
+                1.0 / self.resolution as f32,
+                self.depth_bias,
+                self.ambient,
+            ],
+        };
+
+        let mut split_near = near;
+        for (index, split_far) in splits.into_iter().enumerate() {
+            let corners = cascades::frustum_corners(camera, projection, split_near, split_far);
+            let light_view_proj =
+                cascades::light_view_proj(&corners, light_direction, self.resolution);
+
+            let camera_uniform = CameraUniform {
+                view_position: (-light_direction).extend(0.0).into(),
+                view_proj: light_view_proj.into(),
+            };
+            self.cascade_cameras[index].update(queue, camera_uniform);
+
+            shadow_uniform.light_view_proj[index] = light_view_proj.into();
+            shadow_uniform.split_distances[index] = split_far;
+            split_near = split_far;
+        }
+
+        queue.write_buffer(
+            &self.shadow_buffer,
+            0,
+            bytemuck::cast_slice(&[shadow_uniform]),
+        );
+    }
+
+    /// The camera of the light, to bind when drawing the shadow casters into a cascade
+    pub fn cascade_camera(&self, cascade: usize) -> &CameraUniformBuffer {
+        &self.cascade_cameras[cascade]
+    }
+
+    /// Begins a depth-only pass rendering the shadow casters into one cascade
+    pub fn begin_cascade_pass<'a>(
+        &self,
+        encoder: &'a mut wgpu::CommandEncoder,
+        cascade: usize,
+    ) -> wgpu::RenderPass<'a> {
+        encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Shadow Cascade Render Pass"),
+            color_attachments: &[],
+            depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                view: &self.cascade_views[cascade],
+                depth_ops: Some(wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(1.0),
+                    store: wgpu::StoreOp::Store,
+                }),
+                stencil_ops: None,
+            }),
+            timestamp_writes: None,
+            occlusion_query_set: None,
+            multiview_mask: None,
+        })
+    }
+
+    /// Binds the cascades for the lookup in the shadowed pipelines
+    pub fn bind<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>, group: u32) {
+        render_pass.set_bind_group(group, &self.shadow_bind_group, &[]);
+    }
+}
@@ -0,0 +1,27 @@
+//! Shadows of a directional light, e.g. the sun, with cascaded shadow maps
+//!
+//! The view frustum of the camera is split by distance into up to four cascades, each
+//! rendered from the light into one layer of a depth texture. Near cascades cover a small
+//! area with a high resolution, far ones the rest of large terrains.
+//!
+//! A frame first renders the shadow casters into every cascade with the depth-only variants
+//! of the pipelines, e.g. `vertex_color_shader::Pipeline::new_depth_only`, bound to
+//! `cascade_camera`. The scene is then drawn with the shadowed variants, which look up the
+//! cascades with percentage-closer filtering. `vertex_color_shader::Pipeline::new_lit_shadowed`
+//! blocks the first directional light of the lighting where it is in shadow.
+//!
+
+mod cascades;
+mod directional_shadow_map;
+mod shadow_bind_group_layout;
+mod shadow_uniform;
+
+#[cfg(test)]
+mod tests;
+
+pub use directional_shadow_map::DirectionalShadowMap;
+pub use shadow_bind_group_layout::ShadowBindGroupLayout;
+pub use shadow_uniform::ShadowUniform;
+
+/// Limited by the size of the array in the shader
+pub const MAX_CASCADES: usize = 4;
@@ -0,0 +1,51 @@
+//! A bind group to look up the shadow map in the shadowed pipelines
+//!
+
+pub struct ShadowBindGroupLayout {
+    shadow_bind_group_layout: wgpu::BindGroupLayout,
+}
+
+impl ShadowBindGroupLayout {
+    pub fn new(device: &wgpu::Device) -> Self {
+        let shadow_bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                entries: &[
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Buffer {
+                            ty: wgpu::BufferBindingType::Uniform,
+                            has_dynamic_offset: false,
+                            min_binding_size: None,
+                        },
+                        count: None,
+                    },
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 1,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Texture {
+                            multisampled: false,
+                            view_dimension: wgpu::TextureViewDimension::D2Array,
+                            sample_type: wgpu::TextureSampleType::Depth,
+                        },
+                        count: None,
+                    },
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 2,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Comparison),
+                        count: None,
+                    },
+                ],
+                label: Some("shadow_bind_group_layout"),
+            });
+
+        Self {
+            shadow_bind_group_layout,
+        }
+    }
+
+    pub fn get(&self) -> &wgpu::BindGroupLayout {
+        &self.shadow_bind_group_layout
+    }
+}
@@ -0,0 +1,16 @@
+//! The ShadowUniform struct used in the shader
+//!
+
+use super::MAX_CASCADES;
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct ShadowUniform {
+    pub light_view_proj: [[[f32; 4]; 4]; MAX_CASCADES],
+    /// Depth in the view of the camera up to which each cascade is used
+    pub split_distances: [f32; MAX_CASCADES],
+    pub camera_position: [f32; 4],
+    pub camera_direction: [f32; 4],
+    /// Number of cascades, size of a texel, depth bias and ambient light
+    pub params: [f32; 4],
+}
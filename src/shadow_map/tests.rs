@@ -0,0 +1,170 @@
+//! Unit tests
+
+use cgmath::*;
+
+use super::super::golden_image::test_renderer;
+use super::super::golden_image::test_support::{
+    assert_golden, finish, identity_instance, perspective_camera, CLEAR_COLOR, HEIGHT, WIDTH,
+};
+use super::super::shape::{self, MeshDataInterface};
+use super::super::vertex_color_shader;
+use super::super::wgpu_renderer::camera::{Camera, Projection};
+use super::super::wgpu_renderer::{FramePass, OffscreenRenderer, WgpuRendererInterface};
+use super::cascades::*;
+use super::*;
+
+#[test]
+fn splits_increase_up_to_far() {
+    for lambda in [0.0, 0.5, 1.0] {
+        let splits = cascade_splits(0.1, 100.0, 4, lambda);
+
+        assert_eq!(splits.len(), 4);
+        assert!(splits.windows(2).all(|pair| pair[0] < pair[1]));
+        assert!((splits[3] - 100.0).abs() < 1e-3);
+    }
+
+    let uniform = cascade_splits(1.0, 101.0, 4, 0.0);
+    assert!((uniform[0] - 26.0).abs() < 1e-3);
+}
+
+#[test]
+fn light_projection_contains_cascade() {
+    let camera = Camera::new((1.0, -3.0, 2.0), Deg(60.0), Deg(-20.0));
+    let projection = Projection::new(800, 600, Deg(45.0), 0.1, 100.0);
+    let light_direction = Vector3::new(0.3, 0.5, -1.0);
+
+    let corners = frustum_corners(&camera, &projection, 5.0, 20.0);
+    let light_view_proj = light_view_proj(&corners, light_direction, 1024);
+
+    for corner in corners {
+        let clip = light_view_proj * corner.to_homogeneous();
+        let ndc = clip.truncate() / clip.w;
+        assert!(ndc.x.abs() <= 1.0 && ndc.y.abs() <= 1.0, "{ndc:?}");
+        assert!((0.0..=1.0).contains(&ndc.z), "{ndc:?}");
+    }
+}
+
+#[test]
+fn shadow_map_cascades() {
+    let Some(mut renderer) = test_renderer(WIDTH, HEIGHT) else {
+        return;
+    };
+    let actual = render_shadowed_scene(&mut renderer, false);
+
+    assert_golden("shadow_map_cascades", &actual);
+}
+
+#[test]
+fn shadow_map_lit_cascades() {
+    let Some(mut renderer) = test_renderer(WIDTH, HEIGHT) else {
+        return;
+    };
+    let actual = render_shadowed_scene(&mut renderer, true);
+
+    assert_golden("shadow_map_lit_cascades", &actual);
+}
+
+/// A sphere above the ground, drawn with `new_lit_shadowed` if lit
+fn render_shadowed_scene(renderer: &mut OffscreenRenderer, lit: bool) -> image::RgbaImage {
+    let surface_format = renderer.surface_format();
+    let sample_count = renderer.sample_count();
+
+    let camera_bind_group_layout =
+        vertex_color_shader::CameraBindGroupLayout::new(renderer.device());
+    let light_bind_group_layout = vertex_color_shader::LightBindGroupLayout::new(renderer.device());
+    let shadow_bind_group_layout = ShadowBindGroupLayout::new(renderer.device());
+    let depth_pipeline =
+        vertex_color_shader::Pipeline::new_depth_only(renderer.device(), &camera_bind_group_layout);
+    let pipeline = if lit {
+        vertex_color_shader::Pipeline::new_lit_shadowed(
+            renderer.device(),
+            &camera_bind_group_layout,
+            &light_bind_group_layout,
+            &shadow_bind_group_layout,
+            surface_format,
+            sample_count,
+        )
+    } else {
+        vertex_color_shader::Pipeline::new_shadowed(
+            renderer.device(),
+            &camera_bind_group_layout,
+            &shadow_bind_group_layout,
+            surface_format,
+            sample_count,
+        )
+    };
+
+    let camera = Camera::new((-6.0, -2.0, 3.0), cgmath::Deg(-26.6), cgmath::Deg(16.6));
+    let projection = Projection::new(WIDTH, HEIGHT, cgmath::Deg(45.0), 0.1, 100.0);
+    let camera_buffer = perspective_camera(renderer, &camera_bind_group_layout, &camera);
+
+    let light_direction = cgmath::Vector3::new(0.5, 0.8, -1.0);
+    let mut shadow_map = DirectionalShadowMap::new(
+        renderer.device(),
+        &camera_bind_group_layout,
+        &shadow_bind_group_layout,
+        512,
+        2,
+    );
+    shadow_map.set_max_distance(20.0);
+    shadow_map.update(renderer.queue(), light_direction, &camera, &projection);
+
+    let mut light_uniform = vertex_color_shader::LightUniform::new();
+    light_uniform.add_directional_light(light_direction, cgmath::Vector3::new(1.0, 1.0, 1.0), 0.8);
+    let mut light_buffer =
+        vertex_color_shader::LightUniformBuffer::new(renderer.device(), &light_bind_group_layout);
+    light_buffer.update(renderer.queue(), light_uniform);
+
+    let ground_vertices = [[-8.0, -8.0], [8.0, -8.0], [8.0, 8.0], [-8.0, 8.0]].map(|[x, y]| {
+        vertex_color_shader::Vertex {
+            position: [x, y, -1.0],
+        }
+    });
+    let ground_colors = [vertex_color_shader::Color {
+        color: [0.8, 0.8, 0.7],
+    }; 4];
+    let ground_normals = [vertex_color_shader::Normal {
+        normal: [0.0, 0.0, 1.0],
+    }; 4];
+    let ground = vertex_color_shader::Mesh::new_with_normals(
+        renderer.device(),
+        &ground_vertices,
+        &ground_colors,
+        &ground_normals,
+        &[0, 1, 2, 0, 2, 3],
+        &[identity_instance()],
+    );
+
+    let sphere = shape::UVSphere::new(1.0, 16);
+    let sphere = vertex_color_shader::Mesh::from_shape(
+        renderer.device(),
+        sphere.triangles(),
+        &cgmath::Vector3::new(0.9, 0.2, 0.1),
+        &[identity_instance()],
+    );
+
+    let mut frame = renderer.begin_frame().unwrap();
+    for cascade in 0..shadow_map.cascade_count() {
+        let mut render_pass = shadow_map.begin_cascade_pass(frame.encoder(), cascade);
+        let cascade_camera = shadow_map.cascade_camera(cascade);
+        depth_pipeline.draw(&mut render_pass, cascade_camera, &ground);
+        depth_pipeline.draw(&mut render_pass, cascade_camera, &sphere);
+    }
+    {
+        let mut render_pass = frame.begin_pass(&FramePass::scene(CLEAR_COLOR));
+        for mesh in [&ground, &sphere] {
+            if lit {
+                pipeline.draw_lit_shadowed(
+                    &mut render_pass,
+                    &camera_buffer,
+                    &light_buffer,
+                    &shadow_map,
+                    mesh,
+                );
+            } else {
+                pipeline.draw_shadowed(&mut render_pass, &camera_buffer, &shadow_map, mesh);
+            }
+        }
+    }
+    finish(renderer, frame)
+}
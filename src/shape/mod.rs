@@ -8,7 +8,9 @@ pub use grid::Grid;
 pub use square::Square;
 pub use uv_sphere::UVSphere;
 
-use cgmath::InnerSpace;
+use cgmath::{EuclideanSpace, InnerSpace, Point3};
+
+use crate::culling::{Aabb, BoundingSphere};
 
 pub trait MeshDataInterface {
     fn points(&self) -> &MeshDataPoints;
@@ -29,7 +31,41 @@ pub struct MeshDataTriangles {
     pub indices: Vec<u32>,
 }
 
+impl MeshDataTriangles {
+    pub fn bounding_box(&self) -> Option<Aabb> {
+        Aabb::from_points(
+            self.positions
+                .iter()
+                .map(|position| Point3::from_vec(*position)),
+        )
+    }
+
+    pub fn bounding_sphere(&self) -> Option<BoundingSphere> {
+        BoundingSphere::from_points(
+            self.positions
+                .iter()
+                .map(|position| Point3::from_vec(*position)),
+        )
+    }
+}
+
 impl MeshDataPoints {
+    pub fn bounding_box(&self) -> Option<Aabb> {
+        Aabb::from_points(
+            self.positions
+                .iter()
+                .map(|position| Point3::from_vec(*position)),
+        )
+    }
+
+    pub fn bounding_sphere(&self) -> Option<BoundingSphere> {
+        BoundingSphere::from_points(
+            self.positions
+                .iter()
+                .map(|position| Point3::from_vec(*position)),
+        )
+    }
+
     pub fn triangulate_grid(&self, n: usize) -> MeshDataTriangles {
         let positions = &self.positions;
         let normals = &self.normals;
@@ -0,0 +1,174 @@
+//! A cube texture with six square faces, sampled with a direction
+//!
+
+use anyhow::*;
+
+use super::equirectangular;
+use super::CubemapBindGroupLayout;
+
+pub struct Cubemap {
+    pub texture: wgpu::Texture,
+    pub view: wgpu::TextureView,
+    pub sampler: wgpu::Sampler,
+    pub bind_group: wgpu::BindGroup,
+    size: u32,
+}
+
+impl Cubemap {
+    pub const FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8UnormSrgb;
+
+    /// The faces in the order +X, -X, +Y, -Y, +Z, -Z of the world, Z points up
+    ///
+    /// The images are oriented like the faces of a cube texture, all need the same square size.
+    pub fn from_images(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        cubemap_bind_group_layout: &CubemapBindGroupLayout,
+        faces: [&image::RgbaImage; 6],
+    ) -> Result<Self> {
+        let (width, height) = faces[0].dimensions();
+        if width == 0 || width != height {
+            bail!("Cubemap face of {}x{} pixels is not square", width, height);
+        }
+        if let Some(face) = faces
+            .iter()
+            .find(|face| face.dimensions() != (width, height))
+        {
+            bail!(
+                "Cubemap faces of {}x{} and {}x{} pixels differ in size",
+                width,
+                height,
+                face.width(),
+                face.height()
+            );
+        }
+
+        let cubemap = Self::new_empty(device, cubemap_bind_group_layout, width, Some("cubemap"));
+        for (layer, face) in faces.iter().enumerate() {
+            queue.write_texture(
+                wgpu::TexelCopyTextureInfo {
+                    texture: &cubemap.texture,
+                    mip_level: 0,
+                    origin: wgpu::Origin3d {
+                        x: 0,
+                        y: 0,
+                        z: layer as u32,
+                    },
+                    aspect: wgpu::TextureAspect::All,
+                },
+                face,
+                wgpu::TexelCopyBufferLayout {
+                    offset: 0,
+                    bytes_per_row: Some(4 * width),
+                    rows_per_image: Some(height),
+                },
+                wgpu::Extent3d {
+                    width,
+                    height,
+                    depth_or_array_layers: 1,
+                },
+            );
+        }
+
+        Ok(cubemap)
+    }
+
+    /// Converts a panorama on the GPU, its width covers the longitude and its height the latitude
+    pub fn from_equirectangular(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        cubemap_bind_group_layout: &CubemapBindGroupLayout,
+        panorama: &image::RgbaImage,
+        face_size: u32,
+    ) -> Result<Self> {
+        let (width, height) = panorama.dimensions();
+        if width == 0 || height == 0 || face_size == 0 {
+            bail!(
+                "Panorama of {}x{} pixels can not be converted into faces of {} pixels",
+                width,
+                height,
+                face_size
+            );
+        }
+
+        let cubemap = Self::new_empty(
+            device,
+            cubemap_bind_group_layout,
+            face_size,
+            Some("equirectangular_cubemap"),
+        );
+        equirectangular::convert(device, queue, panorama, &cubemap.texture);
+
+        Ok(cubemap)
+    }
+
+    fn new_empty(
+        device: &wgpu::Device,
+        cubemap_bind_group_layout: &CubemapBindGroupLayout,
+        size: u32,
+        label: Option<&str>,
+    ) -> Self {
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label,
+            size: wgpu::Extent3d {
+                width: size,
+                height: size,
+                depth_or_array_layers: 6,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: Self::FORMAT,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING
+                | wgpu::TextureUsages::COPY_DST
+                | wgpu::TextureUsages::RENDER_ATTACHMENT,
+            view_formats: &[],
+        });
+
+        let view = texture.create_view(&wgpu::TextureViewDescriptor {
+            dimension: Some(wgpu::TextureViewDimension::Cube),
+            ..Default::default()
+        });
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            mipmap_filter: wgpu::MipmapFilterMode::Nearest,
+            ..Default::default()
+        });
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: cubemap_bind_group_layout.get(),
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(&sampler),
+                },
+            ],
+            label: Some("cubemap_bind_group"),
+        });
+
+        Self {
+            texture,
+            view,
+            sampler,
+            bind_group,
+            size,
+        }
+    }
+
+    /// Width and height of a face in pixels
+    pub fn size(&self) -> u32 {
+        self.size
+    }
+
+    pub fn bind<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>, group: u32) {
+        render_pass.set_bind_group(group, &self.bind_group, &[]);
+    }
+}
@@ -0,0 +1,41 @@
+//! A bind group to create cubemaps, for the skybox and environment reflections
+//!
+
+pub struct CubemapBindGroupLayout {
+    cubemap_bind_group_layout: wgpu::BindGroupLayout,
+}
+
+impl CubemapBindGroupLayout {
+    pub fn new(device: &wgpu::Device) -> Self {
+        let cubemap_bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                entries: &[
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Texture {
+                            multisampled: false,
+                            view_dimension: wgpu::TextureViewDimension::Cube,
+                            sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        },
+                        count: None,
+                    },
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 1,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                        count: None,
+                    },
+                ],
+                label: Some("cubemap_bind_group_layout"),
+            });
+
+        Self {
+            cubemap_bind_group_layout,
+        }
+    }
+
+    pub fn get(&self) -> &wgpu::BindGroupLayout {
+        &self.cubemap_bind_group_layout
+    }
+}
@@ -0,0 +1,38 @@
+// Renders one face of a cubemap from an equirectangular panorama
+#include "fullscreen"
+
+// The layer of the face, in the order +X, -X, +Y, -Y, +Z, -Z
+@group(0) @binding(0)
+var<uniform> face: vec4<u32>;
+@group(0) @binding(1)
+var panorama_texture: texture_2d<f32>;
+@group(0) @binding(2)
+var panorama_sampler: sampler;
+
+const PI: f32 = 3.14159265359;
+
+// The direction of a texel, as sampled from a cube texture
+fn face_direction(layer: u32, uv: vec2<f32>) -> vec3<f32> {
+    let s = uv.x * 2.0 - 1.0;
+    let t = uv.y * 2.0 - 1.0;
+    switch layer {
+        case 0u: { return vec3<f32>(1.0, -t, -s); }
+        case 1u: { return vec3<f32>(-1.0, -t, s); }
+        case 2u: { return vec3<f32>(s, 1.0, t); }
+        case 3u: { return vec3<f32>(s, -1.0, -t); }
+        case 4u: { return vec3<f32>(s, -t, 1.0); }
+        default: { return vec3<f32>(-s, -t, -1.0); }
+    }
+}
+
+@fragment
+fn fs_main(in: FullscreenOutput) -> @location(0) vec4<f32> {
+    let direction = normalize(face_direction(face.x, in.tex_coords));
+
+    // Z points up, the longitude starts at +X
+    let longitude = atan2(direction.y, direction.x);
+    let latitude = asin(clamp(direction.z, -1.0, 1.0));
+    let uv = vec2<f32>(0.5 - longitude / (2.0 * PI), 0.5 - latitude / PI);
+
+    return textureSampleLevel(panorama_texture, panorama_sampler, uv, 0.0);
+}
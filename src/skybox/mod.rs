@@ -0,0 +1,22 @@
+//! Cubemaps and a skybox that draws one behind the scene
+//!
+//! A `Cubemap` is loaded from six face images or converted on the GPU from an
+//! equirectangular panorama. The `Skybox` draws it at the far plane with the rotation of
+//! the camera, after the opaque objects of the scene. The same cubemap is reflected by
+//! `vertex_color_shader::Pipeline::new_lit_reflective`, with the reflectivity set in the
+//! `LightUniform`.
+//!
+
+mod cubemap;
+mod cubemap_bind_group_layout;
+mod equirectangular;
+mod pipeline;
+mod skybox_uniform;
+
+#[cfg(test)]
+mod tests;
+
+pub use cubemap::Cubemap;
+pub use cubemap_bind_group_layout::CubemapBindGroupLayout;
+pub use pipeline::Skybox;
+pub use skybox_uniform::SkyboxUniform;
@@ -0,0 +1,138 @@
+//! The pipeline and the uniform buffer to draw a cubemap as the background of the scene
+//!
+
+use wgpu::util::DeviceExt;
+
+use super::super::wgpu_renderer::camera::{Camera, Projection};
+use super::super::wgpu_renderer::depth_texture::DepthTexture;
+use super::super::wgpu_renderer::fragment_output::FragmentOutput;
+use super::super::wgsl_preprocessor;
+use super::Cubemap;
+use super::CubemapBindGroupLayout;
+use super::SkyboxUniform;
+
+pub struct Skybox {
+    render_pipeline: wgpu::RenderPipeline,
+    skybox_buffer: wgpu::Buffer,
+    skybox_bind_group: wgpu::BindGroup,
+}
+
+impl Skybox {
+    /// `target_format` is the format of the pass it draws in, `scene_format()` of the renderer
+    ///
+    /// With HDR the scene pass renders into `Rgba16Float`, not into the surface format.
+    pub fn new(
+        device: &wgpu::Device,
+        cubemap_bind_group_layout: &CubemapBindGroupLayout,
+        target_format: wgpu::TextureFormat,
+        sample_count: u32,
+    ) -> Self {
+        let skybox_bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                entries: &[wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                }],
+                label: Some("skybox_bind_group_layout"),
+            });
+
+        let skybox_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Skybox Buffer"),
+            contents: bytemuck::cast_slice(&[SkyboxUniform::new()]),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+        let skybox_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &skybox_bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: skybox_buffer.as_entire_binding(),
+            }],
+            label: Some("skybox_bind_group"),
+        });
+
+        let shader_code = wgsl_preprocessor::preprocess(include_str!("shader.wgsl"));
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Skybox Shader"),
+            source: wgpu::ShaderSource::Wgsl(shader_code.as_str().into()),
+        });
+
+        let output = FragmentOutput::surface(target_format);
+        let constants = output.constants(&shader_code);
+        let targets = output.targets();
+
+        let render_pipeline_layout =
+            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("Skybox Pipeline Layout"),
+                bind_group_layouts: &[&skybox_bind_group_layout, cubemap_bind_group_layout.get()],
+                immediate_size: 0,
+            });
+
+        let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Skybox Pipeline"),
+            layout: Some(&render_pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: Some("vs_main"),
+                buffers: &[],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: Some("fs_main"),
+                targets: &targets,
+                compilation_options: wgpu::PipelineCompilationOptions {
+                    constants: &constants,
+                    ..Default::default()
+                },
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            // At the far plane, only where nothing else was drawn
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: DepthTexture::DEPTH_FORMAT,
+                depth_write_enabled: false,
+                depth_compare: wgpu::CompareFunction::LessEqual,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState {
+                count: sample_count,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+            cache: None,
+            multiview_mask: None,
+        });
+
+        Self {
+            render_pipeline,
+            skybox_buffer,
+            skybox_bind_group,
+        }
+    }
+
+    /// Call it when the camera turns or the projection changes
+    pub fn update(&self, queue: &wgpu::Queue, camera: &Camera, projection: &Projection) {
+        let mut skybox_uniform = SkyboxUniform::new();
+        skybox_uniform.update(camera, projection);
+
+        queue.write_buffer(
+            &self.skybox_buffer,
+            0,
+            bytemuck::cast_slice(&[skybox_uniform]),
+        );
+    }
+
+    /// Draw it after the opaque objects of the scene, to skip the covered pixels
+    pub fn draw<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>, cubemap: &'a Cubemap) {
+        render_pass.set_pipeline(&self.render_pipeline);
+        render_pass.set_bind_group(0, &self.skybox_bind_group, &[]);
+        cubemap.bind(render_pass, 1);
+        render_pass.draw(0..3, 0..1);
+    }
+}
@@ -0,0 +1,39 @@
+// Draws the cubemap behind everything else, at the far plane
+#define ENVIRONMENT_GROUP 1
+#include "environment"
+#include "surface_color"
+
+// Matches skybox::SkyboxUniform
+struct SkyboxUniform {
+    inverse_view_proj: mat4x4<f32>,
+};
+
+@group(0) @binding(0)
+var<uniform> skybox: SkyboxUniform;
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) ndc: vec2<f32>,
+};
+
+// A triangle that covers the whole target, drawn with draw(0..3, 0..1)
+@vertex
+fn vs_main(@builtin(vertex_index) vertex_index: u32) -> VertexOutput {
+    let uv = vec2<f32>(f32((vertex_index << 1u) & 2u), f32(vertex_index & 2u));
+    let ndc = uv * vec2<f32>(2.0, -2.0) + vec2<f32>(-1.0, 1.0);
+
+    var out: VertexOutput;
+    // A depth of 1.0 is only drawn where the cleared depth was kept
+    out.clip_position = vec4<f32>(ndc, 1.0, 1.0);
+    out.ndc = ndc;
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    // A point in front of the camera, which is at the origin without the translation
+    let point = skybox.inverse_view_proj * vec4<f32>(in.ndc, 0.0, 1.0);
+    let direction = point.xyz / point.w;
+
+    return to_surface_color(vec4<f32>(environment_color(direction), 1.0));
+}
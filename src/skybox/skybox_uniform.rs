@@ -0,0 +1,37 @@
+//! The SkyboxUniform struct used in the skybox shader
+//!
+
+use cgmath::*;
+
+use super::super::wgpu_renderer::camera::{Camera, Projection};
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct SkyboxUniform {
+    /// Turns a position on the screen into a direction of the world
+    inverse_view_proj: [[f32; 4]; 4],
+}
+
+impl Default for SkyboxUniform {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SkyboxUniform {
+    pub fn new() -> Self {
+        Self {
+            inverse_view_proj: Matrix4::identity().into(),
+        }
+    }
+
+    /// Only the rotation of the camera is used, the skybox does not move with it
+    pub fn update(&mut self, camera: &Camera, projection: &Projection) {
+        let mut view = camera.calc_matrix();
+        view.w = Vector4::unit_w();
+
+        if let Some(inverse) = (projection.calc_matrix() * view).invert() {
+            self.inverse_view_proj = inverse.into();
+        }
+    }
+}
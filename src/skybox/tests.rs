@@ -0,0 +1,127 @@
+//! Unit tests
+
+use crate::golden_image::test_renderer;
+use crate::golden_image::test_support::{
+    assert_golden, finish, identity_instance, perspective_camera, CLEAR_COLOR, HEIGHT, WIDTH,
+};
+use crate::shape::{self, MeshDataInterface};
+use crate::skybox;
+use crate::vertex_color_shader;
+use crate::wgpu_renderer::camera::{Camera, Projection};
+use crate::wgpu_renderer::{FramePass, WgpuRendererInterface};
+
+/// Sky above the horizon and ground below, with stripes at +X, +Y and -X
+fn panorama(width: u32, height: u32) -> image::RgbaImage {
+    image::RgbaImage::from_fn(width, height, |x, y| {
+        let u = x as f32 / width as f32;
+        if (u - 0.5).abs() < 0.03 {
+            image::Rgba([220, 40, 40, 255])
+        } else if (u - 0.25).abs() < 0.03 {
+            image::Rgba([40, 200, 60, 255])
+        } else if !(0.03..=0.97).contains(&u) {
+            image::Rgba([230, 210, 40, 255])
+        } else if y < height / 2 {
+            let blue = 255 - (y * 120 / height) as u8;
+            image::Rgba([90, 150, blue, 255])
+        } else {
+            image::Rgba([110, 80, 50, 255])
+        }
+    })
+}
+
+#[test]
+fn skybox_reflective_sphere() {
+    let Some(mut renderer) = test_renderer(WIDTH, HEIGHT) else {
+        return;
+    };
+    let scene_format = renderer.scene_format();
+    let sample_count = renderer.sample_count();
+
+    let camera_bind_group_layout =
+        vertex_color_shader::CameraBindGroupLayout::new(renderer.device());
+    let light_bind_group_layout = vertex_color_shader::LightBindGroupLayout::new(renderer.device());
+    let cubemap_bind_group_layout = skybox::CubemapBindGroupLayout::new(renderer.device());
+
+    let device = renderer.device().clone();
+    let queue = renderer.queue().clone();
+
+    let small_face = image::RgbaImage::new(4, 4);
+    let large_face = image::RgbaImage::new(8, 8);
+    let mismatched = skybox::Cubemap::from_images(
+        &device,
+        &queue,
+        &cubemap_bind_group_layout,
+        [
+            &small_face,
+            &small_face,
+            &small_face,
+            &large_face,
+            &small_face,
+            &small_face,
+        ],
+    );
+    assert!(mismatched.is_err());
+
+    let cubemap = skybox::Cubemap::from_equirectangular(
+        &device,
+        &queue,
+        &cubemap_bind_group_layout,
+        &panorama(128, 64),
+        64,
+    )
+    .unwrap();
+
+    let camera = Camera::new((-5.0, 0.0, 0.0), cgmath::Deg(0.0), cgmath::Deg(0.0));
+    let projection = Projection::new(WIDTH, HEIGHT, cgmath::Deg(45.0), 0.1, 100.0);
+    let camera_buffer = perspective_camera(&mut renderer, &camera_bind_group_layout, &camera);
+    let skybox = skybox::Skybox::new(
+        renderer.device(),
+        &cubemap_bind_group_layout,
+        scene_format,
+        sample_count,
+    );
+    skybox.update(renderer.queue(), &camera, &projection);
+
+    let pipeline = vertex_color_shader::Pipeline::new_lit_reflective(
+        renderer.device(),
+        &camera_bind_group_layout,
+        &light_bind_group_layout,
+        &cubemap_bind_group_layout,
+        scene_format,
+        sample_count,
+    );
+    let mut light_uniform = vertex_color_shader::LightUniform::new();
+    light_uniform.add_directional_light(
+        cgmath::Vector3::new(1.0, 1.0, -1.0),
+        cgmath::Vector3::new(1.0, 1.0, 1.0),
+        0.8,
+    );
+    light_uniform.set_reflectivity(0.6);
+    let mut light_buffer =
+        vertex_color_shader::LightUniformBuffer::new(renderer.device(), &light_bind_group_layout);
+    light_buffer.update(renderer.queue(), light_uniform);
+
+    let sphere = shape::UVSphere::new(1.0, 24);
+    let mesh = vertex_color_shader::Mesh::from_shape(
+        renderer.device(),
+        sphere.triangles(),
+        &cgmath::Vector3::new(0.8, 0.8, 0.8),
+        &[identity_instance()],
+    );
+
+    let mut frame = renderer.begin_frame().unwrap();
+    {
+        let mut render_pass = frame.begin_pass(&FramePass::scene(CLEAR_COLOR));
+        pipeline.draw_lit_reflective(
+            &mut render_pass,
+            &camera_buffer,
+            &light_buffer,
+            &cubemap,
+            &mesh,
+        );
+        skybox.draw(&mut render_pass, &cubemap);
+    }
+    let actual = finish(&mut renderer, frame);
+
+    assert_golden("skybox_reflective_sphere", &actual);
+}
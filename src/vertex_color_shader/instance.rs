@@ -1,27 +1,70 @@
-//! Contains the position and the rotation of an object
+//! Contains the position, rotation, scale, color and id of an object
 //!
 
-use cgmath::Zero;
+use cgmath::{Matrix, One, SquareMatrix, Zero};
 
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Debug, PartialEq)]
 pub struct Instance {
     pub position: cgmath::Vector3<f32>,
     pub rotation: cgmath::Quaternion<f32>,
+    /// Scales the mesh along its own axes, before the rotation
+    pub scale: cgmath::Vector3<f32>,
+    /// Multiplied with the colors of the mesh, white keeps them
+    pub color: cgmath::Vector4<f32>,
+    /// Identifies the instance, e.g. when picking objects
+    pub id: u32,
+}
+
+impl Default for Instance {
+    fn default() -> Self {
+        Self::identity()
+    }
 }
 
 impl Instance {
-    pub fn zero() -> Self {
+    /// At the origin, not rotated or scaled, white and with the id 0
+    pub fn identity() -> Self {
         Self {
             position: cgmath::Vector3::zero(),
-            rotation: cgmath::Quaternion::zero(),
+            rotation: cgmath::Quaternion::one(),
+            scale: cgmath::Vector3::new(1.0, 1.0, 1.0),
+            color: cgmath::Vector4::new(1.0, 1.0, 1.0, 1.0),
+            id: 0,
+        }
+    }
+
+    pub fn new(position: cgmath::Vector3<f32>, rotation: cgmath::Quaternion<f32>) -> Self {
+        Self {
+            position,
+            rotation,
+            ..Self::identity()
         }
     }
+
+    pub fn with_scale(mut self, scale: cgmath::Vector3<f32>) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    pub fn with_color(mut self, color: cgmath::Vector4<f32>) -> Self {
+        self.color = color;
+        self
+    }
+
+    pub fn with_id(mut self, id: u32) -> Self {
+        self.id = id;
+        self
+    }
 }
 
 #[repr(C)]
 #[derive(Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
 pub struct InstanceRaw {
     model: [[f32; 4]; 4],
+    // Inverse transpose of the rotation and scale of the model, to transform normals
+    normal: [[f32; 3]; 3],
+    color: [f32; 4],
+    id: u32,
 }
 
 impl Default for InstanceRaw {
@@ -33,8 +76,14 @@ impl Default for InstanceRaw {
 impl InstanceRaw {
     pub fn new() -> Self {
         let model = glam::Mat4::IDENTITY.to_cols_array_2d();
+        let normal = glam::Mat3::IDENTITY.to_cols_array_2d();
 
-        Self { model }
+        Self {
+            model,
+            normal,
+            color: [1.0; 4],
+            id: 0,
+        }
     }
 
     pub fn desc<'a>() -> wgpu::VertexBufferLayout<'a> {
@@ -70,19 +119,62 @@ impl InstanceRaw {
                     shader_location: 8,
                     format: wgpu::VertexFormat::Float32x4,
                 },
+                // The normal matrix, a mat3 in 3 slots
+                wgpu::VertexAttribute {
+                    offset: mem::size_of::<[f32; 16]>() as wgpu::BufferAddress,
+                    shader_location: 9,
+                    format: wgpu::VertexFormat::Float32x3,
+                },
+                wgpu::VertexAttribute {
+                    offset: mem::size_of::<[f32; 19]>() as wgpu::BufferAddress,
+                    shader_location: 10,
+                    format: wgpu::VertexFormat::Float32x3,
+                },
+                wgpu::VertexAttribute {
+                    offset: mem::size_of::<[f32; 22]>() as wgpu::BufferAddress,
+                    shader_location: 11,
+                    format: wgpu::VertexFormat::Float32x3,
+                },
+                wgpu::VertexAttribute {
+                    offset: mem::size_of::<[f32; 25]>() as wgpu::BufferAddress,
+                    shader_location: 12,
+                    format: wgpu::VertexFormat::Float32x4,
+                },
+                wgpu::VertexAttribute {
+                    offset: mem::size_of::<[f32; 29]>() as wgpu::BufferAddress,
+                    shader_location: 13,
+                    format: wgpu::VertexFormat::Uint32,
+                },
             ],
         }
     }
 }
 
 impl Instance {
+    fn rotation_scale(&self) -> cgmath::Matrix3<f32> {
+        cgmath::Matrix3::from(self.rotation) * cgmath::Matrix3::from_diagonal(self.scale)
+    }
+
+    /// Scales, rotates and then translates the mesh
+    pub fn model_matrix(&self) -> cgmath::Matrix4<f32> {
+        cgmath::Matrix4::from_translation(self.position)
+            * cgmath::Matrix4::from(self.rotation_scale())
+    }
+
     #[allow(clippy::wrong_self_convention)]
     pub fn to_raw(&self) -> InstanceRaw {
-        let model =
-            cgmath::Matrix4::from_translation(self.position) * cgmath::Matrix4::from(self.rotation);
+        let model = self.model_matrix();
+        let normal = self
+            .rotation_scale()
+            .invert()
+            .unwrap_or(cgmath::Matrix3::identity())
+            .transpose();
 
         InstanceRaw {
             model: model.into(),
+            normal: normal.into(),
+            color: self.color.into(),
+            id: self.id,
         }
     }
 }
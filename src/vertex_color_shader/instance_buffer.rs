@@ -29,14 +29,43 @@ where
         }
     }
 
+    /// Fewer instances than the buffer holds are drawn, e.g. the visible ones after culling
+    ///
+    /// Instances beyond the capacity are not drawn, use `update_or_grow` for more instances.
     pub fn update(&mut self, queue: &wgpu::Queue, instances: &[TInstance]) {
-        let data = bytemuck::cast_slice(instances);
+        let capacity = self.capacity();
+        if instances.len() > capacity {
+            log::warn!(
+                "Instance buffer holds {} instances, {} are not drawn",
+                capacity,
+                instances.len() - capacity
+            );
+        }
+        let instances = &instances[..instances.len().min(capacity)];
+
+        queue.write_buffer(&self.buffer, 0, bytemuck::cast_slice(instances));
+        self._size = instances.len() as u32;
+    }
 
-        if data.len() as u64 <= self.buffer.size() {
-            queue.write_buffer(&self.buffer, 0, data);
+    /// Like `update`, the buffer is recreated if there are more instances than it holds
+    pub fn update_or_grow(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        instances: &[TInstance],
+    ) {
+        if instances.len() > self.capacity() {
+            *self = Self::new(device, instances);
+        } else {
+            self.update(queue, instances);
         }
     }
 
+    /// The number of instances the buffer holds
+    pub fn capacity(&self) -> usize {
+        self.buffer.size() as usize / std::mem::size_of::<TInstance>()
+    }
+
     pub fn bind<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>) {
         render_pass.set_vertex_buffer(1, self.buffer.slice(..));
     }
@@ -0,0 +1,34 @@
+//! A bind group to create a light uniform buffer for this shader
+//!
+
+pub struct LightBindGroupLayout {
+    light_bind_group_layout: wgpu::BindGroupLayout,
+}
+
+impl LightBindGroupLayout {
+    pub fn new(device: &wgpu::Device) -> Self {
+        // Lights
+        let light_bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                entries: &[wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                }],
+                label: Some("light_bind_group_layout"),
+            });
+
+        Self {
+            light_bind_group_layout,
+        }
+    }
+
+    pub fn get(&self) -> &wgpu::BindGroupLayout {
+        &self.light_bind_group_layout
+    }
+}
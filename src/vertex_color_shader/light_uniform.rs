@@ -0,0 +1,120 @@
+//! The LightUniform struct used in the lit shader
+//!
+
+use cgmath::*;
+
+/// Limited by the size of the array in the shader
+pub const MAX_DIRECTIONAL_LIGHTS: usize = 4;
+/// Limited by the size of the array in the shader
+pub const MAX_POINT_LIGHTS: usize = 8;
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, Default, bytemuck::Pod, bytemuck::Zeroable)]
+struct DirectionalLightRaw {
+    direction: [f32; 4],
+    color: [f32; 4],
+}
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, Default, bytemuck::Pod, bytemuck::Zeroable)]
+struct PointLightRaw {
+    position: [f32; 4],
+    color: [f32; 4],
+}
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct LightUniform {
+    ambient: [f32; 4],
+    material: [f32; 4],
+    counts: [u32; 4],
+    directional_lights: [DirectionalLightRaw; MAX_DIRECTIONAL_LIGHTS],
+    point_lights: [PointLightRaw; MAX_POINT_LIGHTS],
+}
+
+impl Default for LightUniform {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LightUniform {
+    /// No lights, a dim ambient light and a moderate specular highlight
+    pub fn new() -> Self {
+        Self {
+            ambient: [0.1, 0.1, 0.1, 0.0],
+            material: [0.5, 32.0, 0.0, 0.0],
+            counts: [0; 4],
+            directional_lights: [DirectionalLightRaw::default(); MAX_DIRECTIONAL_LIGHTS],
+            point_lights: [PointLightRaw::default(); MAX_POINT_LIGHTS],
+        }
+    }
+
+    /// Light reaching every surface, independent of its normal
+    pub fn set_ambient(&mut self, color: Vector3<f32>) {
+        self.ambient = color.extend(0.0).into();
+    }
+
+    /// The strength and the exponent of the specular highlights
+    pub fn set_specular(&mut self, strength: f32, shininess: f32) {
+        self.material[0] = strength;
+        self.material[1] = shininess;
+    }
+
+    /// How much of the environment is reflected, from 0.0 to 1.0
+    ///
+    /// Only used by the reflective pipeline, e.g. `Pipeline::new_lit_reflective`.
+    pub fn set_reflectivity(&mut self, reflectivity: f32) {
+        self.material[2] = reflectivity.clamp(0.0, 1.0);
+    }
+
+    /// The direction points from the light towards the scene
+    ///
+    /// Returns false if `MAX_DIRECTIONAL_LIGHTS` are used already.
+    pub fn add_directional_light(
+        &mut self,
+        direction: Vector3<f32>,
+        color: Vector3<f32>,
+        intensity: f32,
+    ) -> bool {
+        let count = self.counts[0] as usize;
+        if count >= MAX_DIRECTIONAL_LIGHTS {
+            return false;
+        }
+
+        self.directional_lights[count] = DirectionalLightRaw {
+            direction: direction.normalize().extend(0.0).into(),
+            color: (color * intensity).extend(0.0).into(),
+        };
+        self.counts[0] += 1;
+        true
+    }
+
+    /// The light fades out towards the range
+    ///
+    /// Returns false if `MAX_POINT_LIGHTS` are used already.
+    pub fn add_point_light(
+        &mut self,
+        position: Point3<f32>,
+        color: Vector3<f32>,
+        intensity: f32,
+        range: f32,
+    ) -> bool {
+        let count = self.counts[1] as usize;
+        if count >= MAX_POINT_LIGHTS {
+            return false;
+        }
+
+        self.point_lights[count] = PointLightRaw {
+            position: position.to_vec().extend(range).into(),
+            color: (color * intensity).extend(0.0).into(),
+        };
+        self.counts[1] += 1;
+        true
+    }
+
+    /// Removes the directional and point lights
+    pub fn clear_lights(&mut self) {
+        self.counts = [0; 4];
+    }
+}
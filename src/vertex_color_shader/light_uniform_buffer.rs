@@ -0,0 +1,55 @@
+//! Contains a buffer for the LightUniform struct
+//!
+
+use super::light_bind_group_layout;
+use super::light_uniform;
+use wgpu::util::DeviceExt;
+
+pub struct LightUniformBuffer {
+    light_buffer: wgpu::Buffer,
+    light_bind_group: wgpu::BindGroup,
+}
+
+impl LightUniformBuffer {
+    /// The group of the lights in the lit pipeline
+    pub const GROUP: u32 = 1;
+
+    pub fn new(
+        device: &wgpu::Device,
+        light_bind_group_layout: &light_bind_group_layout::LightBindGroupLayout,
+    ) -> Self {
+        let light_uniform = light_uniform::LightUniform::new();
+
+        let light_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Light Buffer"),
+            contents: bytemuck::cast_slice(&[light_uniform]),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+
+        let light_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: light_bind_group_layout.get(),
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: light_buffer.as_entire_binding(),
+            }],
+            label: Some("light_bind_group"),
+        });
+
+        Self {
+            light_buffer,
+            light_bind_group,
+        }
+    }
+
+    pub fn update(&mut self, queue: &wgpu::Queue, light_uniform: light_uniform::LightUniform) {
+        queue.write_buffer(
+            &self.light_buffer,
+            0,
+            bytemuck::cast_slice(&[light_uniform]),
+        );
+    }
+
+    pub fn bind<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>) {
+        render_pass.set_bind_group(Self::GROUP, &self.light_bind_group, &[]);
+    }
+}
@@ -1,17 +1,22 @@
 //! Contains the device buffers to render an object with this shader
 //!
 
+use crate::culling::{self, BoundingSphere, CullResult, CullingMode, Frustum, GpuCulling};
 use crate::shape;
+use crate::wgpu_renderer::GpuContext;
 
 use super::vertex_color_shader_draw::VertexColorShaderDrawLines;
+use super::vertex_color_shader_draw::VertexColorShaderDrawLit;
 use super::Color;
 use super::Instance;
 use super::InstanceRaw;
+use super::Normal;
 use super::Vertex;
 
 use super::ColorBuffer;
 use super::IndexBuffer;
 use super::InstanceBuffer;
+use super::NormalBuffer;
 use super::VertexBuffer;
 use super::VertexColorShaderDraw;
 
@@ -19,8 +24,14 @@ use super::VertexColorShaderDraw;
 pub struct Mesh {
     vertex_buffer: VertexBuffer<Vertex>,
     color_buffer: ColorBuffer,
+    normal_buffer: Option<NormalBuffer>,
     index_buffer: IndexBuffer<u32>,
     instance_buffer: InstanceBuffer<InstanceRaw>,
+
+    bounding_sphere: BoundingSphere,
+    instances: Vec<Instance>,
+    /// The indices of the instances in the instance buffer, None if all are drawn
+    visible_indices: Option<Vec<u32>>,
 }
 
 #[allow(dead_code)]
@@ -39,14 +50,40 @@ impl Mesh {
         let instance_data = instances.iter().map(Instance::to_raw).collect::<Vec<_>>();
         let instance_buffer = InstanceBuffer::new(device, &instance_data);
 
+        let bounding_sphere = BoundingSphere::from_points(
+            vertices
+                .iter()
+                .map(|vertex| cgmath::Point3::from(vertex.position)),
+        )
+        .unwrap_or(BoundingSphere::new(cgmath::Point3::new(0.0, 0.0, 0.0), 0.0));
+
         Self {
             vertex_buffer,
             color_buffer,
+            normal_buffer: None,
             index_buffer,
             instance_buffer,
+
+            bounding_sphere,
+            instances: instances.to_vec(),
+            visible_indices: None,
         }
     }
 
+    /// A mesh that can also be drawn with the lit pipeline
+    pub fn new_with_normals(
+        device: &wgpu::Device,
+        vertices: &[Vertex],
+        colors: &[Color],
+        normals: &[Normal],
+        indices: &[u32],
+        instances: &[Instance],
+    ) -> Self {
+        let mut mesh = Self::new(device, vertices, colors, indices, instances);
+        mesh.normal_buffer = Some(NormalBuffer::new(device, normals));
+        mesh
+    }
+
     pub fn from_shape(
         device: &wgpu::Device,
         shape: &shape::MeshDataTriangles,
@@ -67,25 +104,140 @@ impl Mesh {
             });
         }
 
+        let mut normals = Vec::new();
+        for normal in &shape.normals {
+            normals.push(Normal {
+                normal: (*normal).into(),
+            });
+        }
+
         let mut indices = Vec::new();
         for index in &shape.indices {
             indices.push(*index);
         }
 
-        Self::new(device, &vertices, &colors, &indices, instances)
+        Self::new_with_normals(device, &vertices, &colors, &normals, &indices, instances)
     }
 
+    /// The bounding sphere is not updated, it has to contain the new vertices
     pub fn update_vertex_buffer(&mut self, queue: &wgpu::Queue, vertices: &[Vertex]) {
         self.vertex_buffer.update(queue, vertices);
     }
 
+    /// Around the vertices, before the instance transformations
+    pub fn bounding_sphere(&self) -> &BoundingSphere {
+        &self.bounding_sphere
+    }
+
+    /// E.g. when the vertices are moved beyond the initial bounds
+    pub fn set_bounding_sphere(&mut self, bounding_sphere: BoundingSphere) {
+        self.bounding_sphere = bounding_sphere;
+    }
+
+    /// Writes only the instances inside the frustum into the instance buffer
+    ///
+    /// Call it after the camera or the instances changed, the visible instances are
+    /// drawn until the next call or `update_instance_buffer`.
+    pub fn cull(&mut self, queue: &wgpu::Queue, frustum: &Frustum) -> CullResult {
+        let mut visible_indices = self.visible_indices.take().unwrap_or_default();
+        let result = culling::cull_instances(
+            frustum,
+            &self.bounding_sphere,
+            &self.instances,
+            &mut visible_indices,
+        );
+
+        let instance_data = visible_indices
+            .iter()
+            .map(|index| self.instances[*index as usize].to_raw())
+            .collect::<Vec<_>>();
+        self.instance_buffer.update(queue, &instance_data);
+        self.visible_indices = Some(visible_indices);
+
+        result
+    }
+
+    /// Culls the instances of the mesh, `CullingMode::Compute` falls back to the CPU if needed
+    ///
+    /// Draw the mesh with `draw_culled`, the instance buffer of the mesh is not used then.
+    pub fn create_gpu_culling(&self, context: &GpuContext, mode: CullingMode) -> GpuCulling {
+        GpuCulling::new_with_mode(
+            context,
+            mode,
+            self.index_buffer.size(),
+            self.bounding_sphere,
+            &self.instances,
+        )
+    }
+
+    /// Draws the visible instances of a `GpuCulling`, after its `cull`
+    pub fn draw_culled<'a>(
+        &'a self,
+        render_pass: &mut wgpu::RenderPass<'a>,
+        gpu_culling: &'a GpuCulling,
+    ) {
+        self.vertex_buffer.bind(render_pass);
+        self.color_buffer.bind(render_pass);
+        self.index_buffer.bind(render_pass);
+        if let Some(normal_buffer) = &self.normal_buffer {
+            normal_buffer.bind(render_pass);
+        }
+
+        gpu_culling.draw_indexed(render_pass, 2);
+    }
+
+    /// The index in the instances of the mesh, of an instance in the instance buffer
+    ///
+    /// They differ after `cull`, e.g. for the instance index of a `ray_cast::RayHit`.
+    pub fn instance_index(&self, buffer_index: u32) -> Option<usize> {
+        match &self.visible_indices {
+            Some(visible_indices) => visible_indices
+                .get(buffer_index as usize)
+                .map(|index| *index as usize),
+            None => {
+                ((buffer_index as usize) < self.instances.len()).then_some(buffer_index as usize)
+            }
+        }
+    }
+
     pub fn update_color_buffer(&mut self, queue: &wgpu::Queue, colors: &[Color]) {
         self.color_buffer.update(queue, colors);
     }
 
+    /// Does nothing if the mesh was created without normals
+    pub fn update_normal_buffer(&mut self, queue: &wgpu::Queue, normals: &[Normal]) {
+        if let Some(normal_buffer) = &mut self.normal_buffer {
+            normal_buffer.update(queue, normals);
+        }
+    }
+
+    /// Draws all instances again, until the next `cull`
+    ///
+    /// Instances beyond the capacity of the instance buffer are not drawn, use
+    /// `update_instance_buffer_resize` for more instances than the mesh was created with.
     pub fn update_instance_buffer(&mut self, queue: &wgpu::Queue, instances: &[Instance]) {
         let instance_data = &instances.iter().map(Instance::to_raw).collect::<Vec<_>>();
         self.instance_buffer.update(queue, instance_data);
+        self.set_instances(instances);
+    }
+
+    /// Like `update_instance_buffer`, the buffer is recreated if there are more instances
+    pub fn update_instance_buffer_resize(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        instances: &[Instance],
+    ) {
+        let instance_data = &instances.iter().map(Instance::to_raw).collect::<Vec<_>>();
+        self.instance_buffer
+            .update_or_grow(device, queue, instance_data);
+        self.set_instances(instances);
+    }
+
+    fn set_instances(&mut self, instances: &[Instance]) {
+        self.instances.clear();
+        self.instances.extend_from_slice(instances);
+        self.visible_indices = None;
     }
 
     fn do_draw<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>) {
@@ -93,6 +245,9 @@ impl Mesh {
         self.color_buffer.bind(render_pass);
         self.index_buffer.bind(render_pass);
         self.instance_buffer.bind_slot(render_pass, 2);
+        if let Some(normal_buffer) = &self.normal_buffer {
+            normal_buffer.bind(render_pass);
+        }
 
         render_pass.draw_indexed(
             0..self.index_buffer.size(),
@@ -113,3 +268,13 @@ impl VertexColorShaderDrawLines for Mesh {
         self.do_draw(render_pass);
     }
 }
+
+impl VertexColorShaderDrawLit for Mesh {
+    fn draw_lit<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>) {
+        if self.normal_buffer.is_none() {
+            log::warn!("Mesh without normals, not drawn lit");
+            return;
+        }
+        self.do_draw(render_pass);
+    }
+}
@@ -1,6 +1,8 @@
 //! A general purpose pipeline using vertices, colors and instances
 //!
 //! Vertices and Colors are independently updateable
+//! Meshes with normals can also be drawn lit, with the lit pipeline and a LightUniform
+//! The reflective variant of the lit pipeline also reflects a skybox::Cubemap
 //! The implementation uses wgpu for rendering
 //!
 
@@ -12,12 +14,20 @@ pub mod color_buffer;
 pub mod index_buffer;
 pub mod instance;
 pub mod instance_buffer;
+pub mod light_bind_group_layout;
+pub mod light_uniform;
+pub mod light_uniform_buffer;
 pub mod mesh;
+pub mod normal;
+pub mod normal_buffer;
 pub mod pipeline;
 pub mod vertex;
 pub mod vertex_buffer;
 pub mod vertex_color_shader_draw;
 
+#[cfg(test)]
+mod tests;
+
 pub use camera_bind_group_layout::CameraBindGroupLayout;
 pub use camera_uniform::CameraUniform;
 pub use camera_uniform_buffer::CameraUniformBuffer;
@@ -27,8 +37,14 @@ pub use index_buffer::IndexBuffer;
 pub use instance::Instance;
 pub use instance::InstanceRaw;
 pub use instance_buffer::InstanceBuffer;
+pub use light_bind_group_layout::LightBindGroupLayout;
+pub use light_uniform::LightUniform;
+pub use light_uniform_buffer::LightUniformBuffer;
 pub use mesh::Mesh;
+pub use normal::Normal;
+pub use normal_buffer::NormalBuffer;
 pub use pipeline::Pipeline;
 pub use vertex::Vertex;
 pub use vertex_buffer::VertexBuffer;
 pub use vertex_color_shader_draw::VertexColorShaderDraw;
+pub use vertex_color_shader_draw::VertexColorShaderDrawLit;
@@ -0,0 +1,36 @@
+//! GPU memory buffer containing the Normal for the lit shader
+//!
+
+use super::Normal;
+use wgpu::util::DeviceExt;
+
+pub struct NormalBuffer {
+    buffer: wgpu::Buffer,
+}
+
+impl NormalBuffer {
+    /// The slot after the instances, the unlit pipelines ignore it
+    pub const SLOT: u32 = 3;
+
+    pub fn new(device: &wgpu::Device, normals: &[Normal]) -> Self {
+        let buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Normal Buffer"),
+            contents: bytemuck::cast_slice(normals),
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+        });
+
+        Self { buffer }
+    }
+
+    pub fn update(&mut self, queue: &wgpu::Queue, normals: &[Normal]) {
+        let data = bytemuck::cast_slice(normals);
+
+        if self.buffer.size() == data.len() as u64 {
+            queue.write_buffer(&self.buffer, 0, data);
+        }
+    }
+
+    pub fn bind<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>) {
+        render_pass.set_vertex_buffer(Self::SLOT, self.buffer.slice(..));
+    }
+}
@@ -4,14 +4,25 @@
 //! The implementation uses wgpu for rendering
 //!
 
+use super::super::culling::GpuCulling;
+use super::super::picking::{PickingId, PickingIdBindGroupLayout};
+use super::super::shadow_map::{DirectionalShadowMap, ShadowBindGroupLayout};
+use super::super::skybox::{Cubemap, CubemapBindGroupLayout};
 use super::super::wgpu_renderer::depth_texture;
+use super::super::wgpu_renderer::fragment_output::FragmentOutput;
+use super::super::wgsl_preprocessor;
 use super::camera_bind_group_layout;
 use super::color;
 use super::instance;
+use super::normal;
 use super::vertex;
 use super::vertex_color_shader_draw::VertexColorShaderDrawLines;
 use super::CameraUniformBuffer;
+use super::LightBindGroupLayout;
+use super::LightUniformBuffer;
+use super::Mesh;
 use super::VertexColorShaderDraw;
+use super::VertexColorShaderDrawLit;
 
 /// A general purpose shader using vertices, colors and an instance matrix
 #[allow(dead_code)]
@@ -24,12 +35,15 @@ impl Pipeline {
         device: &wgpu::Device,
         camera_bind_group_layout: &camera_bind_group_layout::CameraBindGroupLayout,
         surface_format: wgpu::TextureFormat,
+        sample_count: u32,
     ) -> Self {
         Self::new_parameterized(
             device,
             camera_bind_group_layout,
             surface_format,
+            sample_count,
             wgpu::PrimitiveTopology::LineList,
+            None,
         )
     }
 
@@ -37,32 +51,241 @@ impl Pipeline {
         device: &wgpu::Device,
         camera_bind_group_layout: &camera_bind_group_layout::CameraBindGroupLayout,
         surface_format: wgpu::TextureFormat,
+        sample_count: u32,
     ) -> Self {
         Self::new_parameterized(
             device,
             camera_bind_group_layout,
             surface_format,
+            sample_count,
             wgpu::PrimitiveTopology::TriangleList,
+            None,
         )
     }
 
-    fn new_parameterized(
+    /// Uses the bundled shader if `shader_code` is None
+    pub fn new_parameterized(
         device: &wgpu::Device,
         camera_bind_group_layout: &camera_bind_group_layout::CameraBindGroupLayout,
         surface_format: wgpu::TextureFormat,
+        sample_count: u32,
         topology: wgpu::PrimitiveTopology,
+        shader_code: Option<&str>,
+    ) -> Self {
+        let shader_code =
+            wgsl_preprocessor::preprocess(shader_code.unwrap_or(include_str!("shader.wgsl")));
+
+        Self::create(
+            device,
+            &[camera_bind_group_layout.get()],
+            &shader_code,
+            FragmentOutput::surface(surface_format),
+            sample_count,
+            topology,
+            false,
+        )
+    }
+
+    /// Only writes the depth, to draw the shadow casters into a cascade of a shadow map
+    pub fn new_depth_only(
+        device: &wgpu::Device,
+        camera_bind_group_layout: &camera_bind_group_layout::CameraBindGroupLayout,
+    ) -> Self {
+        let shader_code = wgsl_preprocessor::preprocess(include_str!("shader.wgsl"));
+
+        Self::create(
+            device,
+            &[camera_bind_group_layout.get()],
+            &shader_code,
+            FragmentOutput::DepthOnly,
+            1,
+            wgpu::PrimitiveTopology::TriangleList,
+            false,
+        )
+    }
+
+    /// Darkens the colors where the light of the shadow map is blocked, draw with `draw_shadowed`
+    pub fn new_shadowed(
+        device: &wgpu::Device,
+        camera_bind_group_layout: &camera_bind_group_layout::CameraBindGroupLayout,
+        shadow_bind_group_layout: &ShadowBindGroupLayout,
+        surface_format: wgpu::TextureFormat,
+        sample_count: u32,
+    ) -> Self {
+        let shader_code =
+            wgsl_preprocessor::preprocess_with_defines(include_str!("shader.wgsl"), &["SHADOWS"]);
+
+        Self::create(
+            device,
+            &[
+                camera_bind_group_layout.get(),
+                shadow_bind_group_layout.get(),
+            ],
+            &shader_code,
+            FragmentOutput::surface(surface_format),
+            sample_count,
+            wgpu::PrimitiveTopology::TriangleList,
+            false,
+        )
+    }
+
+    /// Shades the colors with the normals and the lights of a `LightUniform`, draw with `draw_lit`
+    pub fn new_lit(
+        device: &wgpu::Device,
+        camera_bind_group_layout: &camera_bind_group_layout::CameraBindGroupLayout,
+        light_bind_group_layout: &LightBindGroupLayout,
+        surface_format: wgpu::TextureFormat,
+        sample_count: u32,
+    ) -> Self {
+        let shader_code =
+            wgsl_preprocessor::preprocess_with_defines(include_str!("shader.wgsl"), &["LIGHTING"]);
+
+        Self::create(
+            device,
+            &[
+                camera_bind_group_layout.get(),
+                light_bind_group_layout.get(),
+            ],
+            &shader_code,
+            FragmentOutput::surface(surface_format),
+            sample_count,
+            wgpu::PrimitiveTopology::TriangleList,
+            true,
+        )
+    }
+
+    /// Like `new_lit`, also reflects a cubemap of the environment, draw with `draw_lit_reflective`
+    ///
+    /// The reflectivity is set with `LightUniform::set_reflectivity`.
+    pub fn new_lit_reflective(
+        device: &wgpu::Device,
+        camera_bind_group_layout: &camera_bind_group_layout::CameraBindGroupLayout,
+        light_bind_group_layout: &LightBindGroupLayout,
+        cubemap_bind_group_layout: &CubemapBindGroupLayout,
+        surface_format: wgpu::TextureFormat,
+        sample_count: u32,
+    ) -> Self {
+        let shader_code = wgsl_preprocessor::preprocess_with_defines(
+            include_str!("shader.wgsl"),
+            &["LIGHTING", "ENVIRONMENT"],
+        );
+
+        Self::create(
+            device,
+            &[
+                camera_bind_group_layout.get(),
+                light_bind_group_layout.get(),
+                cubemap_bind_group_layout.get(),
+            ],
+            &shader_code,
+            FragmentOutput::surface(surface_format),
+            sample_count,
+            wgpu::PrimitiveTopology::TriangleList,
+            true,
+        )
+    }
+
+    /// Like `new_lit`, the first directional light is blocked by the shadow map
+    ///
+    /// The light of the shadow map should have the direction of the first directional light
+    /// of the `LightUniform`. Draw with `draw_lit_shadowed`.
+    pub fn new_lit_shadowed(
+        device: &wgpu::Device,
+        camera_bind_group_layout: &camera_bind_group_layout::CameraBindGroupLayout,
+        light_bind_group_layout: &LightBindGroupLayout,
+        shadow_bind_group_layout: &ShadowBindGroupLayout,
+        surface_format: wgpu::TextureFormat,
+        sample_count: u32,
+    ) -> Self {
+        let shader_code = wgsl_preprocessor::preprocess_with_defines(
+            include_str!("shader.wgsl"),
+            &["LIGHTING", "SHADOWS"],
+        );
+
+        Self::create(
+            device,
+            &[
+                camera_bind_group_layout.get(),
+                light_bind_group_layout.get(),
+                shadow_bind_group_layout.get(),
+            ],
+            &shader_code,
+            FragmentOutput::surface(surface_format),
+            sample_count,
+            wgpu::PrimitiveTopology::TriangleList,
+            true,
+        )
+    }
+
+    /// Writes the mesh id and the instance id into a `PickingTarget`, draw with `draw_picking`
+    pub fn new_picking(
+        device: &wgpu::Device,
+        camera_bind_group_layout: &camera_bind_group_layout::CameraBindGroupLayout,
+        picking_id_bind_group_layout: &PickingIdBindGroupLayout,
+    ) -> Self {
+        let shader_code =
+            wgsl_preprocessor::preprocess_with_defines(include_str!("shader.wgsl"), &["PICKING"]);
+
+        Self::create(
+            device,
+            &[
+                camera_bind_group_layout.get(),
+                picking_id_bind_group_layout.get(),
+            ],
+            &shader_code,
+            FragmentOutput::Picking,
+            1,
+            wgpu::PrimitiveTopology::TriangleList,
+            false,
+        )
+    }
+
+    /// Shader variants are selected with the defines of the preprocessed code
+    #[allow(clippy::too_many_arguments)]
+    fn create(
+        device: &wgpu::Device,
+        bind_group_layouts: &[&wgpu::BindGroupLayout],
+        shader_code: &str,
+        output: FragmentOutput,
+        sample_count: u32,
+        topology: wgpu::PrimitiveTopology,
+        with_normals: bool,
     ) -> Self {
         // Shader
         let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
             label: Some("Shader"),
-            source: wgpu::ShaderSource::Wgsl(include_str!("shader.wgsl").into()),
+            source: wgpu::ShaderSource::Wgsl(shader_code.into()),
         });
 
+        let constants = output.constants(shader_code);
+        let targets = output.targets();
+
+        // Against shadow acne, on surfaces at a steep angle to the light
+        let bias = if output.is_depth_only() {
+            wgpu::DepthBiasState {
+                constant: 2,
+                slope_scale: 2.0,
+                clamp: 0.0,
+            }
+        } else {
+            wgpu::DepthBiasState::default()
+        };
+
+        // The normals follow the instances, in the slot of the NormalBuffer
+        let mut buffers = vec![
+            vertex::Vertex::desc(),
+            color::Color::desc(),
+            instance::InstanceRaw::desc(),
+        ];
+        if with_normals {
+            buffers.push(normal::Normal::desc());
+        }
+
         // Pipeline
         let render_pipeline_layout =
             device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
                 label: Some("Render Pipeline Layout"),
-                bind_group_layouts: &[camera_bind_group_layout.get()],
+                bind_group_layouts,
                 immediate_size: 0,
             });
 
@@ -72,28 +295,24 @@ impl Pipeline {
             vertex: wgpu::VertexState {
                 module: &shader,
                 entry_point: Some("vs_main"),
-                buffers: &[
-                    vertex::Vertex::desc(),
-                    color::Color::desc(),
-                    instance::InstanceRaw::desc(),
-                ],
+                buffers: &buffers,
                 compilation_options: wgpu::PipelineCompilationOptions::default(),
             },
-            fragment: Some(wgpu::FragmentState {
+            fragment: output.entry_point().map(|entry_point| wgpu::FragmentState {
                 module: &shader,
-                entry_point: Some("fs_main"),
-                targets: &[Some(wgpu::ColorTargetState {
-                    format: surface_format,
-                    blend: Some(wgpu::BlendState::REPLACE),
-                    write_mask: wgpu::ColorWrites::ALL,
-                })],
-                compilation_options: wgpu::PipelineCompilationOptions::default(),
+                entry_point: Some(entry_point),
+                targets: &targets,
+                compilation_options: wgpu::PipelineCompilationOptions {
+                    constants: &constants,
+                    ..Default::default()
+                },
             }),
             primitive: wgpu::PrimitiveState {
                 topology, // wgpu::PrimitiveTopology::TriangleList,
                 strip_index_format: None,
                 front_face: wgpu::FrontFace::Ccw, // counter-clockwise direction
-                cull_mode: Some(wgpu::Face::Back),
+                // Shadow casters are drawn from both sides
+                cull_mode: (!output.is_depth_only()).then_some(wgpu::Face::Back),
                 // Setting this to anything other than Fill requires Features::NON_FILL_POLYGON_MODE
                 polygon_mode: wgpu::PolygonMode::Fill,
                 // Requires Features::DEPTH_CLIP_CONTROL
@@ -106,10 +325,10 @@ impl Pipeline {
                 depth_write_enabled: true,
                 depth_compare: wgpu::CompareFunction::Less,
                 stencil: wgpu::StencilState::default(),
-                bias: wgpu::DepthBiasState::default(),
+                bias,
             }),
             multisample: wgpu::MultisampleState {
-                count: 1,
+                count: sample_count,
                 mask: !0,
                 alpha_to_coverage_enabled: false,
             },
@@ -135,6 +354,19 @@ impl Pipeline {
         mesh.draw(render_pass);
     }
 
+    /// Draws the instances that are visible after `GpuCulling::cull`
+    pub fn draw_culled<'a>(
+        &self,
+        render_pass: &mut wgpu::RenderPass<'a>,
+        camera: &'a CameraUniformBuffer,
+        gpu_culling: &'a GpuCulling,
+        mesh: &'a Mesh,
+    ) {
+        render_pass.set_pipeline(&self.render_pipeline);
+        camera.bind(render_pass);
+        mesh.draw_culled(render_pass, gpu_culling);
+    }
+
     pub fn draw_lines<'a>(
         &self,
         render_pass: &mut wgpu::RenderPass<'a>,
@@ -145,4 +377,78 @@ impl Pipeline {
         camera.bind(render_pass);
         mesh.draw_lines(render_pass);
     }
+
+    /// Draws with a pipeline from `new_shadowed`
+    pub fn draw_shadowed<'a>(
+        &self,
+        render_pass: &mut wgpu::RenderPass<'a>,
+        camera: &'a CameraUniformBuffer,
+        shadow_map: &'a DirectionalShadowMap,
+        mesh: &'a dyn VertexColorShaderDraw,
+    ) {
+        render_pass.set_pipeline(&self.render_pipeline);
+        camera.bind(render_pass);
+        shadow_map.bind(render_pass, 1);
+        mesh.draw(render_pass);
+    }
+
+    /// Draws with a pipeline from `new_lit`
+    pub fn draw_lit<'a>(
+        &self,
+        render_pass: &mut wgpu::RenderPass<'a>,
+        camera: &'a CameraUniformBuffer,
+        lights: &'a LightUniformBuffer,
+        mesh: &'a dyn VertexColorShaderDrawLit,
+    ) {
+        render_pass.set_pipeline(&self.render_pipeline);
+        camera.bind(render_pass);
+        lights.bind(render_pass);
+        mesh.draw_lit(render_pass);
+    }
+
+    /// Draws with a pipeline from `new_lit_shadowed`
+    pub fn draw_lit_shadowed<'a>(
+        &self,
+        render_pass: &mut wgpu::RenderPass<'a>,
+        camera: &'a CameraUniformBuffer,
+        lights: &'a LightUniformBuffer,
+        shadow_map: &'a DirectionalShadowMap,
+        mesh: &'a dyn VertexColorShaderDrawLit,
+    ) {
+        render_pass.set_pipeline(&self.render_pipeline);
+        camera.bind(render_pass);
+        lights.bind(render_pass);
+        shadow_map.bind(render_pass, 2);
+        mesh.draw_lit(render_pass);
+    }
+
+    /// Draws with a pipeline from `new_lit_reflective`
+    pub fn draw_lit_reflective<'a>(
+        &self,
+        render_pass: &mut wgpu::RenderPass<'a>,
+        camera: &'a CameraUniformBuffer,
+        lights: &'a LightUniformBuffer,
+        environment: &'a Cubemap,
+        mesh: &'a dyn VertexColorShaderDrawLit,
+    ) {
+        render_pass.set_pipeline(&self.render_pipeline);
+        camera.bind(render_pass);
+        lights.bind(render_pass);
+        environment.bind(render_pass, 2);
+        mesh.draw_lit(render_pass);
+    }
+
+    /// Draws with a pipeline from `new_picking`
+    pub fn draw_picking<'a>(
+        &self,
+        render_pass: &mut wgpu::RenderPass<'a>,
+        camera: &'a CameraUniformBuffer,
+        picking_id: &'a PickingId,
+        mesh: &'a dyn VertexColorShaderDraw,
+    ) {
+        render_pass.set_pipeline(&self.render_pipeline);
+        camera.bind(render_pass);
+        picking_id.bind(render_pass, 1);
+        mesh.draw(render_pass);
+    }
 }
@@ -1,11 +1,30 @@
 // Vertex shader
-struct CameraUniform {
-    view_pos: vec4<f32>,
-    view_proj: mat4x4<f32>,
-};
+#include "camera"
+#include "instance"
+
+#ifdef LIGHTING
+#define LIGHT_GROUP 1
+#include "lighting"
+#endif
+
+#ifdef SHADOWS
+#ifdef LIGHTING
+#define SHADOW_GROUP 2
+#else
+#define SHADOW_GROUP 1
+#endif
+#include "shadow"
+#endif
 
-@group(0) @binding(0)
-var<uniform> camera: CameraUniform;
+#ifdef ENVIRONMENT
+#define ENVIRONMENT_GROUP 2
+#include "environment"
+#endif
+
+#ifdef PICKING
+#define PICKING_GROUP 1
+#include "picking"
+#endif
 
 struct VertexInput {
     @location(0) position: vec3<f32>,
@@ -15,40 +34,76 @@ struct ColorInput {
     @location(1) color: vec3<f32>,
 }
 
-struct InstanceInput {
-    @location(5) model_matrix_0: vec4<f32>,
-    @location(6) model_matrix_1: vec4<f32>,
-    @location(7) model_matrix_2: vec4<f32>,
-    @location(8) model_matrix_3: vec4<f32>,
+#ifdef LIGHTING
+struct NormalInput {
+    @location(2) normal: vec3<f32>,
 }
+#endif
 
 struct VertexOutput {
     @builtin(position) clip_position: vec4<f32>,
-    @location(0) color: vec3<f32>,
+    @location(0) color: vec4<f32>,
+    @location(1) world_position: vec3<f32>,
+#ifdef LIGHTING
+    @location(2) normal: vec3<f32>,
+    @location(3) view_position: vec3<f32>,
+#endif
+    @location(4) @interpolate(flat) instance_id: u32,
 };
 
 @vertex 
 fn vs_main(
     model: VertexInput,
     model_color: ColorInput,
+#ifdef LIGHTING
+    model_normal: NormalInput,
+#endif
     instance: InstanceInput,
 ) -> VertexOutput {
-    let model_matrix = mat4x4<f32>(
-        instance.model_matrix_0,
-        instance.model_matrix_1,
-        instance.model_matrix_2,
-        instance.model_matrix_3,
-    );
+    let model_matrix = instance_model_matrix(instance);
+    let world_position = model_matrix * vec4<f32>(model.position, 1.0);
 
     var out: VertexOutput;
-    out.color = model_color.color;
-    out.clip_position = camera.view_proj * model_matrix * vec4<f32>(model.position, 1.0);
+    out.color = vec4<f32>(model_color.color, 1.0) * instance.color;
+    out.clip_position = camera.view_proj * world_position;
+    out.world_position = world_position.xyz;
+    out.instance_id = instance.id;
+#ifdef LIGHTING
+    out.normal = instance_normal_matrix(instance) * model_normal.normal;
+    out.view_position = camera.view_pos.xyz;
+#endif
     return out;
 }
 
 // Fragment shader
+#include "surface_color"
 
 @fragment
 fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
-    return vec4<f32>(in.color, 1.0);
+    var color = in.color.rgb;
+#ifdef LIGHTING
+    color = lighting(color, in.normal, in.world_position, in.view_position);
+#endif
+#ifdef ENVIRONMENT
+    color = environment_reflection(
+        color,
+        in.normal,
+        in.world_position,
+        in.view_position,
+        lights.material.z,
+    );
+#endif
+#ifdef SHADOWS
+#ifndef LIGHTING
+    color *= shadow_light_factor(in.world_position);
+#endif
+#endif
+    return to_surface_color(vec4<f32>(color, in.color.a));
+}
+
+#ifdef PICKING
+@fragment
+fn fs_picking(in: VertexOutput) -> PickingOutput {
+    return picking_output(in.instance_id, in.clip_position.z);
 }
+#endif
@@ -0,0 +1,159 @@
+//! Unit tests
+
+use cgmath::One;
+
+use crate::golden_image::test_support::{
+    assert_golden, finish, identity_instance, perspective_camera, render_sphere, CLEAR_COLOR,
+    HEIGHT, WIDTH,
+};
+use crate::golden_image::{test_renderer, test_renderer_with_config};
+use crate::shape::{self, MeshDataInterface};
+use crate::vertex_color_shader;
+use crate::wgpu_renderer::camera::Camera;
+use crate::wgpu_renderer::{FramePass, OffscreenRenderer, RendererConfig, WgpuRendererInterface};
+
+#[test]
+fn vertex_color_shader_sphere() {
+    let Some(mut renderer) = test_renderer(WIDTH, HEIGHT) else {
+        return;
+    };
+    let image = render_sphere(&mut renderer);
+
+    assert_golden("vertex_color_shader_sphere", &image);
+}
+
+#[test]
+fn vertex_color_shader_sphere_msaa() {
+    let Some(mut renderer) = test_renderer_with_config(
+        WIDTH,
+        HEIGHT,
+        OffscreenRenderer::DEFAULT_FORMAT,
+        &RendererConfig::default().with_sample_count(4),
+    ) else {
+        return;
+    };
+    let image = render_sphere(&mut renderer);
+
+    assert_golden(
+        &format!("vertex_color_shader_sphere_msaa{}", renderer.sample_count()),
+        &image,
+    );
+}
+
+#[test]
+fn vertex_color_shader_instances() {
+    let Some(mut renderer) = test_renderer(WIDTH, HEIGHT) else {
+        return;
+    };
+    let surface_format = renderer.surface_format();
+    let sample_count = renderer.sample_count();
+
+    let camera_bind_group_layout =
+        vertex_color_shader::CameraBindGroupLayout::new(renderer.device());
+    let pipeline = vertex_color_shader::Pipeline::new(
+        renderer.device(),
+        &camera_bind_group_layout,
+        surface_format,
+        sample_count,
+    );
+
+    let camera = Camera::new((-8.0, 0.0, 0.0), cgmath::Deg(0.0), cgmath::Deg(0.0));
+    let camera_buffer = perspective_camera(&mut renderer, &camera_bind_group_layout, &camera);
+
+    // One mesh, differently scaled and tinted in a 4 x 3 grid
+    let instances: Vec<_> = (0..12)
+        .map(|index| {
+            let column = (index % 4) as f32;
+            let row = (index / 4) as f32;
+            vertex_color_shader::Instance::new(
+                cgmath::Vector3::new(0.0, 2.4 - 1.6 * column, 1.4 - 1.4 * row),
+                cgmath::Quaternion::one(),
+            )
+            .with_scale(cgmath::Vector3::new(
+                0.3,
+                0.2 + 0.15 * column,
+                0.2 + 0.2 * row,
+            ))
+            .with_color(cgmath::Vector4::new(
+                1.0 - column / 3.0,
+                row / 2.0,
+                column / 3.0,
+                1.0,
+            ))
+            .with_id(index)
+        })
+        .collect();
+
+    let sphere = shape::UVSphere::new(1.0, 16);
+    let mesh = vertex_color_shader::Mesh::from_shape(
+        renderer.device(),
+        sphere.triangles(),
+        &cgmath::Vector3::new(1.0, 1.0, 1.0),
+        &instances,
+    );
+
+    let mut frame = renderer.begin_frame().unwrap();
+    {
+        let mut render_pass = frame.begin_pass(&FramePass::scene(CLEAR_COLOR));
+        pipeline.draw(&mut render_pass, &camera_buffer, &mesh);
+    }
+    let actual = finish(&mut renderer, frame);
+
+    assert_golden("vertex_color_shader_instances", &actual);
+}
+
+#[test]
+fn vertex_color_shader_lit_sphere() {
+    let Some(mut renderer) = test_renderer(WIDTH, HEIGHT) else {
+        return;
+    };
+    let surface_format = renderer.surface_format();
+    let sample_count = renderer.sample_count();
+
+    let camera_bind_group_layout =
+        vertex_color_shader::CameraBindGroupLayout::new(renderer.device());
+    let light_bind_group_layout = vertex_color_shader::LightBindGroupLayout::new(renderer.device());
+    let pipeline = vertex_color_shader::Pipeline::new_lit(
+        renderer.device(),
+        &camera_bind_group_layout,
+        &light_bind_group_layout,
+        surface_format,
+        sample_count,
+    );
+
+    let camera = Camera::new((-5.0, 0.0, 0.0), cgmath::Deg(0.0), cgmath::Deg(0.0));
+    let camera_buffer = perspective_camera(&mut renderer, &camera_bind_group_layout, &camera);
+
+    let mut light_uniform = vertex_color_shader::LightUniform::new();
+    light_uniform.add_directional_light(
+        cgmath::Vector3::new(1.0, 1.0, -1.0),
+        cgmath::Vector3::new(1.0, 1.0, 1.0),
+        0.8,
+    );
+    light_uniform.add_point_light(
+        cgmath::Point3::new(-2.0, -2.0, -1.0),
+        cgmath::Vector3::new(0.2, 0.4, 1.0),
+        1.5,
+        5.0,
+    );
+    let mut light_buffer =
+        vertex_color_shader::LightUniformBuffer::new(renderer.device(), &light_bind_group_layout);
+    light_buffer.update(renderer.queue(), light_uniform);
+
+    let sphere = shape::UVSphere::new(1.0, 16);
+    let mesh = vertex_color_shader::Mesh::from_shape(
+        renderer.device(),
+        sphere.triangles(),
+        &cgmath::Vector3::new(0.9, 0.6, 0.3),
+        &[identity_instance()],
+    );
+
+    let mut frame = renderer.begin_frame().unwrap();
+    {
+        let mut render_pass = frame.begin_pass(&FramePass::scene(CLEAR_COLOR));
+        pipeline.draw_lit(&mut render_pass, &camera_buffer, &light_buffer, &mesh);
+    }
+    let actual = finish(&mut renderer, frame);
+
+    assert_golden("vertex_color_shader_lit_sphere", &actual);
+}
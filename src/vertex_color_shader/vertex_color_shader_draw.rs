@@ -5,3 +5,8 @@ pub trait VertexColorShaderDraw {
 pub trait VertexColorShaderDrawLines {
     fn draw_lines<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>);
 }
+
+/// Drawing with the lit pipeline needs the normals of the vertices
+pub trait VertexColorShaderDrawLit {
+    fn draw_lit<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>);
+}
@@ -1,6 +1,9 @@
 //! Contains the device buffers to render an object with this shader
 //!
 
+use std::cell::Cell;
+
+use crate::asset_manager::{Assets, Handle};
 use crate::wgpu_renderer::WgpuRendererInterface;
 
 use super::Heightmap;
@@ -19,7 +22,9 @@ use super::VertexBuffer;
 /// A general purpose shader using vertices, colors and an instance matrix
 pub struct Mesh {
     vertex_buffer: VertexBuffer,
-    texture_index: usize,
+    texture: Handle<Texture>,
+    /// The missing texture is only reported by the first `draw`
+    texture_missing_reported: Cell<bool>,
     heightmap_texture: HeightmapTexture,
     index_buffer: IndexBuffer<u32>,
     instance_buffer: InstanceBuffer<InstanceRaw>,
@@ -29,7 +34,7 @@ impl Mesh {
     pub fn new(
         renderer: &mut dyn WgpuRendererInterface,
         vertices: &[Vertex],
-        texture_index: usize,
+        texture: Handle<Texture>,
         heightmap2d: &Heightmap2D,
         heightmap_bind_group_layout: &HeightmapBindGroupLayout,
         indices: &[u32],
@@ -51,7 +56,8 @@ impl Mesh {
 
         Self {
             vertex_buffer,
-            texture_index,
+            texture,
+            texture_missing_reported: Cell::new(false),
             heightmap_texture,
             index_buffer,
             instance_buffer,
@@ -62,8 +68,9 @@ impl Mesh {
         self.vertex_buffer.update(queue, vertices);
     }
 
-    pub fn _set_texture_index(&mut self, texture_index: usize) {
-        self.texture_index = texture_index;
+    pub fn set_texture(&mut self, texture: Handle<Texture>) {
+        self.texture = texture;
+        self.texture_missing_reported.set(false);
     }
 
     pub fn update_heightmap_texture(&mut self, queue: &wgpu::Queue, heightmap: &[Heightmap]) {
@@ -75,9 +82,21 @@ impl Mesh {
         self.instance_buffer.update(queue, instance_data);
     }
 
-    pub fn draw<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>, textures: &'a [Texture]) {
+    /// Nothing is drawn if the texture is not part of the given assets, this is logged once
+    pub fn draw<'a>(
+        &'a self,
+        render_pass: &mut wgpu::RenderPass<'a>,
+        textures: &'a Assets<Texture>,
+    ) {
+        let Some(texture) = textures.get(&self.texture) else {
+            if !self.texture_missing_reported.replace(true) {
+                log::warn!("Texture {:?} not found, mesh not drawn", self.texture);
+            }
+            return;
+        };
+
         self.vertex_buffer.bind(render_pass);
-        textures[self.texture_index].bind(render_pass);
+        texture.bind(render_pass);
         self.heightmap_texture.bind(render_pass);
         self.index_buffer.bind(render_pass);
         self.instance_buffer.bind_slot(render_pass, 1);
@@ -7,6 +7,9 @@ mod heightmap_texture;
 mod mesh;
 mod pipeline;
 
+#[cfg(test)]
+mod tests;
+
 pub use mesh::Mesh;
 pub use pipeline::Pipeline;
 
@@ -1,7 +1,11 @@
 //! A general purpose pipeline using vertices, textures, a heightmap and instances
 //!
 
+use super::super::picking::{PickingId, PickingIdBindGroupLayout};
+use super::super::shadow_map::{DirectionalShadowMap, ShadowBindGroupLayout};
 use super::super::wgpu_renderer::depth_texture::DepthTexture;
+use super::super::wgpu_renderer::fragment_output::FragmentOutput;
+use super::super::wgsl_preprocessor;
 use super::CameraBindGroupLayout;
 use super::HeightmapBindGroupLayout;
 use super::InstanceRaw;
@@ -20,25 +24,134 @@ impl Pipeline {
         texture_bind_group_layout: &TextureBindGroupLayout,
         heightmap_bind_group_layout: &HeightmapBindGroupLayout,
         surface_format: wgpu::TextureFormat,
+        sample_count: u32,
         shader_code: Option<&str>,
+    ) -> Self {
+        let shader_code =
+            wgsl_preprocessor::preprocess(shader_code.unwrap_or(include_str!("shader.wgsl")));
+
+        Self::create(
+            device,
+            &[
+                camera_bind_group_layout.get(),
+                texture_bind_group_layout.get(),
+                heightmap_bind_group_layout.get(),
+            ],
+            &shader_code,
+            FragmentOutput::surface(surface_format),
+            sample_count,
+        )
+    }
+
+    /// Only writes the depth, to draw the terrain into a cascade of a shadow map
+    pub fn new_depth_only(
+        device: &wgpu::Device,
+        camera_bind_group_layout: &CameraBindGroupLayout,
+        texture_bind_group_layout: &TextureBindGroupLayout,
+        heightmap_bind_group_layout: &HeightmapBindGroupLayout,
+    ) -> Self {
+        let shader_code = wgsl_preprocessor::preprocess(include_str!("shader.wgsl"));
+
+        Self::create(
+            device,
+            &[
+                camera_bind_group_layout.get(),
+                texture_bind_group_layout.get(),
+                heightmap_bind_group_layout.get(),
+            ],
+            &shader_code,
+            FragmentOutput::DepthOnly,
+            1,
+        )
+    }
+
+    /// Darkens the terrain where the light of the shadow map is blocked,
+    /// bind the shadow map with `bind_shadow_map`
+    pub fn new_shadowed(
+        device: &wgpu::Device,
+        camera_bind_group_layout: &CameraBindGroupLayout,
+        texture_bind_group_layout: &TextureBindGroupLayout,
+        heightmap_bind_group_layout: &HeightmapBindGroupLayout,
+        shadow_bind_group_layout: &ShadowBindGroupLayout,
+        surface_format: wgpu::TextureFormat,
+        sample_count: u32,
+    ) -> Self {
+        let shader_code =
+            wgsl_preprocessor::preprocess_with_defines(include_str!("shader.wgsl"), &["SHADOWS"]);
+
+        Self::create(
+            device,
+            &[
+                camera_bind_group_layout.get(),
+                texture_bind_group_layout.get(),
+                heightmap_bind_group_layout.get(),
+                shadow_bind_group_layout.get(),
+            ],
+            &shader_code,
+            FragmentOutput::surface(surface_format),
+            sample_count,
+        )
+    }
+
+    /// Writes the mesh id and the instance id into a `PickingTarget`,
+    /// bind the id with `bind_picking_id`
+    pub fn new_picking(
+        device: &wgpu::Device,
+        camera_bind_group_layout: &CameraBindGroupLayout,
+        texture_bind_group_layout: &TextureBindGroupLayout,
+        heightmap_bind_group_layout: &HeightmapBindGroupLayout,
+        picking_id_bind_group_layout: &PickingIdBindGroupLayout,
+    ) -> Self {
+        let shader_code =
+            wgsl_preprocessor::preprocess_with_defines(include_str!("shader.wgsl"), &["PICKING"]);
+
+        Self::create(
+            device,
+            &[
+                camera_bind_group_layout.get(),
+                texture_bind_group_layout.get(),
+                heightmap_bind_group_layout.get(),
+                picking_id_bind_group_layout.get(),
+            ],
+            &shader_code,
+            FragmentOutput::Picking,
+            1,
+        )
+    }
+
+    /// Shader variants are selected with the defines of the preprocessed code
+    fn create(
+        device: &wgpu::Device,
+        bind_group_layouts: &[&wgpu::BindGroupLayout],
+        shader_code: &str,
+        output: FragmentOutput,
+        sample_count: u32,
     ) -> Self {
         // Shader
         let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
             label: Some("Heightmap Shader"),
-            source: wgpu::ShaderSource::Wgsl(
-                shader_code.unwrap_or(include_str!("shader.wgsl")).into(),
-            ),
+            source: wgpu::ShaderSource::Wgsl(shader_code.into()),
         });
 
+        let constants = output.constants(shader_code);
+        let targets = output.targets();
+
+        // Against shadow acne, on slopes at a steep angle to the light
+        let bias = if output.is_depth_only() {
+            wgpu::DepthBiasState {
+                constant: 2,
+                slope_scale: 2.0,
+                clamp: 0.0,
+            }
+        } else {
+            wgpu::DepthBiasState::default()
+        };
+
         // Pipeline
         let render_pipeline_layout =
             device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
                 label: Some("Render Pipeline Layout"),
-                bind_group_layouts: &[
-                    camera_bind_group_layout.get(),
-                    texture_bind_group_layout.get(),
-                    heightmap_bind_group_layout.get(),
-                ],
+                bind_group_layouts,
                 immediate_size: 0,
             });
 
@@ -55,21 +168,21 @@ impl Pipeline {
                 ],
                 compilation_options: wgpu::PipelineCompilationOptions::default(),
             },
-            fragment: Some(wgpu::FragmentState {
+            fragment: output.entry_point().map(|entry_point| wgpu::FragmentState {
                 module: &shader,
-                entry_point: Some("fs_main"),
-                targets: &[Some(wgpu::ColorTargetState {
-                    format: surface_format,
-                    blend: Some(wgpu::BlendState::REPLACE),
-                    write_mask: wgpu::ColorWrites::ALL,
-                })],
-                compilation_options: wgpu::PipelineCompilationOptions::default(),
+                entry_point: Some(entry_point),
+                targets: &targets,
+                compilation_options: wgpu::PipelineCompilationOptions {
+                    constants: &constants,
+                    ..Default::default()
+                },
             }),
             primitive: wgpu::PrimitiveState {
                 topology: wgpu::PrimitiveTopology::TriangleList,
                 strip_index_format: None,
                 front_face: wgpu::FrontFace::Ccw, // counter-clockwise direction
-                cull_mode: Some(wgpu::Face::Back),
+                // Shadow casters are drawn from both sides
+                cull_mode: (!output.is_depth_only()).then_some(wgpu::Face::Back),
                 // Setting this to anything other than Fill requires Features::NON_FILL_POLYGON_MODE
                 polygon_mode: wgpu::PolygonMode::Fill,
                 // Requires Features::DEPTH_CLIP_CONTROL
@@ -82,10 +195,10 @@ impl Pipeline {
                 depth_write_enabled: true,
                 depth_compare: wgpu::CompareFunction::Less,
                 stencil: wgpu::StencilState::default(),
-                bias: wgpu::DepthBiasState::default(),
+                bias,
             }),
             multisample: wgpu::MultisampleState {
-                count: 1,
+                count: sample_count,
                 mask: !0,
                 alpha_to_coverage_enabled: false,
             },
@@ -99,4 +212,22 @@ impl Pipeline {
     pub fn bind<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>) {
         render_pass.set_pipeline(&self.render_pipeline);
     }
+
+    /// Binds the cascades for a pipeline from `new_shadowed`
+    pub fn bind_shadow_map<'a>(
+        &self,
+        render_pass: &mut wgpu::RenderPass<'a>,
+        shadow_map: &'a DirectionalShadowMap,
+    ) {
+        shadow_map.bind(render_pass, 3);
+    }
+
+    /// Binds the mesh id for a pipeline from `new_picking`
+    pub fn bind_picking_id<'a>(
+        &self,
+        render_pass: &mut wgpu::RenderPass<'a>,
+        picking_id: &'a PickingId,
+    ) {
+        picking_id.bind(render_pass, 3);
+    }
 }
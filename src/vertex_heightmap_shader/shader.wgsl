@@ -1,11 +1,16 @@
 // Vertex shader
-struct CameraUniform {
-    view_pos: vec4<f32>,
-    view_proj: mat4x4<f32>,
-};
+#include "camera"
+#include "instance"
+
+#ifdef SHADOWS
+#define SHADOW_GROUP 3
+#include "shadow"
+#endif
 
-@group(0) @binding(0)
-var<uniform> camera: CameraUniform;
+#ifdef PICKING
+#define PICKING_GROUP 3
+#include "picking"
+#endif
 
 @group(2) @binding(0)
 var t_heightmap: texture_2d<f32>;
@@ -15,16 +20,14 @@ struct VertexInput {
     @location(1) tex_coords: vec2<f32>,
 }
 
-struct InstanceInput {
-    @location(5) model_matrix_0: vec4<f32>,
-    @location(6) model_matrix_1: vec4<f32>,
-    @location(7) model_matrix_2: vec4<f32>,
-    @location(8) model_matrix_3: vec4<f32>,
-}
-
 struct VertexOutput {
     @builtin(position) clip_position: vec4<f32>,
     @location(0) tex_coords: vec2<f32>,
+#ifdef SHADOWS
+    @location(1) world_position: vec3<f32>,
+#endif
+    @location(2) color: vec4<f32>,
+    @location(3) @interpolate(flat) instance_id: u32,
 };
 
 @vertex 
@@ -33,12 +36,7 @@ fn vs_main(
     model: VertexInput,
     instance: InstanceInput,
 ) -> VertexOutput {
-    let model_matrix = mat4x4<f32>(
-        instance.model_matrix_0,
-        instance.model_matrix_1,
-        instance.model_matrix_2,
-        instance.model_matrix_3,
-    );
+    let model_matrix = instance_model_matrix(instance);
 
     let dim: vec2<u32> = textureDimensions(t_heightmap);
     let width = dim.x;
@@ -48,13 +46,21 @@ fn vs_main(
     let pos_rgb: vec4<f32> = textureLoad(t_heightmap, index, 0);
     let posz = pos_rgb.r;
 
+    let world_position = model_matrix * vec4<f32>(model.position.x, model.position.y, posz, 1.0);
+
     var out: VertexOutput;
     out.tex_coords = model.tex_coords;
-    out.clip_position = camera.view_proj * model_matrix * vec4<f32>(model.position.x, model.position.y, posz, 1.0);
+    out.color = instance.color;
+    out.instance_id = instance.id;
+    out.clip_position = camera.view_proj * world_position;
+#ifdef SHADOWS
+    out.world_position = world_position.xyz;
+#endif
     return out;
 }
 
 // Fragment shader
+#include "surface_color"
 
 @group(1) @binding(0)
 var t_diffuse: texture_2d<f32>;
@@ -63,5 +69,18 @@ var s_diffuse: sampler;
 
 @fragment
 fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
-    return textureSample(t_diffuse, s_diffuse, in.tex_coords);
+    let color = textureSample(t_diffuse, s_diffuse, in.tex_coords) * in.color;
+#ifdef SHADOWS
+    let light = shadow_light_factor(in.world_position);
+#else
+    let light = 1.0;
+#endif
+    return to_surface_color(vec4<f32>(color.rgb * light, color.a));
+}
+
+#ifdef PICKING
+@fragment
+fn fs_picking(in: VertexOutput) -> PickingOutput {
+    return picking_output(in.instance_id, in.clip_position.z);
 }
+#endif
@@ -0,0 +1,98 @@
+//! Unit tests
+
+use crate::asset_manager::Assets;
+use crate::golden_image::test_renderer;
+use crate::golden_image::test_support::{
+    assert_golden, checkerboard, finish, identity_instance, perspective_camera, CLEAR_COLOR,
+    HEIGHT, WIDTH,
+};
+use crate::vertex_heightmap_shader;
+use crate::wgpu_renderer::camera::Camera;
+use crate::wgpu_renderer::{FramePass, WgpuRendererInterface};
+
+#[test]
+fn vertex_heightmap_shader_terrain() {
+    let Some(mut renderer) = test_renderer(WIDTH, HEIGHT) else {
+        return;
+    };
+    let surface_format = renderer.surface_format();
+    let sample_count = renderer.sample_count();
+
+    let camera_bind_group_layout =
+        vertex_heightmap_shader::CameraBindGroupLayout::new(renderer.device());
+    let texture_bind_group_layout =
+        vertex_heightmap_shader::TextureBindGroupLayout::new(renderer.device());
+    let heightmap_bind_group_layout =
+        vertex_heightmap_shader::HeightmapBindGroupLayout::new(renderer.device());
+    let pipeline = vertex_heightmap_shader::Pipeline::new(
+        renderer.device(),
+        &camera_bind_group_layout,
+        &texture_bind_group_layout,
+        &heightmap_bind_group_layout,
+        surface_format,
+        sample_count,
+        None,
+    );
+
+    let camera = Camera::new((-3.0, 2.0, 3.0), cgmath::Deg(-35.0), cgmath::Deg(0.0));
+    let camera_buffer = perspective_camera(&mut renderer, &camera_bind_group_layout, &camera);
+
+    let texture = vertex_heightmap_shader::Texture::new(
+        &mut renderer,
+        &texture_bind_group_layout,
+        &checkerboard(64, 8),
+        Some("checkerboard"),
+    )
+    .unwrap();
+
+    const N: u32 = 16;
+    let mut vertices = Vec::new();
+    let mut heightmap = Vec::new();
+    for y in 0..N {
+        for x in 0..N {
+            let u = x as f32 / (N - 1) as f32;
+            let v = y as f32 / (N - 1) as f32;
+            vertices.push(vertex_heightmap_shader::Vertex {
+                position: [u * 4.0, v * 4.0, 0.0],
+                tex_coords: [u, v],
+            });
+            heightmap.push(vertex_heightmap_shader::Heightmap {
+                height: 0.5 * (u * 6.0).sin() * (v * 4.0).cos(),
+            });
+        }
+    }
+    let mut indices = Vec::new();
+    for y in 0..N - 1 {
+        for x in 0..N - 1 {
+            let i = y * N + x;
+            indices.extend_from_slice(&[i, i + 1, i + N + 1, i + N + 1, i + N, i]);
+        }
+    }
+
+    let mut textures = Assets::new();
+    let texture = textures.insert(texture);
+    let mesh = vertex_heightmap_shader::Mesh::new(
+        &mut renderer,
+        &vertices,
+        texture,
+        &vertex_heightmap_shader::Heightmap2D {
+            data: &heightmap,
+            width: N,
+            height: N,
+        },
+        &heightmap_bind_group_layout,
+        &indices,
+        &[identity_instance()],
+    );
+
+    let mut frame = renderer.begin_frame().unwrap();
+    {
+        let mut render_pass = frame.begin_pass(&FramePass::scene(CLEAR_COLOR));
+        pipeline.bind(&mut render_pass);
+        camera_buffer.bind(&mut render_pass);
+        mesh.draw(&mut render_pass, &textures);
+    }
+    let image = finish(&mut renderer, frame);
+
+    assert_golden("vertex_heightmap_shader_terrain", &image);
+}
@@ -1,6 +1,10 @@
 //! Contains the device buffers to render an object with this shader
 //!
 
+use std::cell::Cell;
+
+use crate::asset_manager::{Assets, Handle};
+
 use super::Instance;
 use super::InstanceRaw;
 use super::Texture;
@@ -14,7 +18,9 @@ use super::VertexBuffer;
 #[allow(dead_code)]
 pub struct Mesh {
     vertex_buffer: VertexBuffer,
-    texture_index: usize,
+    texture: Handle<Texture>,
+    /// The missing texture is only reported by the first `draw`
+    texture_missing_reported: Cell<bool>,
     index_buffer: IndexBuffer<u32>,
     instance_buffer: InstanceBuffer<InstanceRaw>,
 }
@@ -24,7 +30,7 @@ impl Mesh {
     pub fn new(
         device: &wgpu::Device,
         vertices: &[Vertex],
-        texture_index: usize,
+        texture: Handle<Texture>,
         indices: &[u32],
         instances: &[Instance],
     ) -> Self {
@@ -36,7 +42,8 @@ impl Mesh {
 
         Self {
             vertex_buffer,
-            texture_index,
+            texture,
+            texture_missing_reported: Cell::new(false),
             index_buffer,
             instance_buffer,
         }
@@ -46,8 +53,9 @@ impl Mesh {
         self.vertex_buffer.update(queue, vertices);
     }
 
-    pub fn _set_texture_index(&mut self, texture_index: usize) {
-        self.texture_index = texture_index;
+    pub fn set_texture(&mut self, texture: Handle<Texture>) {
+        self.texture = texture;
+        self.texture_missing_reported.set(false);
     }
 
     pub fn update_instance_buffer(&mut self, queue: &wgpu::Queue, instances: &[Instance]) {
@@ -55,9 +63,21 @@ impl Mesh {
         self.instance_buffer.update(queue, instance_data);
     }
 
-    pub fn draw<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>, textures: &'a [Texture]) {
+    /// Nothing is drawn if the texture is not part of the given assets, this is logged once
+    pub fn draw<'a>(
+        &'a self,
+        render_pass: &mut wgpu::RenderPass<'a>,
+        textures: &'a Assets<Texture>,
+    ) {
+        let Some(texture) = textures.get(&self.texture) else {
+            if !self.texture_missing_reported.replace(true) {
+                log::warn!("Texture {:?} not found, mesh not drawn", self.texture);
+            }
+            return;
+        };
+
         self.vertex_buffer.bind(render_pass);
-        textures[self.texture_index].bind(render_pass);
+        texture.bind(render_pass);
         self.index_buffer.bind(render_pass);
         self.instance_buffer.bind_slot(render_pass, 1);
 
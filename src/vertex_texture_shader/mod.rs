@@ -3,6 +3,7 @@
 
 mod mesh;
 mod pipeline;
+mod render_target;
 
 mod texture;
 mod texture_bind_group_layout;
@@ -10,8 +11,12 @@ mod vertex;
 mod vertex_buffer;
 mod vertex_texture_shader_draw;
 
+#[cfg(test)]
+mod tests;
+
 pub use mesh::Mesh;
 pub use pipeline::Pipeline;
+pub use render_target::RenderTarget;
 
 pub use texture::Texture;
 pub use texture_bind_group_layout::TextureBindGroupLayout;
@@ -1,7 +1,10 @@
 //! A general purpose pipeline using vertices, textures and instances
 //!
 
+use super::super::picking::{PickingId, PickingIdBindGroupLayout};
 use super::super::wgpu_renderer::depth_texture::DepthTexture;
+use super::super::wgpu_renderer::fragment_output::FragmentOutput;
+use super::super::wgsl_preprocessor;
 use super::CameraBindGroupLayout;
 use super::CameraUniformBuffer;
 use super::InstanceRaw;
@@ -22,14 +25,17 @@ impl Pipeline {
         camera_bind_group_layout: &CameraBindGroupLayout,
         texture_bind_group_layout: &TextureBindGroupLayout,
         surface_format: wgpu::TextureFormat,
+        sample_count: u32,
     ) -> Self {
         Self::new_parameterized(
             device,
             camera_bind_group_layout,
             texture_bind_group_layout,
             surface_format,
+            sample_count,
             wgpu::BlendState::REPLACE,
             wgpu::CompareFunction::Less,
+            None,
         )
     }
 
@@ -38,39 +44,98 @@ impl Pipeline {
         camera_bind_group_layout: &CameraBindGroupLayout,
         texture_bind_group_layout: &TextureBindGroupLayout,
         surface_format: wgpu::TextureFormat,
+        sample_count: u32,
     ) -> Self {
         Self::new_parameterized(
             device,
             camera_bind_group_layout,
             texture_bind_group_layout,
             surface_format,
+            sample_count,
             wgpu::BlendState::ALPHA_BLENDING,
             wgpu::CompareFunction::Always,
+            None,
         )
     }
 
+    /// Uses the bundled shader if `shader_code` is None
+    #[allow(clippy::too_many_arguments)]
     pub fn new_parameterized(
         device: &wgpu::Device,
         camera_bind_group_layout: &CameraBindGroupLayout,
         texture_bind_group_layout: &TextureBindGroupLayout,
         surface_format: wgpu::TextureFormat,
+        sample_count: u32,
         blend: wgpu::BlendState,
         depth_compare: wgpu::CompareFunction,
+        shader_code: Option<&str>,
+    ) -> Self {
+        let shader_code =
+            wgsl_preprocessor::preprocess(shader_code.unwrap_or(include_str!("shader.wgsl")));
+
+        Self::create(
+            device,
+            &[
+                camera_bind_group_layout.get(),
+                texture_bind_group_layout.get(),
+            ],
+            &shader_code,
+            FragmentOutput::Surface {
+                format: surface_format,
+                blend,
+            },
+            sample_count,
+            depth_compare,
+        )
+    }
+
+    /// Writes the mesh id and the instance id into a `PickingTarget`, draw with `draw_picking`
+    pub fn new_picking(
+        device: &wgpu::Device,
+        camera_bind_group_layout: &CameraBindGroupLayout,
+        texture_bind_group_layout: &TextureBindGroupLayout,
+        picking_id_bind_group_layout: &PickingIdBindGroupLayout,
+    ) -> Self {
+        let shader_code =
+            wgsl_preprocessor::preprocess_with_defines(include_str!("shader.wgsl"), &["PICKING"]);
+
+        Self::create(
+            device,
+            &[
+                camera_bind_group_layout.get(),
+                texture_bind_group_layout.get(),
+                picking_id_bind_group_layout.get(),
+            ],
+            &shader_code,
+            FragmentOutput::Picking,
+            1,
+            wgpu::CompareFunction::Less,
+        )
+    }
+
+    /// Shader variants are selected with the defines of the preprocessed code
+    fn create(
+        device: &wgpu::Device,
+        bind_group_layouts: &[&wgpu::BindGroupLayout],
+        shader_code: &str,
+        output: FragmentOutput,
+        sample_count: u32,
+        depth_compare: wgpu::CompareFunction,
     ) -> Self {
         // Shader
         let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
             label: Some("Texture Shader"),
-            source: wgpu::ShaderSource::Wgsl(include_str!("shader.wgsl").into()),
+            source: wgpu::ShaderSource::Wgsl(shader_code.into()),
         });
 
+        let constants = output.constants(shader_code);
+        let targets = output.targets();
+
         // Pipeline
         let render_pipeline_layout =
             device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
                 label: Some("Render Pipeline Layout"),
-                bind_group_layouts: &[
-                    camera_bind_group_layout.get(),
-                    texture_bind_group_layout.get(),
-                ],
+                bind_group_layouts,
                 immediate_size: 0,
             });
 
@@ -87,15 +152,14 @@ impl Pipeline {
                 ],
                 compilation_options: wgpu::PipelineCompilationOptions::default(),
             },
-            fragment: Some(wgpu::FragmentState {
+            fragment: output.entry_point().map(|entry_point| wgpu::FragmentState {
                 module: &shader,
-                entry_point: Some("fs_main"),
-                targets: &[Some(wgpu::ColorTargetState {
-                    format: surface_format,
-                    blend: Some(blend),
-                    write_mask: wgpu::ColorWrites::ALL,
-                })],
-                compilation_options: wgpu::PipelineCompilationOptions::default(),
+                entry_point: Some(entry_point),
+                targets: &targets,
+                compilation_options: wgpu::PipelineCompilationOptions {
+                    constants: &constants,
+                    ..Default::default()
+                },
             }),
             primitive: wgpu::PrimitiveState {
                 topology: wgpu::PrimitiveTopology::TriangleList,
@@ -117,7 +181,7 @@ impl Pipeline {
                 bias: wgpu::DepthBiasState::default(),
             }),
             multisample: wgpu::MultisampleState {
-                count: 1,
+                count: sample_count,
                 mask: !0,
                 alpha_to_coverage_enabled: false,
             },
@@ -142,4 +206,18 @@ impl Pipeline {
         camera.bind(render_pass);
         mesh.draw(render_pass);
     }
+
+    /// Draws with a pipeline from `new_picking`
+    pub fn draw_picking<'a>(
+        &self,
+        render_pass: &mut wgpu::RenderPass<'a>,
+        camera: &'a CameraUniformBuffer,
+        picking_id: &'a PickingId,
+        mesh: &'a dyn VertexTextureShaderDraw,
+    ) {
+        render_pass.set_pipeline(&self.render_pipeline);
+        camera.bind(render_pass);
+        picking_id.bind(render_pass, 2);
+        mesh.draw(render_pass);
+    }
 }
@@ -0,0 +1,195 @@
+//! A texture that the pipelines render into and that is drawn as a texture later
+//!
+//! E.g. for minimaps, picture-in-picture views or monitors in the scene. The passes are
+//! recorded with any encoder, e.g. `Frame::encoder` before the scene pass of the frame.
+//! The bundled pipelines draw into it when they are created with `format()` and
+//! `sample_count()` of the target, they need the depth texture.
+//!
+
+use super::super::wgpu_renderer::depth_texture::DepthTexture;
+use super::super::wgpu_renderer::surface_format;
+use super::super::wgpu_renderer::{FrameDepth, FramePass};
+use super::Texture;
+use super::TextureBindGroupLayout;
+
+pub struct RenderTarget {
+    texture: Texture,
+    depth_texture: Option<DepthTexture>,
+    format: wgpu::TextureFormat,
+    width: u32,
+    height: u32,
+}
+
+impl RenderTarget {
+    pub const DEFAULT_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8UnormSrgb;
+
+    /// A target with a depth texture, as needed by the bundled pipelines
+    pub fn new(
+        device: &wgpu::Device,
+        texture_bind_group_layout: &TextureBindGroupLayout,
+        width: u32,
+        height: u32,
+        format: wgpu::TextureFormat,
+    ) -> Self {
+        Self::create(
+            device,
+            texture_bind_group_layout,
+            width,
+            height,
+            format,
+            true,
+        )
+    }
+
+    /// Only for pipelines without a depth stencil state, passes need `FrameDepth::Disabled`
+    pub fn new_without_depth(
+        device: &wgpu::Device,
+        texture_bind_group_layout: &TextureBindGroupLayout,
+        width: u32,
+        height: u32,
+        format: wgpu::TextureFormat,
+    ) -> Self {
+        Self::create(
+            device,
+            texture_bind_group_layout,
+            width,
+            height,
+            format,
+            false,
+        )
+    }
+
+    fn create(
+        device: &wgpu::Device,
+        texture_bind_group_layout: &TextureBindGroupLayout,
+        width: u32,
+        height: u32,
+        format: wgpu::TextureFormat,
+        with_depth: bool,
+    ) -> Self {
+        let width = width.max(1);
+        let height = height.max(1);
+
+        let texture = Texture::new_render_attachment(
+            device,
+            texture_bind_group_layout,
+            width,
+            height,
+            format,
+            Some("render_target"),
+        );
+        let depth_texture = with_depth
+            .then(|| DepthTexture::new(device, width, height, "render_target_depth_texture"));
+
+        Self {
+            texture,
+            depth_texture,
+            format,
+            width,
+            height,
+        }
+    }
+
+    /// Creates new textures, textures taken before keep showing the old content
+    pub fn resize(
+        &mut self,
+        device: &wgpu::Device,
+        texture_bind_group_layout: &TextureBindGroupLayout,
+        width: u32,
+        height: u32,
+    ) {
+        let width = width.max(1);
+        let height = height.max(1);
+        if width == self.width && height == self.height {
+            return;
+        }
+
+        *self = Self::create(
+            device,
+            texture_bind_group_layout,
+            width,
+            height,
+            self.format,
+            self.depth_texture.is_some(),
+        );
+    }
+
+    /// Clone it to insert it into the assets of a mesh, it shares the content of the target
+    pub fn texture(&self) -> &Texture {
+        &self.texture
+    }
+
+    pub fn depth_texture(&self) -> Option<&DepthTexture> {
+        self.depth_texture.as_ref()
+    }
+
+    pub fn format(&self) -> wgpu::TextureFormat {
+        self.format
+    }
+
+    /// Render targets are not multisampled
+    pub fn sample_count(&self) -> u32 {
+        1
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Begins a render pass into the target, the depth is ignored without a depth texture
+    pub fn begin_pass<'a>(
+        &self,
+        encoder: &'a mut wgpu::CommandEncoder,
+        pass: &FramePass,
+    ) -> wgpu::RenderPass<'a> {
+        let load = match pass.clear_color {
+            Some(clear_color) => wgpu::LoadOp::Clear(surface_format::surface_clear_color(
+                clear_color,
+                self.format,
+            )),
+            None => wgpu::LoadOp::Load,
+        };
+
+        let depth_load = match pass.depth {
+            FrameDepth::Disabled => None,
+            FrameDepth::Load => Some(wgpu::LoadOp::Load),
+            FrameDepth::Clear(depth) => Some(wgpu::LoadOp::Clear(depth)),
+        };
+        let depth_stencil_attachment = match (depth_load, &self.depth_texture) {
+            (Some(load), Some(depth_texture)) => Some(wgpu::RenderPassDepthStencilAttachment {
+                view: &depth_texture.view,
+                depth_ops: Some(wgpu::Operations {
+                    load,
+                    store: wgpu::StoreOp::Store,
+                }),
+                stencil_ops: None,
+            }),
+            (Some(_), None) => {
+                log::warn!("Render target has no depth texture, pass begins without depth");
+                None
+            }
+            (None, _) => None,
+        };
+
+        encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: pass.label,
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: &self.texture.view,
+                depth_slice: None,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load,
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+            multiview_mask: None,
+        })
+    }
+}
@@ -1,27 +1,22 @@
 // Vertex shader
-struct CameraUniform {
-    view_pos: vec4<f32>,
-    view_proj: mat4x4<f32>,
-};
+#include "camera"
+#include "instance"
 
-@group(0) @binding(0)
-var<uniform> camera: CameraUniform;
+#ifdef PICKING
+#define PICKING_GROUP 2
+#include "picking"
+#endif
 
 struct VertexInput {
     @location(0) position: vec3<f32>,
     @location(1) tex_coords: vec2<f32>,
 }
 
-struct InstanceInput {
-    @location(5) model_matrix_0: vec4<f32>,
-    @location(6) model_matrix_1: vec4<f32>,
-    @location(7) model_matrix_2: vec4<f32>,
-    @location(8) model_matrix_3: vec4<f32>,
-}
-
 struct VertexOutput {
     @builtin(position) clip_position: vec4<f32>,
     @location(0) tex_coords: vec2<f32>,
+    @location(1) color: vec4<f32>,
+    @location(2) @interpolate(flat) instance_id: u32,
 };
 
 @vertex 
@@ -29,20 +24,18 @@ fn vs_main(
     model: VertexInput,
     instance: InstanceInput,
 ) -> VertexOutput {
-    let model_matrix = mat4x4<f32>(
-        instance.model_matrix_0,
-        instance.model_matrix_1,
-        instance.model_matrix_2,
-        instance.model_matrix_3,
-    );
+    let model_matrix = instance_model_matrix(instance);
 
     var out: VertexOutput;
     out.tex_coords = model.tex_coords;
+    out.color = instance.color;
+    out.instance_id = instance.id;
     out.clip_position = camera.view_proj * model_matrix * vec4<f32>(model.position, 1.0);
     return out;
 }
 
 // Fragment shader
+#include "surface_color"
 
 @group(1) @binding(0)
 var t_diffuse: texture_2d<f32>;
@@ -51,5 +44,12 @@ var s_diffuse: sampler;
 
 @fragment
 fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
-    return textureSample(t_diffuse, s_diffuse, in.tex_coords);
+    return to_surface_color(textureSample(t_diffuse, s_diffuse, in.tex_coords) * in.color);
+}
+
+#ifdef PICKING
+@fragment
+fn fs_picking(in: VertexOutput) -> PickingOutput {
+    return picking_output(in.instance_id, in.clip_position.z);
 }
+#endif
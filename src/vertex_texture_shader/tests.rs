@@ -0,0 +1,203 @@
+//! Unit tests
+
+use crate::asset_manager::Assets;
+use crate::golden_image::test_renderer;
+use crate::golden_image::test_support::{
+    assert_golden, checkerboard, finish, identity_instance, orthographic_camera, CLEAR_COLOR,
+    HEIGHT, WIDTH,
+};
+use crate::shape::{self, MeshDataInterface};
+use crate::vertex_color_shader;
+use crate::vertex_texture_shader;
+use crate::wgpu_renderer::camera::{Camera, Projection};
+use crate::wgpu_renderer::{FramePass, WgpuRendererInterface};
+
+#[test]
+fn vertex_texture_shader_quad() {
+    let Some(mut renderer) = test_renderer(WIDTH, HEIGHT) else {
+        return;
+    };
+    let surface_format = renderer.surface_format();
+    let sample_count = renderer.sample_count();
+
+    let camera_bind_group_layout =
+        vertex_texture_shader::CameraBindGroupLayout::new(renderer.device());
+    let texture_bind_group_layout =
+        vertex_texture_shader::TextureBindGroupLayout::new(renderer.device());
+    let pipeline = vertex_texture_shader::Pipeline::new(
+        renderer.device(),
+        &camera_bind_group_layout,
+        &texture_bind_group_layout,
+        surface_format,
+        sample_count,
+    );
+
+    let camera_buffer = orthographic_camera(&mut renderer, &camera_bind_group_layout);
+
+    let texture = vertex_texture_shader::Texture::new(
+        &mut renderer,
+        &texture_bind_group_layout,
+        &checkerboard(64, 8),
+        Some("checkerboard"),
+    )
+    .unwrap();
+
+    let vertices = [
+        vertex_texture_shader::Vertex {
+            position: [20.0, 10.0, 0.0],
+            tex_coords: [0.0, 1.0],
+        },
+        vertex_texture_shader::Vertex {
+            position: [140.0, 10.0, 0.0],
+            tex_coords: [1.0, 1.0],
+        },
+        vertex_texture_shader::Vertex {
+            position: [140.0, 110.0, 0.0],
+            tex_coords: [1.0, 0.0],
+        },
+        vertex_texture_shader::Vertex {
+            position: [20.0, 110.0, 0.0],
+            tex_coords: [0.0, 0.0],
+        },
+    ];
+    let indices = [0, 1, 2, 2, 3, 0];
+    let mut textures = Assets::new();
+    let texture = textures.insert(texture);
+    let mesh = vertex_texture_shader::Mesh::new(
+        renderer.device(),
+        &vertices,
+        texture,
+        &indices,
+        &[identity_instance()],
+    );
+
+    let mut frame = renderer.begin_frame().unwrap();
+    {
+        let mut render_pass = frame.begin_pass(&FramePass::scene(CLEAR_COLOR));
+        pipeline.bind(&mut render_pass);
+        camera_buffer.bind(&mut render_pass);
+        mesh.draw(&mut render_pass, &textures);
+    }
+    let image = finish(&mut renderer, frame);
+
+    assert_golden("vertex_texture_shader_quad", &image);
+}
+
+#[test]
+fn render_target_picture_in_picture() {
+    let Some(mut renderer) = test_renderer(WIDTH, HEIGHT) else {
+        return;
+    };
+    let surface_format = renderer.surface_format();
+    let sample_count = renderer.sample_count();
+
+    let camera_bind_group_layout =
+        vertex_color_shader::CameraBindGroupLayout::new(renderer.device());
+    let texture_bind_group_layout =
+        vertex_texture_shader::TextureBindGroupLayout::new(renderer.device());
+    let render_target = vertex_texture_shader::RenderTarget::new(
+        renderer.device(),
+        &texture_bind_group_layout,
+        64,
+        64,
+        vertex_texture_shader::RenderTarget::DEFAULT_FORMAT,
+    );
+
+    // The sphere is drawn into the target
+    let color_pipeline = vertex_color_shader::Pipeline::new(
+        renderer.device(),
+        &camera_bind_group_layout,
+        render_target.format(),
+        render_target.sample_count(),
+    );
+    let camera = Camera::new((-5.0, 0.0, 0.0), cgmath::Deg(0.0), cgmath::Deg(0.0));
+    let projection = Projection::new(64, 64, cgmath::Deg(45.0), 0.1, 100.0);
+    let mut camera_uniform = vertex_color_shader::CameraUniform::new();
+    camera_uniform.update_view_proj(&camera, &projection);
+    let mut sphere_camera_buffer =
+        vertex_color_shader::CameraUniformBuffer::new(renderer.device(), &camera_bind_group_layout);
+    sphere_camera_buffer.update(renderer.queue(), camera_uniform);
+
+    let sphere = shape::UVSphere::new(1.5, 16);
+    let triangles = sphere.triangles();
+    let vertices: Vec<_> = triangles
+        .positions
+        .iter()
+        .map(|position| vertex_color_shader::Vertex {
+            position: (*position).into(),
+        })
+        .collect();
+    let colors: Vec<_> = triangles
+        .positions
+        .iter()
+        .map(|position| vertex_color_shader::Color {
+            color: (position * 0.3 + cgmath::Vector3::new(0.5, 0.5, 0.5)).into(),
+        })
+        .collect();
+    let sphere_mesh = vertex_color_shader::Mesh::new(
+        renderer.device(),
+        &vertices,
+        &colors,
+        &triangles.indices,
+        &[identity_instance()],
+    );
+
+    // The target is shown on a quad in the frame
+    let texture_pipeline = vertex_texture_shader::Pipeline::new(
+        renderer.device(),
+        &camera_bind_group_layout,
+        &texture_bind_group_layout,
+        surface_format,
+        sample_count,
+    );
+    let camera_buffer = orthographic_camera(&mut renderer, &camera_bind_group_layout);
+    let mut textures = Assets::new();
+    let texture = textures.insert(render_target.texture().clone());
+    let vertices = [
+        vertex_texture_shader::Vertex {
+            position: [40.0, 20.0, 0.0],
+            tex_coords: [0.0, 1.0],
+        },
+        vertex_texture_shader::Vertex {
+            position: [120.0, 20.0, 0.0],
+            tex_coords: [1.0, 1.0],
+        },
+        vertex_texture_shader::Vertex {
+            position: [120.0, 100.0, 0.0],
+            tex_coords: [1.0, 0.0],
+        },
+        vertex_texture_shader::Vertex {
+            position: [40.0, 100.0, 0.0],
+            tex_coords: [0.0, 0.0],
+        },
+    ];
+    let quad = vertex_texture_shader::Mesh::new(
+        renderer.device(),
+        &vertices,
+        texture,
+        &[0, 1, 2, 2, 3, 0],
+        &[identity_instance()],
+    );
+
+    let mut frame = renderer.begin_frame().unwrap();
+    {
+        let target_clear_color = wgpu::Color {
+            r: 0.9,
+            g: 0.9,
+            b: 0.8,
+            a: 1.0,
+        };
+        let mut render_pass =
+            render_target.begin_pass(frame.encoder(), &FramePass::scene(target_clear_color));
+        color_pipeline.draw(&mut render_pass, &sphere_camera_buffer, &sphere_mesh);
+    }
+    {
+        let mut render_pass = frame.begin_pass(&FramePass::scene(CLEAR_COLOR));
+        texture_pipeline.bind(&mut render_pass);
+        camera_buffer.bind(&mut render_pass);
+        quad.draw(&mut render_pass, &textures);
+    }
+    let image = finish(&mut renderer, frame);
+
+    assert_golden("render_target_picture_in_picture", &image);
+}
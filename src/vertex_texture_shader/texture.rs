@@ -6,6 +6,8 @@ use super::texture_bind_group_layout::TextureBindGroupLayout;
 use anyhow::*;
 use image::Rgba;
 
+/// Clones share the texture on the device, e.g. to put the texture of a `RenderTarget` into assets
+#[derive(Clone)]
 pub struct Texture {
     pub texture: wgpu::Texture,
     pub view: wgpu::TextureView,
@@ -66,22 +68,12 @@ impl Texture {
                 ..Default::default()
             });
 
-        let bind_group = wgpu_renderer
-            .device()
-            .create_bind_group(&wgpu::BindGroupDescriptor {
-                layout: texture_bind_group_layout.get(),
-                entries: &[
-                    wgpu::BindGroupEntry {
-                        binding: 0,
-                        resource: wgpu::BindingResource::TextureView(&view),
-                    },
-                    wgpu::BindGroupEntry {
-                        binding: 1,
-                        resource: wgpu::BindingResource::Sampler(&sampler),
-                    },
-                ],
-                label: Some("texture_bind_group"),
-            });
+        let bind_group = Self::create_bind_group(
+            wgpu_renderer.device(),
+            texture_bind_group_layout,
+            &view,
+            &sampler,
+        );
 
         Ok(Self {
             texture,
@@ -92,6 +84,78 @@ impl Texture {
         })
     }
 
+    /// An empty texture that can be rendered into and sampled afterwards
+    pub fn new_render_attachment(
+        device: &wgpu::Device,
+        texture_bind_group_layout: &TextureBindGroupLayout,
+        width: u32,
+        height: u32,
+        format: wgpu::TextureFormat,
+        label: Option<&str>,
+    ) -> Self {
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label,
+            size: wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT
+                | wgpu::TextureUsages::TEXTURE_BINDING
+                | wgpu::TextureUsages::COPY_SRC
+                | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        });
+
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        // Clamped, repeating the edge of a rendered view looks wrong
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            mipmap_filter: wgpu::MipmapFilterMode::Nearest,
+            ..Default::default()
+        });
+        let bind_group =
+            Self::create_bind_group(device, texture_bind_group_layout, &view, &sampler);
+
+        Self {
+            texture,
+            view,
+            sampler,
+            bind_group,
+            nr_mipmaps: 1,
+        }
+    }
+
+    fn create_bind_group(
+        device: &wgpu::Device,
+        texture_bind_group_layout: &TextureBindGroupLayout,
+        view: &wgpu::TextureView,
+        sampler: &wgpu::Sampler,
+    ) -> wgpu::BindGroup {
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: texture_bind_group_layout.get(),
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(sampler),
+                },
+            ],
+            label: Some("texture_bind_group"),
+        })
+    }
+
     fn write_texture(
         queue: &wgpu::Queue,
         texture: &wgpu::Texture,
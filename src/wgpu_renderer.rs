@@ -3,9 +3,30 @@
 
 pub mod camera;
 pub mod depth_texture;
+pub(crate) mod fragment_output;
+pub mod frame;
+pub mod frame_capture;
+pub mod gpu_context;
+pub mod msaa_texture;
+pub mod offscreen_renderer;
+pub mod renderer_config;
+pub mod renderer_error;
+pub mod shader_hot_reload;
+pub mod surface_format;
+
+#[cfg(test)]
+mod tests;
+
+pub use frame::{Frame, FrameDepth, FramePass};
+pub use gpu_context::GpuContext;
+pub use offscreen_renderer::OffscreenRenderer;
+pub use renderer_config::RendererConfig;
+pub use renderer_error::RendererError;
+pub use shader_hot_reload::HotReloadPipeline;
 
 use std::sync::Arc;
 
+use super::post_processing::PostProcessing;
 use winit::{dpi::PhysicalSize, window::Window};
 
 pub trait WgpuRendererInterface {
@@ -15,10 +36,68 @@ pub trait WgpuRendererInterface {
     fn surface_width(&self) -> u32;
     fn surface_height(&self) -> u32;
     fn surface_format(&self) -> wgpu::TextureFormat;
+    /// The format the scene pipelines target, `Rgba16Float` if HDR is enabled, else the surface format
+    ///
+    /// Pipelines drawing after `Frame::apply_post_processing`, e.g. a GUI overlay, use the surface format.
+    fn scene_format(&self) -> wgpu::TextureFormat {
+        self.surface_format()
+    }
     fn get_depth_texture_view(&self) -> &wgpu::TextureView;
+    /// Number of samples per pixel of the color and the depth target, 1 if MSAA is disabled
+    fn sample_count(&self) -> u32;
+    /// The multisampled color target, that has to be resolved into the surface texture
+    fn get_msaa_texture_view(&self) -> Option<&wgpu::TextureView>;
     fn get_current_texture(&self) -> Result<wgpu::SurfaceTexture, wgpu::SurfaceError>;
     fn enable_vsync(&mut self, enabled: bool);
     fn request_window_size(&mut self, width: u32, height: u32);
+
+    /// The effects applied to the frames, None if the renderer does not support them
+    fn post_processing(&mut self) -> Option<&mut PostProcessing> {
+        None
+    }
+
+    /// Acquires the next surface texture and creates the encoder for its render passes
+    fn begin_frame(&mut self) -> Result<Frame, wgpu::SurfaceError> {
+        let surface_texture = self.get_current_texture()?;
+        let texture = surface_texture.texture.clone();
+        let msaa_view = self.get_msaa_texture_view().cloned();
+        let depth_view = self.get_depth_texture_view().clone();
+        let device = self.device().clone();
+        let queue = self.queue().clone();
+        let post_processing = self
+            .post_processing()
+            .and_then(|post_processing| post_processing.passes(&device));
+
+        Ok(Frame::new(
+            &device,
+            &queue,
+            Some(surface_texture),
+            texture,
+            msaa_view,
+            depth_view,
+        )
+        .with_post_processing(post_processing))
+    }
+
+    /// Copies the content of a color target (e.g. the current surface texture) into an image
+    ///
+    /// Needs to be called after the frame was submitted, but before it is presented
+    fn capture_frame(&mut self, texture: &wgpu::Texture) -> anyhow::Result<image::RgbaImage> {
+        let device = self.device().clone();
+        let queue = self.queue().clone();
+        frame_capture::capture_texture(&device, &queue, texture)
+    }
+
+    /// Captures a color target and stores it as png file
+    fn save_frame(
+        &mut self,
+        texture: &wgpu::Texture,
+        path: &std::path::Path,
+    ) -> anyhow::Result<()> {
+        let image = self.capture_frame(texture)?;
+        image.save_with_format(path, image::ImageFormat::Png)?;
+        Ok(())
+    }
 }
 
 pub struct WgpuRenderer {
@@ -27,163 +106,167 @@ pub struct WgpuRenderer {
     queue: wgpu::Queue,
     config: wgpu::SurfaceConfiguration,
     size: winit::dpi::PhysicalSize<u32>,
+    sample_count: u32,
+    msaa_texture: Option<msaa_texture::MsaaTexture>,
     depth_texture: depth_texture::DepthTexture,
+    post_processing: PostProcessing,
+    context: GpuContext,
 
     window: Arc<Window>,
 }
 
 impl WgpuRenderer {
-    pub async fn new(window: Arc<Window>, present_mode: Option<wgpu::PresentMode>) -> Self {
-        let present_mode = present_mode.unwrap_or(wgpu::PresentMode::Fifo);
-
-        let size = PhysicalSize {
-            width: 800,
-            height: 600,
-        };
-
+    pub async fn new(
+        window: Arc<Window>,
+        renderer_config: &RendererConfig,
+    ) -> Result<Self, RendererError> {
         // The instance is a handle to our GPU
-        // Backends::all => Vulkan + Metal + DX12 + Browser WebGPU
-        let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor {
-            backends: wgpu::Backends::all(),
-            flags: wgpu::InstanceFlags::default(),
-            backend_options: wgpu::BackendOptions::default(),
-            memory_budget_thresholds: wgpu::MemoryBudgetThresholds {
-                for_resource_creation: None,
-                for_device_loss: None,
-            },
-            // dx12_shader_compiler: Default::default(),
-            // gles_minor_version: wgpu::Gles3MinorVersion::default(),
-        });
+        let instance = wgpu::Instance::new(&renderer_config.instance_descriptor());
         log::info!("Instance created");
 
         // # Safety
         //
         // The surface needs to live as long as the window that created it
         // State owns the window so this should be safe
-        let surface = { instance.create_surface(window.clone()) }.unwrap();
+        let surface = { instance.create_surface(window.clone()) }?;
+        log::info!("Surface created");
+
+        let context = GpuContext::new(instance, Some(&surface), renderer_config).await?;
+
+        Self::new_with_surface(window, surface, context, renderer_config)
+    }
+
+    /// Renders into another window with the device of an existing renderer
+    pub fn new_with_context(
+        window: Arc<Window>,
+        context: &GpuContext,
+        renderer_config: &RendererConfig,
+    ) -> Result<Self, RendererError> {
+        let surface = context.instance().create_surface(window.clone())?;
         log::info!("Surface created");
 
-        let adapter = instance
-            .request_adapter(&wgpu::RequestAdapterOptions {
-                power_preference: wgpu::PowerPreference::HighPerformance,
-                compatible_surface: Some(&surface),
-                force_fallback_adapter: false,
-            })
-            .await
-            .unwrap();
-        log::info!("Adapter created");
-
-        // let downlevel_capabilities = adapter.get_downlevel_capabilities();
-        // let downlevel_flags = downlevel_capabilities.flags;
-        // let vertex_storage = downlevel_flags.contains(wgpu::DownlevelFlags::VERTEX_STORAGE);
-        // let compute_shader = downlevel_flags.contains(wgpu::DownlevelFlags::COMPUTE_SHADERS);
-
-        // log::error!("vertex storage: {}", vertex_storage);
-        // log::error!("compute shader: {}", compute_shader);
-
-        let (device, queue) = adapter
-            .request_device(&wgpu::DeviceDescriptor {
-                required_features: wgpu::Features::empty(),
-                // WebGL doesn't support all of wgpu's features, so if
-                // we're building for the web we'll have to disable some.
-                required_limits: if cfg!(target_arch = "wasm32") {
-                    let mut defaults = wgpu::Limits::downlevel_webgl2_defaults();
-                    defaults.max_texture_dimension_2d = 4096;
-                    // defaults.max_color_attachment_bytes_per_sample = 64;
-                    defaults.max_buffer_size = 1024 << 20; // (1 GiB)
-                    defaults
-                } else {
-                    wgpu::Limits {
-                        // max_color_attachment_bytes_per_sample: 64,
-                        max_buffer_size: 1024 << 20, // (1 GiB)
-                        ..Default::default()
-                    }
-                },
-                label: None,
-                memory_hints: wgpu::MemoryHints::default(),
-                experimental_features: wgpu::ExperimentalFeatures::disabled(),
-                trace: wgpu::Trace::Off,
-            })
-            .await
-            .unwrap();
-        log::info!("Device created");
-
-        let surface_caps = surface.get_capabilities(&adapter);
-        // Shader code in this tutorial assumes an sRGB surface texture. Using a different
-        // one will result all the colors coming out darker. If you want to support non
-        // sRGB surfaces, you'll need to account to that when drawing to the frame.
-        // #[allow(clippy::filter_next)]
-        // let surface_format = surface_caps
-        //     .formats
-        //     .iter()
-        //     .copied()
-        //     .filter(|f| f.is_srgb())
-        //     .next()
-        //     .unwrap_or(surface_caps.formats[0]);
-
-        let config = wgpu::SurfaceConfiguration {
-            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
-            format: if surface_caps
-                .formats
-                .contains(&wgpu::TextureFormat::Rgba8UnormSrgb)
-            {
-                wgpu::TextureFormat::Rgba8UnormSrgb
-            } else if surface_caps
-                .formats
-                .contains(&wgpu::TextureFormat::Rgba8Unorm)
-            {
-                wgpu::TextureFormat::Rgba8Unorm
-            } else {
-                log::info!("surface_caps {:?}", surface_caps);
-                panic!("No suitable Texture Format found");
-            },
-            width: size.width,
-            height: size.height,
-            present_mode: {
-                if surface_caps.present_modes.contains(&present_mode) {
-                    present_mode
-                } else {
-                    wgpu::PresentMode::Fifo // default, vsync on
-                }
-            },
-            desired_maximum_frame_latency: 2,
-            alpha_mode: surface_caps.alpha_modes[0],
-            view_formats: vec![],
+        if !context.adapter().is_surface_supported(&surface) {
+            return Err(RendererError::IncompatibleSurface);
+        }
+
+        Self::new_with_surface(window, surface, context.clone(), renderer_config)
+    }
+
+    fn new_with_surface(
+        window: Arc<Window>,
+        surface: wgpu::Surface<'static>,
+        context: GpuContext,
+        renderer_config: &RendererConfig,
+    ) -> Result<Self, RendererError> {
+        let size = PhysicalSize {
+            width: 800,
+            height: 600,
+        };
+
+        let adapter = context.adapter();
+        let device = context.device().clone();
+        let queue = context.queue().clone();
+
+        let surface_caps = surface.get_capabilities(adapter);
+
+        // Non sRGB formats are gamma corrected in the shaders
+        let Some(format) = surface_format::select_surface_format(&surface_caps.formats) else {
+            log::info!("surface_caps {:?}", surface_caps);
+            return Err(RendererError::UnsupportedSurfaceFormat(
+                surface_caps.formats,
+            ));
         };
+        log::info!("Surface format: {:?}", format);
+
+        let config =
+            renderer_config.surface_configuration(&surface_caps, format, size.width, size.height);
 
         surface.configure(&device, &config);
         log::info!("Surface configured");
 
-        let depth_texture =
-            depth_texture::DepthTexture::create_depth_texture(&device, &config, "depth_texture");
+        let sample_count = renderer_config.supported_sample_count(adapter, format);
+        let msaa_texture = msaa_texture::MsaaTexture::create_optional(
+            &device,
+            format,
+            size.width,
+            size.height,
+            sample_count,
+        );
+        let depth_texture = depth_texture::DepthTexture::new_multisampled(
+            &device,
+            size.width,
+            size.height,
+            sample_count,
+            "depth_texture",
+        );
         log::info!("Depth texture created");
 
-        Self {
+        let post_processing = PostProcessing::new(
+            &device,
+            &queue,
+            format,
+            renderer_config.is_hdr(),
+            sample_count,
+            size.width,
+            size.height,
+        );
+
+        Ok(Self {
             surface,
             device,
             queue,
             config,
             size,
+            sample_count,
+            msaa_texture,
             depth_texture,
+            post_processing,
+            context,
 
             window,
-        }
+        })
     }
 
     pub fn size(&self) -> winit::dpi::PhysicalSize<u32> {
         self.size
     }
 
+    /// The device, that can be shared with the renderers of other windows
+    pub fn context(&self) -> &GpuContext {
+        &self.context
+    }
+
+    pub fn window(&self) -> &Arc<Window> {
+        &self.window
+    }
+
+    /// True after the device was lost, e.g. because of a driver reset
+    ///
+    /// The renderer can not be used anymore and has to be replaced by a new one.
+    pub fn is_device_lost(&self) -> bool {
+        self.context.is_device_lost()
+    }
+
     pub fn resize(&mut self, new_size: winit::dpi::PhysicalSize<u32>) {
         if new_size.width > 0 && new_size.height > 0 {
             self.size = new_size;
             self.config.width = new_size.width;
             self.config.height = new_size.height;
-            self.depth_texture = depth_texture::DepthTexture::create_depth_texture(
+            self.msaa_texture = msaa_texture::MsaaTexture::create_optional(
+                &self.device,
+                self.config.format,
+                new_size.width,
+                new_size.height,
+                self.sample_count,
+            );
+            self.depth_texture = depth_texture::DepthTexture::new_multisampled(
                 &self.device,
-                &self.config,
+                new_size.width,
+                new_size.height,
+                self.sample_count,
                 "depth_texture",
             );
+            self.post_processing.resize(new_size.width, new_size.height);
             self.surface.configure(&self.device, &self.config)
         }
     }
@@ -234,6 +317,16 @@ impl WgpuRendererInterface for WgpuRenderer {
         &self.depth_texture.view
     }
 
+    fn sample_count(&self) -> u32 {
+        self.sample_count
+    }
+
+    fn get_msaa_texture_view(&self) -> Option<&wgpu::TextureView> {
+        self.msaa_texture
+            .as_ref()
+            .map(|msaa_texture| &msaa_texture.view)
+    }
+
     fn get_current_texture(&self) -> Result<wgpu::SurfaceTexture, wgpu::SurfaceError> {
         self.surface.get_current_texture()
     }
@@ -253,4 +346,12 @@ impl WgpuRendererInterface for WgpuRenderer {
             .window
             .request_inner_size(PhysicalSize::new(width, height));
     }
+
+    fn scene_format(&self) -> wgpu::TextureFormat {
+        self.post_processing.scene_format()
+    }
+
+    fn post_processing(&mut self) -> Option<&mut PostProcessing> {
+        Some(&mut self.post_processing)
+    }
 }
@@ -14,27 +14,100 @@ impl DepthTexture {
         device: &wgpu::Device,
         config: &wgpu::SurfaceConfiguration,
         label: &str,
+    ) -> Self {
+        Self::new(device, config.width, config.height, label)
+    }
+
+    pub fn new(device: &wgpu::Device, width: u32, height: u32, label: &str) -> Self {
+        Self::new_multisampled(device, width, height, 1, label)
+    }
+
+    /// The sample count needs to match the color target it is used with
+    pub fn new_multisampled(
+        device: &wgpu::Device,
+        width: u32,
+        height: u32,
+        sample_count: u32,
+        label: &str,
     ) -> Self {
         let size = wgpu::Extent3d {
-            width: config.width,
-            height: config.height,
+            width,
+            height,
             depth_or_array_layers: 1,
         };
 
+        // Multisampled depth can not be sampled with the comparison sampler, it is only an attachment
+        let usage = if sample_count > 1 {
+            wgpu::TextureUsages::RENDER_ATTACHMENT
+        } else {
+            wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING
+        };
+
         let desc = wgpu::TextureDescriptor {
             label: Some(label),
             size,
             mip_level_count: 1,
-            sample_count: 1,
+            sample_count,
             dimension: wgpu::TextureDimension::D2,
             format: Self::DEPTH_FORMAT,
-            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+            usage,
             view_formats: Default::default(),
         };
         let texture = device.create_texture(&desc);
 
         let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
-        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+        let sampler = Self::create_comparison_sampler(device);
+
+        Self {
+            texture,
+            view,
+            sampler,
+        }
+    }
+
+    /// A texture with several layers, e.g. for the cascades of a shadow map
+    ///
+    /// The view covers all layers, single layers are rendered with `layer_view`.
+    pub fn new_array(device: &wgpu::Device, size: u32, layers: u32, label: &str) -> Self {
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some(label),
+            size: wgpu::Extent3d {
+                width: size,
+                height: size,
+                depth_or_array_layers: layers,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: Self::DEPTH_FORMAT,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+            view_formats: Default::default(),
+        });
+
+        let view = texture.create_view(&wgpu::TextureViewDescriptor {
+            dimension: Some(wgpu::TextureViewDimension::D2Array),
+            ..Default::default()
+        });
+        let sampler = Self::create_comparison_sampler(device);
+
+        Self {
+            texture,
+            view,
+            sampler,
+        }
+    }
+
+    pub fn layer_view(&self, layer: u32) -> wgpu::TextureView {
+        self.texture.create_view(&wgpu::TextureViewDescriptor {
+            dimension: Some(wgpu::TextureViewDimension::D2),
+            base_array_layer: layer,
+            array_layer_count: Some(1),
+            ..Default::default()
+        })
+    }
+
+    fn create_comparison_sampler(device: &wgpu::Device) -> wgpu::Sampler {
+        device.create_sampler(&wgpu::SamplerDescriptor {
             address_mode_u: wgpu::AddressMode::ClampToEdge,
             address_mode_v: wgpu::AddressMode::ClampToEdge,
             address_mode_w: wgpu::AddressMode::ClampToEdge,
@@ -45,12 +118,6 @@ impl DepthTexture {
             lod_min_clamp: 0.0,
             lod_max_clamp: 100.0,
             ..Default::default()
-        });
-
-        Self {
-            texture,
-            view,
-            sampler,
-        }
+        })
     }
 }
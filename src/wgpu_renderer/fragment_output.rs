@@ -0,0 +1,66 @@
+//! The outputs of the variants of a pipeline, e.g. the surface, only the depth or the picking ids
+//!
+
+use super::surface_format;
+use crate::picking::PickingTarget;
+
+#[derive(Copy, Clone)]
+pub(crate) enum FragmentOutput {
+    Surface {
+        format: wgpu::TextureFormat,
+        blend: wgpu::BlendState,
+    },
+    /// No fragment shader, e.g. for the cascades of a shadow map
+    DepthOnly,
+    /// `fs_picking` writes into the targets of a `PickingTarget`
+    Picking,
+}
+
+impl FragmentOutput {
+    pub fn surface(format: wgpu::TextureFormat) -> Self {
+        Self::Surface {
+            format,
+            blend: wgpu::BlendState::REPLACE,
+        }
+    }
+
+    pub fn entry_point(&self) -> Option<&'static str> {
+        match self {
+            Self::Surface { .. } => Some("fs_main"),
+            Self::DepthOnly => None,
+            Self::Picking => Some("fs_picking"),
+        }
+    }
+
+    pub fn targets(&self) -> Vec<Option<wgpu::ColorTargetState>> {
+        let target = |format, blend| {
+            Some(wgpu::ColorTargetState {
+                format,
+                blend,
+                write_mask: wgpu::ColorWrites::ALL,
+            })
+        };
+
+        match self {
+            Self::Surface { format, blend } => vec![target(*format, Some(*blend))],
+            Self::DepthOnly => Vec::new(),
+            // Integer and 32 bit float targets can not be blended
+            Self::Picking => vec![
+                target(PickingTarget::ID_FORMAT, None),
+                target(PickingTarget::DEPTH_VALUE_FORMAT, None),
+            ],
+        }
+    }
+
+    pub fn constants(&self, shader_code: &str) -> Vec<(&'static str, f64)> {
+        match self {
+            Self::Surface { format, .. } => surface_format::shader_constants(shader_code, *format),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Shadow casters are drawn from both sides, with a slope scaled bias against shadow acne
+    pub fn is_depth_only(&self) -> bool {
+        matches!(self, Self::DepthOnly)
+    }
+}
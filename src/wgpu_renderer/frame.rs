@@ -0,0 +1,225 @@
+//! Owns the command encoder and the targets of a single frame
+//!
+//! A frame is started with `WgpuRendererInterface::begin_frame`, followed by one or
+//! more render passes that are recorded in order, e.g. the 3D scene and a GUI overlay.
+//! `present` submits all passes and presents the surface texture.
+//!
+
+use super::super::post_processing::PostProcessingPasses;
+use super::surface_format;
+
+/// What happens with the depth target when a pass begins
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum FrameDepth {
+    /// The pass has no depth attachment, for pipelines without a depth stencil state
+    Disabled,
+    /// Keeps the depth values of the previous pass
+    Load,
+    /// Clears the depth target to the given value, usually 1.0
+    Clear(f32),
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct FramePass {
+    pub label: Option<&'static str>,
+    /// Clears the color target, the result of the previous pass is kept if None
+    pub clear_color: Option<wgpu::Color>,
+    pub depth: FrameDepth,
+}
+
+impl FramePass {
+    /// Clears color and depth, typically the first pass of a frame
+    pub fn scene(clear_color: wgpu::Color) -> Self {
+        Self {
+            label: Some("Scene Render Pass"),
+            clear_color: Some(clear_color),
+            depth: FrameDepth::Clear(1.0),
+        }
+    }
+
+    /// Draws on top of the previous passes
+    ///
+    /// The depth of the scene is cleared, so the bundled GUI pipelines are not hidden by it.
+    pub fn overlay() -> Self {
+        Self {
+            label: Some("Overlay Render Pass"),
+            clear_color: None,
+            depth: FrameDepth::Clear(1.0),
+        }
+    }
+
+    pub fn with_label(mut self, label: &'static str) -> Self {
+        self.label = Some(label);
+        self
+    }
+
+    pub fn with_clear_color(mut self, clear_color: Option<wgpu::Color>) -> Self {
+        self.clear_color = clear_color;
+        self
+    }
+
+    pub fn with_depth(mut self, depth: FrameDepth) -> Self {
+        self.depth = depth;
+        self
+    }
+}
+
+pub struct Frame {
+    queue: wgpu::Queue,
+    device: wgpu::Device,
+    encoder: wgpu::CommandEncoder,
+    surface_texture: Option<wgpu::SurfaceTexture>,
+    texture: wgpu::Texture,
+    view: wgpu::TextureView,
+    msaa_view: Option<wgpu::TextureView>,
+    depth_view: wgpu::TextureView,
+    /// Set until the effects are applied, the passes render into their input before
+    post_processing: Option<PostProcessingPasses>,
+}
+
+impl Frame {
+    /// The surface texture is presented at the end of the frame, offscreen targets pass None
+    pub fn new(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        surface_texture: Option<wgpu::SurfaceTexture>,
+        texture: wgpu::Texture,
+        msaa_view: Option<wgpu::TextureView>,
+        depth_view: wgpu::TextureView,
+    ) -> Self {
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let encoder = Self::create_encoder(device);
+
+        Self {
+            queue: queue.clone(),
+            device: device.clone(),
+            encoder,
+            surface_texture,
+            texture,
+            view,
+            msaa_view,
+            depth_view,
+            post_processing: None,
+        }
+    }
+
+    /// Renders the scene into the input of the post-processing effects, None keeps the frame unchanged
+    pub fn with_post_processing(mut self, post_processing: Option<PostProcessingPasses>) -> Self {
+        self.post_processing = post_processing;
+        self
+    }
+
+    fn create_encoder(device: &wgpu::Device) -> wgpu::CommandEncoder {
+        device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Frame Encoder"),
+        })
+    }
+
+    /// The texture that is presented, e.g. to capture the frame after `submit`
+    pub fn texture(&self) -> &wgpu::Texture {
+        &self.texture
+    }
+
+    pub fn view(&self) -> &wgpu::TextureView {
+        &self.view
+    }
+
+    /// For commands outside of render passes, e.g. copies or compute passes
+    pub fn encoder(&mut self) -> &mut wgpu::CommandEncoder {
+        &mut self.encoder
+    }
+
+    /// Begins a render pass, that has to be dropped before the next one is started
+    ///
+    /// Multisampled targets are resolved into the frame texture at the end of every pass.
+    pub fn begin_pass(&mut self, pass: &FramePass) -> wgpu::RenderPass<'_> {
+        self.begin_pass_with_timestamps(pass, None)
+    }
+
+    /// Begins a render pass that is measured on the GPU, e.g. by the performance monitor
+    pub fn begin_pass_with_timestamps(
+        &mut self,
+        pass: &FramePass,
+        timestamp_writes: Option<wgpu::RenderPassTimestampWrites<'_>>,
+    ) -> wgpu::RenderPass<'_> {
+        let (target_view, msaa_view, format) = match &self.post_processing {
+            Some(post_processing) => (
+                post_processing.scene_view(),
+                post_processing
+                    .scene_msaa_view()
+                    .or(self.msaa_view.as_ref()),
+                post_processing.scene_format(),
+            ),
+            None => (&self.view, self.msaa_view.as_ref(), self.texture.format()),
+        };
+
+        let load = match pass.clear_color {
+            Some(clear_color) => {
+                wgpu::LoadOp::Clear(surface_format::surface_clear_color(clear_color, format))
+            }
+            None => wgpu::LoadOp::Load,
+        };
+
+        let (view, resolve_target) = match msaa_view {
+            Some(msaa_view) => (msaa_view, Some(target_view)),
+            None => (target_view, None),
+        };
+
+        let depth_load = match pass.depth {
+            FrameDepth::Disabled => None,
+            FrameDepth::Load => Some(wgpu::LoadOp::Load),
+            FrameDepth::Clear(depth) => Some(wgpu::LoadOp::Clear(depth)),
+        };
+        let depth_stencil_attachment =
+            depth_load.map(|load| wgpu::RenderPassDepthStencilAttachment {
+                view: &self.depth_view,
+                depth_ops: Some(wgpu::Operations {
+                    load,
+                    store: wgpu::StoreOp::Store,
+                }),
+                stencil_ops: None,
+            });
+
+        self.encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: pass.label,
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view,
+                depth_slice: None,
+                resolve_target,
+                ops: wgpu::Operations {
+                    load,
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment,
+            timestamp_writes,
+            occlusion_query_set: None,
+            multiview_mask: None,
+        })
+    }
+
+    /// Applies the post-processing effects to the passes so far, later passes draw on top of the result
+    ///
+    /// Does nothing if there are no effects or they were already applied.
+    pub fn apply_post_processing(&mut self) {
+        if let Some(post_processing) = self.post_processing.take() {
+            post_processing.record(&mut self.encoder, &self.view, self.msaa_view.as_ref());
+        }
+    }
+
+    /// Submits the passes recorded so far, further passes are recorded into a new encoder
+    pub fn submit(&mut self) {
+        let encoder = std::mem::replace(&mut self.encoder, Self::create_encoder(&self.device));
+        self.queue.submit(std::iter::once(encoder.finish()));
+    }
+
+    /// Applies the post-processing effects, submits the remaining passes and presents the surface texture
+    pub fn present(mut self) {
+        self.apply_post_processing();
+        self.submit();
+
+        if let Some(surface_texture) = self.surface_texture {
+            surface_texture.present();
+        }
+    }
+}
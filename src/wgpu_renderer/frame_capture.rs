@@ -0,0 +1,119 @@
+//! Copies the content of a color texture back into host memory
+//!
+
+use anyhow::*;
+
+/// Memory layout of the supported 4 byte formats
+enum PixelLayout {
+    Rgba8,
+    Bgra8,
+    Rgb10a2,
+}
+
+/// Reads back a 2D color texture as an rgba image
+///
+/// The texture needs to be created with `TextureUsages::COPY_SRC`.
+/// Blocks until the copy has finished on the GPU.
+pub fn capture_texture(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    texture: &wgpu::Texture,
+) -> Result<image::RgbaImage> {
+    let format = texture.format();
+    let layout = match format {
+        wgpu::TextureFormat::Rgba8Unorm | wgpu::TextureFormat::Rgba8UnormSrgb => PixelLayout::Rgba8,
+        wgpu::TextureFormat::Bgra8Unorm | wgpu::TextureFormat::Bgra8UnormSrgb => PixelLayout::Bgra8,
+        wgpu::TextureFormat::Rgb10a2Unorm => PixelLayout::Rgb10a2,
+        _ => bail!(
+            "capturing textures with format {:?} is not supported",
+            format
+        ),
+    };
+
+    if !texture.usage().contains(wgpu::TextureUsages::COPY_SRC) {
+        bail!("texture was not created with TextureUsages::COPY_SRC");
+    }
+
+    let width = texture.width();
+    let height = texture.height();
+
+    // Rows of a texture copy need to be aligned to 256 bytes
+    let unpadded_bytes_per_row = 4 * width;
+    let align = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+    let padded_bytes_per_row = unpadded_bytes_per_row.div_ceil(align) * align;
+
+    let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("Capture Buffer"),
+        size: (padded_bytes_per_row * height) as wgpu::BufferAddress,
+        usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+        mapped_at_creation: false,
+    });
+
+    let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+        label: Some("Capture Encoder"),
+    });
+    encoder.copy_texture_to_buffer(
+        wgpu::TexelCopyTextureInfo {
+            aspect: wgpu::TextureAspect::All,
+            texture,
+            mip_level: 0,
+            origin: wgpu::Origin3d::ZERO,
+        },
+        wgpu::TexelCopyBufferInfo {
+            buffer: &buffer,
+            layout: wgpu::TexelCopyBufferLayout {
+                offset: 0,
+                bytes_per_row: Some(padded_bytes_per_row),
+                rows_per_image: Some(height),
+            },
+        },
+        wgpu::Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        },
+    );
+    queue.submit(std::iter::once(encoder.finish()));
+
+    let slice = buffer.slice(..);
+    let (sender, receiver) = std::sync::mpsc::channel();
+    slice.map_async(wgpu::MapMode::Read, move |result| {
+        let _res = sender.send(result);
+    });
+    device.poll(wgpu::PollType::wait_indefinitely())?;
+
+    match receiver.try_recv() {
+        std::result::Result::Ok(result) => result?,
+        Err(_) => bail!("capture buffer could not be mapped"),
+    }
+
+    let mut pixels = Vec::with_capacity((unpadded_bytes_per_row * height) as usize);
+    {
+        let data = slice.get_mapped_range();
+        for row in data.chunks(padded_bytes_per_row as usize) {
+            pixels.extend_from_slice(&row[..unpadded_bytes_per_row as usize]);
+        }
+    }
+    buffer.unmap();
+
+    match layout {
+        PixelLayout::Rgba8 => {}
+        PixelLayout::Bgra8 => {
+            for pixel in pixels.chunks_exact_mut(4) {
+                pixel.swap(0, 2);
+            }
+        }
+        PixelLayout::Rgb10a2 => {
+            for pixel in pixels.chunks_exact_mut(4) {
+                let value = u32::from_le_bytes([pixel[0], pixel[1], pixel[2], pixel[3]]);
+                pixel[0] = ((value & 0x3ff) >> 2) as u8;
+                pixel[1] = (((value >> 10) & 0x3ff) >> 2) as u8;
+                pixel[2] = (((value >> 20) & 0x3ff) >> 2) as u8;
+                pixel[3] = ((value >> 30) * 85) as u8;
+            }
+        }
+    }
+
+    image::RgbaImage::from_raw(width, height, pixels)
+        .ok_or_else(|| anyhow!("captured data does not match the texture size"))
+}
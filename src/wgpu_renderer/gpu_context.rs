@@ -0,0 +1,81 @@
+//! The adapter, device and queue that can be shared by the renderers of several windows
+//!
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use super::RendererConfig;
+use super::RendererError;
+
+#[derive(Clone)]
+pub struct GpuContext {
+    instance: wgpu::Instance,
+    adapter: wgpu::Adapter,
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+    device_lost: Arc<AtomicBool>,
+}
+
+impl GpuContext {
+    /// The surface is used to find an adapter that can present to it, None for offscreen rendering
+    pub async fn new(
+        instance: wgpu::Instance,
+        compatible_surface: Option<&wgpu::Surface<'_>>,
+        config: &RendererConfig,
+    ) -> Result<Self, RendererError> {
+        let adapter = instance
+            .request_adapter(&config.adapter_options(compatible_surface))
+            .await?;
+        log::info!("Adapter created");
+
+        let (device, queue) = adapter
+            .request_device(&config.device_descriptor(&adapter))
+            .await?;
+        log::info!("Device created");
+
+        // Set from the callback and polled by the application, the renderers have to be recreated then
+        let device_lost = Arc::new(AtomicBool::new(false));
+        {
+            let device_lost = device_lost.clone();
+            device.set_device_lost_callback(move |reason, message| {
+                if reason == wgpu::DeviceLostReason::Destroyed {
+                    log::info!("Device destroyed: {}", message);
+                } else {
+                    log::error!("Device lost: {}", message);
+                    device_lost.store(true, Ordering::SeqCst);
+                }
+            });
+        }
+
+        Ok(Self {
+            instance,
+            adapter,
+            device,
+            queue,
+            device_lost,
+        })
+    }
+
+    pub fn instance(&self) -> &wgpu::Instance {
+        &self.instance
+    }
+
+    pub fn adapter(&self) -> &wgpu::Adapter {
+        &self.adapter
+    }
+
+    pub fn device(&self) -> &wgpu::Device {
+        &self.device
+    }
+
+    pub fn queue(&self) -> &wgpu::Queue {
+        &self.queue
+    }
+
+    /// True after the device was lost, e.g. because of a driver reset
+    ///
+    /// The context and all renderers using it can not be used anymore and have to be replaced.
+    pub fn is_device_lost(&self) -> bool {
+        self.device_lost.load(Ordering::SeqCst)
+    }
+}
@@ -0,0 +1,250 @@
+//! Instantiates a device that renders into a texture instead of a window
+//!
+//! Useful for tests, batch jobs and machines without a display
+//!
+
+use super::super::post_processing::PostProcessing;
+use super::depth_texture;
+use super::frame::Frame;
+use super::frame_capture;
+use super::msaa_texture;
+use super::GpuContext;
+use super::RendererConfig;
+use super::RendererError;
+use super::WgpuRendererInterface;
+
+pub struct OffscreenRenderer {
+    context: GpuContext,
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+    format: wgpu::TextureFormat,
+    width: u32,
+    height: u32,
+    texture: wgpu::Texture,
+    view: wgpu::TextureView,
+    sample_count: u32,
+    msaa_texture: Option<msaa_texture::MsaaTexture>,
+    depth_texture: depth_texture::DepthTexture,
+    post_processing: PostProcessing,
+}
+
+impl OffscreenRenderer {
+    pub const DEFAULT_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8UnormSrgb;
+
+    pub async fn new(
+        width: u32,
+        height: u32,
+        format: wgpu::TextureFormat,
+        config: &RendererConfig,
+    ) -> Result<Self, RendererError> {
+        let instance = wgpu::Instance::new(&config.instance_descriptor());
+        log::info!("Instance created");
+
+        let context = GpuContext::new(instance, None, config).await?;
+        Self::new_with_context(&context, width, height, format, config)
+    }
+
+    /// Renders with the device of an existing context, e.g. the one of a window
+    pub fn new_with_context(
+        context: &GpuContext,
+        width: u32,
+        height: u32,
+        format: wgpu::TextureFormat,
+        config: &RendererConfig,
+    ) -> Result<Self, RendererError> {
+        let adapter = context.adapter();
+        let device = context.device().clone();
+        let queue = context.queue().clone();
+        log::info!("Adapter: {:?}", adapter.get_info());
+
+        let (texture, view) = Self::create_target(&device, format, width, height);
+        let sample_count = config.supported_sample_count(adapter, format);
+        let msaa_texture = msaa_texture::MsaaTexture::create_optional(
+            &device,
+            format,
+            width,
+            height,
+            sample_count,
+        );
+        let depth_texture = depth_texture::DepthTexture::new_multisampled(
+            &device,
+            width,
+            height,
+            sample_count,
+            "depth_texture",
+        );
+        log::info!("Offscreen target created");
+
+        let post_processing = PostProcessing::new(
+            &device,
+            &queue,
+            format,
+            config.is_hdr(),
+            sample_count,
+            width,
+            height,
+        );
+
+        Ok(Self {
+            context: context.clone(),
+            device,
+            queue,
+            format,
+            width,
+            height,
+            texture,
+            view,
+            sample_count,
+            msaa_texture,
+            depth_texture,
+            post_processing,
+        })
+    }
+
+    fn create_target(
+        device: &wgpu::Device,
+        format: wgpu::TextureFormat,
+        width: u32,
+        height: u32,
+    ) -> (wgpu::Texture, wgpu::TextureView) {
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("offscreen_texture"),
+            size: wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT
+                | wgpu::TextureUsages::TEXTURE_BINDING
+                | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+        (texture, view)
+    }
+
+    pub fn resize(&mut self, width: u32, height: u32) {
+        if width > 0 && height > 0 {
+            self.width = width;
+            self.height = height;
+            (self.texture, self.view) =
+                Self::create_target(&self.device, self.format, width, height);
+            self.msaa_texture = msaa_texture::MsaaTexture::create_optional(
+                &self.device,
+                self.format,
+                width,
+                height,
+                self.sample_count,
+            );
+            self.depth_texture = depth_texture::DepthTexture::new_multisampled(
+                &self.device,
+                width,
+                height,
+                self.sample_count,
+                "depth_texture",
+            );
+            self.post_processing.resize(width, height);
+        }
+    }
+
+    /// The device, that can be shared with other renderers
+    pub fn context(&self) -> &GpuContext {
+        &self.context
+    }
+
+    /// True after the device was lost, e.g. because of a driver reset
+    ///
+    /// The renderer can not be used anymore and has to be replaced by a new one.
+    pub fn is_device_lost(&self) -> bool {
+        self.context.is_device_lost()
+    }
+
+    /// The texture all passes are rendered or resolved into
+    pub fn texture(&self) -> &wgpu::Texture {
+        &self.texture
+    }
+
+    pub fn texture_view(&self) -> &wgpu::TextureView {
+        &self.view
+    }
+
+    /// Reads back what has been rendered into the target texture so far
+    pub fn capture(&self) -> anyhow::Result<image::RgbaImage> {
+        frame_capture::capture_texture(&self.device, &self.queue, &self.texture)
+    }
+}
+
+impl WgpuRendererInterface for OffscreenRenderer {
+    fn device(&mut self) -> &mut wgpu::Device {
+        &mut self.device
+    }
+
+    fn queue(&mut self) -> &mut wgpu::Queue {
+        &mut self.queue
+    }
+
+    fn surface_width(&self) -> u32 {
+        self.width
+    }
+
+    fn surface_height(&self) -> u32 {
+        self.height
+    }
+
+    fn surface_format(&self) -> wgpu::TextureFormat {
+        self.format
+    }
+
+    fn get_depth_texture_view(&self) -> &wgpu::TextureView {
+        &self.depth_texture.view
+    }
+
+    fn sample_count(&self) -> u32 {
+        self.sample_count
+    }
+
+    fn get_msaa_texture_view(&self) -> Option<&wgpu::TextureView> {
+        self.msaa_texture
+            .as_ref()
+            .map(|msaa_texture| &msaa_texture.view)
+    }
+
+    /// There is no surface to acquire a texture from, use `begin_frame` or `texture_view()` instead
+    fn get_current_texture(&self) -> Result<wgpu::SurfaceTexture, wgpu::SurfaceError> {
+        Err(wgpu::SurfaceError::Other)
+    }
+
+    /// Renders into the offscreen texture, `present` only submits the passes
+    fn begin_frame(&mut self) -> Result<Frame, wgpu::SurfaceError> {
+        let post_processing = self.post_processing.passes(&self.device);
+
+        Ok(Frame::new(
+            &self.device,
+            &self.queue,
+            None,
+            self.texture.clone(),
+            self.get_msaa_texture_view().cloned(),
+            self.depth_texture.view.clone(),
+        )
+        .with_post_processing(post_processing))
+    }
+
+    fn enable_vsync(&mut self, _enabled: bool) {}
+
+    fn request_window_size(&mut self, width: u32, height: u32) {
+        self.resize(width, height);
+    }
+
+    fn scene_format(&self) -> wgpu::TextureFormat {
+        self.post_processing.scene_format()
+    }
+
+    fn post_processing(&mut self) -> Option<&mut PostProcessing> {
+        Some(&mut self.post_processing)
+    }
+}
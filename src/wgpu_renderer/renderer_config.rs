@@ -0,0 +1,328 @@
+//! Options used to create the adapter, the device and the surface
+//!
+
+use super::depth_texture::DepthTexture;
+use super::surface_format;
+
+#[derive(Clone, Debug)]
+pub struct RendererConfig {
+    backends: wgpu::Backends,
+    power_preference: wgpu::PowerPreference,
+    force_fallback_adapter: bool,
+    limits: wgpu::Limits,
+    required_features: wgpu::Features,
+    optional_features: wgpu::Features,
+    desired_maximum_frame_latency: u32,
+    alpha_mode: Option<wgpu::CompositeAlphaMode>,
+    present_mode: wgpu::PresentMode,
+    sample_count: u32,
+    hdr: bool,
+}
+
+impl Default for RendererConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RendererConfig {
+    pub fn new() -> Self {
+        Self {
+            // Backends::all => Vulkan + Metal + DX12 + Browser WebGPU
+            backends: wgpu::Backends::all(),
+            power_preference: wgpu::PowerPreference::HighPerformance,
+            force_fallback_adapter: false,
+            limits: Self::default_limits(),
+            required_features: wgpu::Features::empty(),
+            optional_features: wgpu::Features::empty(),
+            desired_maximum_frame_latency: 2,
+            alpha_mode: None,
+            present_mode: wgpu::PresentMode::Fifo, // vsync on
+            sample_count: 1,
+            hdr: false,
+        }
+    }
+
+    fn default_limits() -> wgpu::Limits {
+        // WebGL doesn't support all of wgpu's features, so if
+        // we're building for the web we'll have to disable some.
+        if cfg!(target_arch = "wasm32") {
+            let mut defaults = wgpu::Limits::downlevel_webgl2_defaults();
+            defaults.max_texture_dimension_2d = 4096;
+            defaults.max_buffer_size = 1024 << 20; // (1 GiB)
+            defaults
+        } else {
+            wgpu::Limits {
+                max_buffer_size: 1024 << 20, // (1 GiB)
+                ..Default::default()
+            }
+        }
+    }
+
+    pub fn with_backends(mut self, backends: wgpu::Backends) -> Self {
+        self.backends = backends;
+        self
+    }
+
+    pub fn with_power_preference(mut self, power_preference: wgpu::PowerPreference) -> Self {
+        self.power_preference = power_preference;
+        self
+    }
+
+    /// Use a software or fallback adapter, e.g. to render without a GPU
+    pub fn with_force_fallback_adapter(mut self, force_fallback_adapter: bool) -> Self {
+        self.force_fallback_adapter = force_fallback_adapter;
+        self
+    }
+
+    /// Preferred limits, each one is lowered to the adapter's if it does not reach it
+    pub fn with_limits(mut self, limits: wgpu::Limits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Features the device can not be created without
+    pub fn with_required_features(mut self, features: wgpu::Features) -> Self {
+        self.required_features = features;
+        self
+    }
+
+    /// Features that are only enabled if the adapter supports them, e.g. `POLYGON_MODE_LINE`
+    pub fn with_optional_features(mut self, features: wgpu::Features) -> Self {
+        self.optional_features = features;
+        self
+    }
+
+    pub fn with_desired_maximum_frame_latency(mut self, latency: u32) -> Self {
+        self.desired_maximum_frame_latency = latency;
+        self
+    }
+
+    /// Falls back to the first supported alpha mode if not set or not supported
+    pub fn with_alpha_mode(mut self, alpha_mode: wgpu::CompositeAlphaMode) -> Self {
+        self.alpha_mode = Some(alpha_mode);
+        self
+    }
+
+    /// Falls back to `PresentMode::Fifo` if not supported
+    pub fn with_present_mode(mut self, present_mode: wgpu::PresentMode) -> Self {
+        self.present_mode = present_mode;
+        self
+    }
+
+    /// Number of samples for multisample anti-aliasing, e.g. 2, 4 or 8
+    ///
+    /// Falls back to the highest supported count below
+    pub fn with_sample_count(mut self, sample_count: u32) -> Self {
+        self.sample_count = sample_count;
+        self
+    }
+
+    /// Renders the scene into an `Rgba16Float` target, that is tonemapped to the surface format
+    ///
+    /// The scene pipelines have to be created with `WgpuRendererInterface::scene_format` then.
+    pub fn with_hdr(mut self, hdr: bool) -> Self {
+        self.hdr = hdr;
+        self
+    }
+
+    pub(crate) fn is_hdr(&self) -> bool {
+        self.hdr
+    }
+
+    /// The format of the scene target, for a surface or offscreen target of the given format
+    pub(crate) fn scene_format(&self, format: wgpu::TextureFormat) -> wgpu::TextureFormat {
+        surface_format::scene_format(format, self.hdr)
+    }
+
+    pub(crate) fn instance_descriptor(&self) -> wgpu::InstanceDescriptor {
+        wgpu::InstanceDescriptor {
+            backends: self.backends,
+            flags: wgpu::InstanceFlags::default(),
+            backend_options: wgpu::BackendOptions::default(),
+            memory_budget_thresholds: wgpu::MemoryBudgetThresholds {
+                for_resource_creation: None,
+                for_device_loss: None,
+            },
+        }
+    }
+
+    pub(crate) fn adapter_options<'a, 'b>(
+        &self,
+        compatible_surface: Option<&'a wgpu::Surface<'b>>,
+    ) -> wgpu::RequestAdapterOptions<'a, 'b> {
+        wgpu::RequestAdapterOptions {
+            power_preference: self.power_preference,
+            compatible_surface,
+            force_fallback_adapter: self.force_fallback_adapter,
+        }
+    }
+
+    pub(crate) fn device_descriptor(&self, adapter: &wgpu::Adapter) -> wgpu::DeviceDescriptor<'_> {
+        // Software and fallback adapters often do not reach the default limits
+        let required_limits = clamp_limits(&self.limits, &adapter.limits());
+
+        let adapter_features = adapter.features();
+        let missing_features = self.optional_features - adapter_features;
+        if !missing_features.is_empty() {
+            log::warn!("Optional features not supported: {:?}", missing_features);
+        }
+
+        wgpu::DeviceDescriptor {
+            required_features: self.required_features | (self.optional_features & adapter_features),
+            required_limits,
+            label: None,
+            memory_hints: wgpu::MemoryHints::default(),
+            experimental_features: wgpu::ExperimentalFeatures::disabled(),
+            trace: wgpu::Trace::Off,
+        }
+    }
+
+    pub(crate) fn surface_configuration(
+        &self,
+        surface_caps: &wgpu::SurfaceCapabilities,
+        format: wgpu::TextureFormat,
+        width: u32,
+        height: u32,
+    ) -> wgpu::SurfaceConfiguration {
+        // Allow copying the surface texture to capture frames
+        let usage = if surface_caps.usages.contains(wgpu::TextureUsages::COPY_SRC) {
+            wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC
+        } else {
+            wgpu::TextureUsages::RENDER_ATTACHMENT
+        };
+
+        let present_mode = if surface_caps.present_modes.contains(&self.present_mode) {
+            self.present_mode
+        } else {
+            wgpu::PresentMode::Fifo // default, vsync on
+        };
+
+        let alpha_mode = match self.alpha_mode {
+            Some(alpha_mode) if surface_caps.alpha_modes.contains(&alpha_mode) => alpha_mode,
+            _ => surface_caps.alpha_modes[0],
+        };
+
+        wgpu::SurfaceConfiguration {
+            usage,
+            format,
+            width,
+            height,
+            present_mode,
+            desired_maximum_frame_latency: self.desired_maximum_frame_latency,
+            alpha_mode,
+            view_formats: vec![],
+        }
+    }
+
+    /// The highest sample count up to the requested one, that works with the color and the depth formats
+    pub(crate) fn supported_sample_count(
+        &self,
+        adapter: &wgpu::Adapter,
+        format: wgpu::TextureFormat,
+    ) -> u32 {
+        let color_flags = adapter.get_texture_format_features(format).flags;
+        let scene_flags = adapter
+            .get_texture_format_features(self.scene_format(format))
+            .flags;
+        let depth_flags = adapter
+            .get_texture_format_features(DepthTexture::DEPTH_FORMAT)
+            .flags;
+
+        let sample_count = [16, 8, 4, 2, 1]
+            .into_iter()
+            .filter(|count| *count <= self.sample_count)
+            .find(|count| {
+                color_flags.sample_count_supported(*count)
+                    && scene_flags.sample_count_supported(*count)
+                    && depth_flags.sample_count_supported(*count)
+            })
+            .unwrap_or(1);
+
+        if sample_count != self.sample_count {
+            log::warn!(
+                "Sample count {} not supported, using {}",
+                self.sample_count,
+                sample_count
+            );
+        }
+
+        sample_count
+    }
+}
+
+/// Lowers each limit that the adapter does not reach to the adapter's value
+pub(super) fn clamp_limits(requested: &wgpu::Limits, allowed: &wgpu::Limits) -> wgpu::Limits {
+    let mut limits = requested.clone();
+
+    macro_rules! clamp {
+        ($name:expr, $($field:ident),* $(,)?) => {
+            match $name {
+                $(stringify!($field) => limits.$field = allowed.$field,)*
+                name => log::error!("Limit {} can not be lowered", name),
+            }
+        };
+    }
+
+    requested.check_limits_with_fail_fn(allowed, false, |name, requested, allowed| {
+        log::warn!(
+            "Adapter does not reach the limit {}: {} lowered to {}",
+            name,
+            requested,
+            allowed
+        );
+        clamp!(
+            name,
+            max_texture_dimension_1d,
+            max_texture_dimension_2d,
+            max_texture_dimension_3d,
+            max_texture_array_layers,
+            max_bind_groups,
+            max_bindings_per_bind_group,
+            max_dynamic_uniform_buffers_per_pipeline_layout,
+            max_dynamic_storage_buffers_per_pipeline_layout,
+            max_sampled_textures_per_shader_stage,
+            max_samplers_per_shader_stage,
+            max_storage_buffers_per_shader_stage,
+            max_storage_textures_per_shader_stage,
+            max_uniform_buffers_per_shader_stage,
+            max_binding_array_elements_per_shader_stage,
+            max_uniform_buffer_binding_size,
+            max_storage_buffer_binding_size,
+            max_vertex_buffers,
+            max_buffer_size,
+            max_vertex_attributes,
+            max_vertex_buffer_array_stride,
+            min_uniform_buffer_offset_alignment,
+            min_storage_buffer_offset_alignment,
+            max_inter_stage_shader_components,
+            max_color_attachments,
+            max_color_attachment_bytes_per_sample,
+            max_compute_workgroup_storage_size,
+            max_compute_invocations_per_workgroup,
+            max_compute_workgroup_size_x,
+            max_compute_workgroup_size_y,
+            max_compute_workgroup_size_z,
+            max_compute_workgroups_per_dimension,
+            max_immediate_size,
+            max_non_sampler_bindings,
+            max_task_mesh_workgroup_total_count,
+            max_task_mesh_workgroups_per_dimension,
+            max_task_invocations_per_workgroup,
+            max_task_invocations_per_dimension,
+            max_mesh_invocations_per_workgroup,
+            max_mesh_invocations_per_dimension,
+            max_task_payload_size,
+            max_mesh_output_vertices,
+            max_mesh_output_primitives,
+            max_mesh_output_layers,
+            max_mesh_multiview_view_count,
+            max_blas_primitive_count,
+            max_blas_geometry_count,
+            max_tlas_instance_count,
+            max_multiview_view_count,
+        );
+    });
+
+    limits
+}
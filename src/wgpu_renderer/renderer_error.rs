@@ -0,0 +1,59 @@
+//! Errors that can occur while creating a renderer
+//!
+
+#[derive(Debug)]
+pub enum RendererError {
+    SurfaceCreationFailed(wgpu::CreateSurfaceError),
+    NoAdapter(wgpu::RequestAdapterError),
+    DeviceRequestFailed(wgpu::RequestDeviceError),
+    UnsupportedSurfaceFormat(Vec<wgpu::TextureFormat>),
+    /// The adapter of a shared context can not present to the surface of another window
+    IncompatibleSurface,
+}
+
+impl std::fmt::Display for RendererError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RendererError::SurfaceCreationFailed(err) => {
+                write!(f, "Surface creation failed: {err}")
+            }
+            RendererError::NoAdapter(err) => write!(f, "No suitable adapter found: {err}"),
+            RendererError::DeviceRequestFailed(err) => write!(f, "Device request failed: {err}"),
+            RendererError::UnsupportedSurfaceFormat(formats) => {
+                write!(f, "No suitable texture format found in {formats:?}")
+            }
+            RendererError::IncompatibleSurface => {
+                write!(f, "Surface is not supported by the adapter")
+            }
+        }
+    }
+}
+
+impl std::error::Error for RendererError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            RendererError::SurfaceCreationFailed(err) => Some(err),
+            RendererError::NoAdapter(err) => Some(err),
+            RendererError::DeviceRequestFailed(err) => Some(err),
+            RendererError::UnsupportedSurfaceFormat(_) | RendererError::IncompatibleSurface => None,
+        }
+    }
+}
+
+impl From<wgpu::CreateSurfaceError> for RendererError {
+    fn from(err: wgpu::CreateSurfaceError) -> Self {
+        Self::SurfaceCreationFailed(err)
+    }
+}
+
+impl From<wgpu::RequestAdapterError> for RendererError {
+    fn from(err: wgpu::RequestAdapterError) -> Self {
+        Self::NoAdapter(err)
+    }
+}
+
+impl From<wgpu::RequestDeviceError> for RendererError {
+    fn from(err: wgpu::RequestDeviceError) -> Self {
+        Self::DeviceRequestFailed(err)
+    }
+}
@@ -0,0 +1,202 @@
+//! Rebuilds pipelines when their shader file changes, meant for development
+//!
+//! The pipeline is created by a closure from the current shader code, so bundled and user
+//! supplied shaders work the same way, e.g. `src/vertex_color_shader/shader.wgsl` with
+//! `vertex_color_shader::Pipeline::new_parameterized`. If the new code does not compile,
+//! the error is logged and the previous pipeline is kept.
+//!
+//! The closure gets the file contents unchanged and preprocesses them itself, like the
+//! bundled pipelines do. The preprocessor of the `HotReloadPipeline` only finds the included
+//! `name.wgsl` files next to the shader, which are watched as well. Shader variants need
+//! their defines to find the modules included in `#ifdef` blocks, see `new_with_preprocessor`.
+//!
+
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use super::super::wgsl_preprocessor::Preprocessor;
+
+/// Files are checked at most this often
+const CHECK_INTERVAL: instant::Duration = instant::Duration::from_millis(250);
+
+type CreatePipeline<P> = Box<dyn Fn(&wgpu::Device, &str) -> P>;
+
+/// A watched file with its modification time when it was last read
+struct WatchedFile {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+}
+
+impl WatchedFile {
+    fn new(path: PathBuf) -> Self {
+        let modified = modified_time(&path);
+        Self { path, modified }
+    }
+}
+
+pub struct HotReloadPipeline<P> {
+    pipeline: P,
+    path: PathBuf,
+    /// The shader file and the modules it included
+    files: Vec<WatchedFile>,
+    /// Only used to find the included module files
+    preprocessor: Preprocessor,
+    last_check: instant::Instant,
+    create: CreatePipeline<P>,
+}
+
+impl<P> HotReloadPipeline<P> {
+    /// Creates the pipeline from the file, or from `fallback_code` if it can not be read or compiled
+    pub fn new(
+        device: &wgpu::Device,
+        path: impl Into<PathBuf>,
+        fallback_code: &str,
+        create: impl Fn(&wgpu::Device, &str) -> P + 'static,
+    ) -> Self {
+        let path = path.into();
+        let module_dir = path.parent().unwrap_or(Path::new("")).to_path_buf();
+        let preprocessor = Preprocessor::new().with_module_dir(module_dir);
+
+        Self::new_with_preprocessor(device, path, preprocessor, fallback_code, create)
+    }
+
+    /// Like `new`, the included files are found by `preprocessor`, e.g. with the defines of a variant
+    ///
+    /// Only modules read from files with `Preprocessor::with_module_dir` are watched.
+    pub fn new_with_preprocessor(
+        device: &wgpu::Device,
+        path: impl Into<PathBuf>,
+        preprocessor: Preprocessor,
+        fallback_code: &str,
+        create: impl Fn(&wgpu::Device, &str) -> P + 'static,
+    ) -> Self {
+        let path = path.into();
+        let mut files = vec![WatchedFile::new(path.clone())];
+
+        let pipeline = read_shader(&path, &preprocessor).and_then(|(shader_code, module_files)| {
+            files.extend(module_files.into_iter().map(WatchedFile::new));
+            create_checked(device, &path, &shader_code, &create)
+        });
+        let pipeline = pipeline.unwrap_or_else(|| create(device, fallback_code));
+
+        Self {
+            pipeline,
+            path,
+            files,
+            preprocessor,
+            last_check: instant::Instant::now(),
+            create: Box::new(create),
+        }
+    }
+
+    pub fn get(&self) -> &P {
+        &self.pipeline
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The shader file and the included module files that are watched
+    pub fn watched_files(&self) -> impl Iterator<Item = &Path> {
+        self.files.iter().map(|file| file.path.as_path())
+    }
+
+    /// Rebuilds the pipeline if a watched file was modified, returns true if it was replaced
+    pub fn reload_if_changed(&mut self, device: &wgpu::Device) -> bool {
+        let now = instant::Instant::now();
+        if now - self.last_check < CHECK_INTERVAL {
+            return false;
+        }
+        self.last_check = now;
+
+        self.reload_now_if_changed(device)
+    }
+
+    /// Like `reload_if_changed`, but without limiting how often the files are checked
+    pub fn reload_now_if_changed(&mut self, device: &wgpu::Device) -> bool {
+        let mut changed = false;
+        for file in &mut self.files {
+            let modified = modified_time(&file.path);
+            if modified.is_some() && modified != file.modified {
+                file.modified = modified;
+                changed = true;
+            }
+        }
+        if !changed {
+            return false;
+        }
+
+        let Some((shader_code, module_files)) = read_shader(&self.path, &self.preprocessor) else {
+            return false;
+        };
+
+        // the includes may have changed, the main file is always the first one
+        self.files.truncate(1);
+        self.files
+            .extend(module_files.into_iter().map(WatchedFile::new));
+
+        match create_checked(device, &self.path, &shader_code, &self.create) {
+            Some(pipeline) => {
+                self.pipeline = pipeline;
+                log::info!("Shader reloaded: {}", self.path.display());
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// Reads the shader, returns its code and the included module files
+fn read_shader(path: &Path, preprocessor: &Preprocessor) -> Option<(String, Vec<PathBuf>)> {
+    let shader_code = match std::fs::read_to_string(path) {
+        Ok(shader_code) => shader_code,
+        Err(err) => {
+            log::warn!("Failed to read shader {}: {}", path.display(), err);
+            return None;
+        }
+    };
+
+    match preprocessor.process_with_files(&shader_code) {
+        Ok((_processed, module_files)) => Some((shader_code, module_files)),
+        Err(err) => {
+            log::error!("Shader {} failed to preprocess: {}", path.display(), err);
+            None
+        }
+    }
+}
+
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+/// Creates the pipeline inside an error scope, so invalid shaders do not abort the application
+fn create_checked<P>(
+    device: &wgpu::Device,
+    path: &Path,
+    shader_code: &str,
+    create: &dyn Fn(&wgpu::Device, &str) -> P,
+) -> Option<P> {
+    let error_scope = device.push_error_scope(wgpu::ErrorFilter::Validation);
+    let pipeline = create(device, shader_code);
+    let error = error_scope.pop();
+
+    #[cfg(not(target_arch = "wasm32"))]
+    let error = pollster::block_on(error);
+    // There is no file system on the web, the shaders are never reloaded there
+    #[cfg(target_arch = "wasm32")]
+    let error: Option<wgpu::Error> = {
+        drop(error);
+        None
+    };
+
+    match error {
+        Some(err) => {
+            log::error!("Shader {} failed to compile: {}", path.display(), err);
+            None
+        }
+        None => Some(pipeline),
+    }
+}
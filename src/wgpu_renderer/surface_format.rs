@@ -0,0 +1,93 @@
+//! Selects the texture format of the surface
+//!
+//! The shaders write linear colors. If the selected format is not sRGB,
+//! the pipelines encode the colors themselves, so they look the same on every format.
+//! Float formats like the HDR scene target store linear colors, like sRGB formats.
+//!
+
+/// Surface formats in the order they are preferred
+pub const PREFERRED_SURFACE_FORMATS: &[wgpu::TextureFormat] = &[
+    wgpu::TextureFormat::Rgba8UnormSrgb,
+    wgpu::TextureFormat::Bgra8UnormSrgb,
+    wgpu::TextureFormat::Rgb10a2Unorm,
+    wgpu::TextureFormat::Rgba8Unorm,
+    wgpu::TextureFormat::Bgra8Unorm,
+];
+
+/// Name of the pipeline-overridable constant in the shaders
+pub const SURFACE_NEEDS_NO_GAMMA_ENCODING: &str = "surface_needs_no_gamma_encoding";
+
+pub fn select_surface_format(available: &[wgpu::TextureFormat]) -> Option<wgpu::TextureFormat> {
+    PREFERRED_SURFACE_FORMATS
+        .iter()
+        .copied()
+        .find(|format| available.contains(format))
+}
+
+/// The format of the HDR scene target
+pub const HDR_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba16Float;
+
+/// The format the scene is rendered in, `HDR_FORMAT` or the one of the surface
+pub fn scene_format(format: wgpu::TextureFormat, hdr: bool) -> wgpu::TextureFormat {
+    if hdr {
+        HDR_FORMAT
+    } else {
+        format
+    }
+}
+
+/// True if the format is sRGB or float, so colors are written without gamma correction
+pub fn needs_no_gamma_encoding(format: wgpu::TextureFormat) -> bool {
+    format.is_srgb()
+        || matches!(
+            format,
+            wgpu::TextureFormat::Rgba16Float | wgpu::TextureFormat::Rgba32Float
+        )
+}
+
+/// The pipeline constants telling the fragment shader whether it needs to apply the gamma correction
+pub fn surface_constants(surface_format: wgpu::TextureFormat) -> [(&'static str, f64); 1] {
+    let value = if needs_no_gamma_encoding(surface_format) {
+        1.0
+    } else {
+        0.0
+    };
+
+    [(SURFACE_NEEDS_NO_GAMMA_ENCODING, value)]
+}
+
+/// Like `surface_constants`, but empty if the shader does not declare the constant
+///
+/// User supplied shaders only get the gamma correction if they declare it.
+pub fn shader_constants(
+    shader_code: &str,
+    surface_format: wgpu::TextureFormat,
+) -> Vec<(&'static str, f64)> {
+    if shader_code.contains(SURFACE_NEEDS_NO_GAMMA_ENCODING) {
+        surface_constants(surface_format).to_vec()
+    } else {
+        Vec::new()
+    }
+}
+
+/// Clear colors are not processed by the shaders, so they need to be encoded for non sRGB formats
+pub fn surface_clear_color(color: wgpu::Color, surface_format: wgpu::TextureFormat) -> wgpu::Color {
+    if needs_no_gamma_encoding(surface_format) {
+        return color;
+    }
+
+    wgpu::Color {
+        r: linear_to_srgb(color.r),
+        g: linear_to_srgb(color.g),
+        b: linear_to_srgb(color.b),
+        a: color.a,
+    }
+}
+
+fn linear_to_srgb(value: f64) -> f64 {
+    if value <= 0.0031308 {
+        value * 12.92
+    } else {
+        1.055 * value.powf(1.0 / 2.4) - 0.055
+    }
+}
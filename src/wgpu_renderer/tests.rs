@@ -0,0 +1,126 @@
+//! Unit tests
+
+use std::time::{Duration, SystemTime};
+
+use crate::golden_image::test_support::{check_golden, render_sphere, HEIGHT, WIDTH};
+use crate::golden_image::{test_renderer, test_renderer_with_config};
+use crate::wgsl_preprocessor::Preprocessor;
+
+use super::{HotReloadPipeline, RendererConfig, WgpuRendererInterface};
+
+fn write_shader(path: &std::path::Path, shader_code: &str, modified: SystemTime) {
+    std::fs::write(path, shader_code).unwrap();
+    let file = std::fs::File::options().write(true).open(path).unwrap();
+    file.set_modified(modified).unwrap();
+}
+
+#[test]
+fn hot_reload_keeps_pipeline_on_error() {
+    let Some(mut renderer) = test_renderer(16, 16) else {
+        return;
+    };
+    let device = renderer.device().clone();
+
+    let dir = std::env::temp_dir().join(format!(
+        "wgpu_renderer_hot_reload_keeps_pipeline_on_error_{}",
+        std::process::id()
+    ));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("shader.wgsl");
+    let shader_code = "@compute @workgroup_size(1)\nfn cs_main() {}\n";
+    let start = SystemTime::now() - Duration::from_secs(60);
+    write_shader(&path, shader_code, start);
+
+    // the closure gets the unprocessed file and resolves the includes itself
+    let preprocessor = Preprocessor::new().with_module_dir(&dir);
+    let mut pipeline = HotReloadPipeline::new(&device, &path, shader_code, move |device, code| {
+        assert!(!code.contains("// module"));
+        let code = preprocessor
+            .process(code)
+            .unwrap_or_else(|_| code.to_string());
+        device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: None,
+            source: wgpu::ShaderSource::Wgsl(code.into()),
+        })
+    });
+    assert!(!pipeline.reload_now_if_changed(&device));
+
+    write_shader(&path, "this is not wgsl", start + Duration::from_secs(1));
+    assert!(!pipeline.reload_now_if_changed(&device));
+
+    let changed_code = format!("{shader_code}\n// changed\n");
+    write_shader(&path, &changed_code, start + Duration::from_secs(2));
+    assert!(pipeline.reload_now_if_changed(&device));
+
+    // included modules next to the shader are watched as well
+    let module_path = dir.join("hot_reload_module.wgsl");
+    write_shader(&module_path, "// module\n", start);
+    let including_code = format!("{shader_code}\n#include \"hot_reload_module\"\n");
+    write_shader(&path, &including_code, start + Duration::from_secs(3));
+    assert!(pipeline.reload_now_if_changed(&device));
+    assert!(pipeline.watched_files().any(|file| file == module_path));
+
+    write_shader(
+        &module_path,
+        "this is not wgsl",
+        start + Duration::from_secs(1),
+    );
+    assert!(!pipeline.reload_now_if_changed(&device));
+
+    write_shader(
+        &module_path,
+        "// changed module\n",
+        start + Duration::from_secs(2),
+    );
+    assert!(pipeline.reload_now_if_changed(&device));
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn limits_are_clamped_per_field() {
+    let requested = wgpu::Limits {
+        max_buffer_size: 1024 << 20,
+        max_texture_dimension_2d: 16384,
+        ..Default::default()
+    };
+    let allowed = wgpu::Limits {
+        max_buffer_size: 2048 << 20,
+        max_texture_dimension_2d: 4096,
+        min_uniform_buffer_offset_alignment: 512,
+        ..Default::default()
+    };
+
+    let limits = super::renderer_config::clamp_limits(&requested, &allowed);
+
+    assert_eq!(limits.max_buffer_size, 1024 << 20);
+    assert_eq!(limits.max_texture_dimension_2d, 4096);
+    assert_eq!(limits.min_uniform_buffer_offset_alignment, 512);
+    assert_eq!(
+        limits.max_storage_buffers_per_shader_stage,
+        requested.max_storage_buffers_per_shader_stage
+    );
+    assert!(limits.check_limits(&allowed));
+}
+
+/// Non sRGB targets are gamma corrected in the shader and have to look the same
+#[test]
+fn surface_formats() {
+    for format in [
+        wgpu::TextureFormat::Bgra8UnormSrgb,
+        wgpu::TextureFormat::Rgba8Unorm,
+        wgpu::TextureFormat::Bgra8Unorm,
+        wgpu::TextureFormat::Rgb10a2Unorm,
+    ] {
+        let Some(mut renderer) =
+            test_renderer_with_config(WIDTH, HEIGHT, format, &RendererConfig::default())
+        else {
+            return;
+        };
+        let image = render_sphere(&mut renderer);
+
+        if let Err(err) = check_golden("vertex_color_shader_sphere", &image) {
+            panic!("{format:?}: {err}");
+        }
+    }
+}
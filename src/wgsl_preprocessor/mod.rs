@@ -0,0 +1,63 @@
+//! A small preprocessor for WGSL, to share code between shaders
+//!
+//! Supports `#include "module"`, `#define NAME [value]`, `#undef NAME`,
+//! `#ifdef NAME`, `#ifndef NAME`, `#else` and `#endif`. Directives have to stand on
+//! their own line. Every module is included at most once, so modules can include
+//! their dependencies without duplicate definitions. With `Preprocessor::with_module_dir`
+//! modules are also read from files, e.g. for `HotReloadPipeline`.
+//!
+//! The built-in modules are
+//! - `camera`: `CameraUniform` bound to `@group(0) @binding(0)` as `camera`
+//! - `instance`: `InstanceInput` at locations 5 to 13, `instance_model_matrix` and
+//!   `instance_normal_matrix`
+//! - `surface_color`: `to_surface_color`, which encodes to sRGB if the surface is not sRGB
+//! - `fullscreen`: `vs_fullscreen`, a vertex shader for full-screen passes
+//! - `shadow`: `shadow_light_factor` of a `DirectionalShadowMap`, bound to the group `SHADOW_GROUP`
+//! - `lighting`: Blinn-Phong `lighting` of a `LightUniform`, bound to the group `LIGHT_GROUP`.
+//!   With `SHADOWS` defined, the first directional light uses `shadow_visibility`
+//! - `picking`: `picking_output` with the `PickingId` bound to the group `PICKING_GROUP`
+//! - `environment`: `environment_color` and `environment_reflection` of a `Cubemap`, bound to
+//!   the group `ENVIRONMENT_GROUP`
+//!
+
+mod preprocessor;
+
+#[cfg(test)]
+mod tests;
+
+pub use preprocessor::{Preprocessor, PreprocessorError};
+
+pub(crate) const BUILTIN_MODULES: &[(&str, &str)] = &[
+    ("camera", include_str!("modules/camera.wgsl")),
+    ("instance", include_str!("modules/instance.wgsl")),
+    ("surface_color", include_str!("modules/surface_color.wgsl")),
+    ("fullscreen", include_str!("modules/fullscreen.wgsl")),
+    ("shadow", include_str!("modules/shadow.wgsl")),
+    ("lighting", include_str!("modules/lighting.wgsl")),
+    ("picking", include_str!("modules/picking.wgsl")),
+    ("environment", include_str!("modules/environment.wgsl")),
+];
+
+/// Processes shader code with the built-in modules, as done by the bundled pipelines
+///
+/// On errors the code is returned unchanged, wgpu then reports where it fails to compile.
+pub fn preprocess(shader_code: &str) -> String {
+    preprocess_with_defines(shader_code, &[])
+}
+
+/// Like `preprocess`, with names that are defined before, e.g. to select a variant
+pub fn preprocess_with_defines(shader_code: &str, defines: &[&str]) -> String {
+    let preprocessor = defines
+        .iter()
+        .fold(Preprocessor::new(), |preprocessor, define| {
+            preprocessor.with_define(define, "")
+        });
+
+    match preprocessor.process(shader_code) {
+        Ok(processed) => processed,
+        Err(err) => {
+            log::error!("Shader preprocessing failed: {}", err);
+            shader_code.to_string()
+        }
+    }
+}
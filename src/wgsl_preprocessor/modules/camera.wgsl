@@ -0,0 +1,8 @@
+// Matches vertex_color_shader::CameraUniform
+struct CameraUniform {
+    view_pos: vec4<f32>,
+    view_proj: mat4x4<f32>,
+};
+
+@group(0) @binding(0)
+var<uniform> camera: CameraUniform;
@@ -0,0 +1,24 @@
+// The cubemap of the environment, e.g. of a skybox
+
+@group(ENVIRONMENT_GROUP) @binding(0)
+var environment_texture: texture_cube<f32>;
+@group(ENVIRONMENT_GROUP) @binding(1)
+var environment_sampler: sampler;
+
+// The color of the environment in a direction of the world
+fn environment_color(direction: vec3<f32>) -> vec3<f32> {
+    return textureSample(environment_texture, environment_sampler, direction).rgb;
+}
+
+// Mixes the reflection of the environment into the color of a surface
+fn environment_reflection(
+    color: vec3<f32>,
+    normal: vec3<f32>,
+    world_position: vec3<f32>,
+    view_position: vec3<f32>,
+    reflectivity: f32,
+) -> vec3<f32> {
+    let incident = normalize(world_position - view_position);
+    let reflected = reflect(incident, normalize(normal));
+    return mix(color, environment_color(reflected), clamp(reflectivity, 0.0, 1.0));
+}
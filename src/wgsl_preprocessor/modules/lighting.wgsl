@@ -0,0 +1,88 @@
+// Blinn-Phong shading with ambient, directional and point lights
+//
+// With SHADOWS defined, the first directional light is blocked where the shadow module's
+// `shadow_visibility` is in shadow.
+
+// Matches vertex_color_shader::light_uniform::DirectionalLightRaw
+struct DirectionalLight {
+    // Points from the light towards the scene
+    direction: vec4<f32>,
+    // Color multiplied by the intensity
+    color: vec4<f32>,
+};
+
+// Matches vertex_color_shader::light_uniform::PointLightRaw
+struct PointLight {
+    // The range in w, the light fades out towards it
+    position: vec4<f32>,
+    color: vec4<f32>,
+};
+
+// Matches vertex_color_shader::LightUniform
+struct LightUniform {
+    ambient: vec4<f32>,
+    // Specular strength, shininess and the reflectivity of the environment
+    material: vec4<f32>,
+    // Number of directional and point lights
+    counts: vec4<u32>,
+    directional_lights: array<DirectionalLight, 4>,
+    point_lights: array<PointLight, 8>,
+};
+
+@group(LIGHT_GROUP) @binding(0)
+var<uniform> lights: LightUniform;
+
+fn blinn_phong(
+    normal: vec3<f32>,
+    to_light: vec3<f32>,
+    to_viewer: vec3<f32>,
+    color: vec3<f32>,
+    base_color: vec3<f32>,
+) -> vec3<f32> {
+    let diffuse = max(dot(normal, to_light), 0.0);
+    if (diffuse <= 0.0) {
+        return vec3<f32>(0.0);
+    }
+
+    let half_dir = normalize(to_light + to_viewer);
+    let specular = pow(max(dot(normal, half_dir), 0.0), lights.material.y) * lights.material.x;
+
+    return color * (base_color * diffuse + vec3<f32>(specular));
+}
+
+// The color of a surface with the base color, lit by all lights
+fn lighting(
+    base_color: vec3<f32>,
+    normal: vec3<f32>,
+    world_position: vec3<f32>,
+    view_position: vec3<f32>,
+) -> vec3<f32> {
+    let n = normalize(normal);
+    let to_viewer = normalize(view_position - world_position);
+
+    var color = base_color * lights.ambient.rgb;
+
+    for (var i = 0u; i < min(lights.counts.x, 4u); i++) {
+        let light = lights.directional_lights[i];
+        let to_light = -normalize(light.direction.xyz);
+        var light_color = light.color.rgb;
+#ifdef SHADOWS
+        if (i == 0u) {
+            light_color *= shadow_visibility(world_position);
+        }
+#endif
+        color += blinn_phong(n, to_light, to_viewer, light_color, base_color);
+    }
+
+    for (var i = 0u; i < min(lights.counts.y, 8u); i++) {
+        let light = lights.point_lights[i];
+        let offset = light.position.xyz - world_position;
+        let distance = length(offset);
+        let falloff = clamp(1.0 - distance / light.position.w, 0.0, 1.0);
+        let attenuation = falloff * falloff;
+        color += blinn_phong(n, offset / max(distance, 0.0001), to_viewer, light.color.rgb, base_color)
+            * attenuation;
+    }
+
+    return color;
+}
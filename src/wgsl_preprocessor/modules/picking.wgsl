@@ -0,0 +1,23 @@
+// Writes the ids of the picking pass, see picking::PickingTarget
+
+// Matches picking::picking_id::PickingIdUniform
+struct PickingIdUniform {
+    mesh_id: u32,
+};
+
+@group(PICKING_GROUP) @binding(0)
+var<uniform> picking_id: PickingIdUniform;
+
+struct PickingOutput {
+    @location(0) ids: vec2<u32>,
+    // The bits of the depth, float targets are not renderable everywhere
+    @location(1) depth: u32,
+};
+
+// The depth is the z of the fragment position
+fn picking_output(instance_id: u32, depth: f32) -> PickingOutput {
+    var out: PickingOutput;
+    out.ids = vec2<u32>(picking_id.mesh_id, instance_id);
+    out.depth = bitcast<u32>(depth);
+    return out;
+}
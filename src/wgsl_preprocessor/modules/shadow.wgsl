@@ -0,0 +1,56 @@
+// Matches shadow_map::ShadowUniform, the group has to be set with #define SHADOW_GROUP
+struct ShadowUniform {
+    light_view_proj: array<mat4x4<f32>, 4>,
+    // Depth in the view of the camera up to which each cascade is used
+    split_distances: vec4<f32>,
+    camera_position: vec4<f32>,
+    camera_direction: vec4<f32>,
+    // x: number of cascades, y: size of a texel, z: depth bias, w: ambient light
+    params: vec4<f32>,
+};
+
+@group(SHADOW_GROUP) @binding(0)
+var<uniform> shadow: ShadowUniform;
+@group(SHADOW_GROUP) @binding(1)
+var t_shadow: texture_depth_2d_array;
+@group(SHADOW_GROUP) @binding(2)
+var s_shadow: sampler_comparison;
+
+// 1.0 if the position is lit, 0.0 if it is in shadow, filtered over 3x3 texels
+fn shadow_visibility(world_position: vec3<f32>) -> f32 {
+    let depth_in_view = dot(world_position - shadow.camera_position.xyz, shadow.camera_direction.xyz);
+    let count = i32(shadow.params.x);
+
+    var cascade = count;
+    for (var i = count - 1; i >= 0; i--) {
+        if (depth_in_view <= shadow.split_distances[i]) {
+            cascade = i;
+        }
+    }
+    if (cascade >= count) {
+        return 1.0;
+    }
+
+    let light_position = shadow.light_view_proj[cascade] * vec4<f32>(world_position, 1.0);
+    let ndc = light_position.xyz / light_position.w;
+    let uv = ndc.xy * vec2<f32>(0.5, -0.5) + vec2<f32>(0.5);
+    let depth = ndc.z - shadow.params.z;
+    if (depth > 1.0) {
+        return 1.0;
+    }
+
+    var visibility = 0.0;
+    for (var y = -1; y <= 1; y++) {
+        for (var x = -1; x <= 1; x++) {
+            let offset = vec2<f32>(f32(x), f32(y)) * shadow.params.y;
+            visibility += textureSampleCompareLevel(t_shadow, s_shadow, uv + offset, cascade, depth);
+        }
+    }
+    return visibility / 9.0;
+}
+
+// The ambient light in shadow, 1.0 if lit
+fn shadow_light_factor(world_position: vec3<f32>) -> f32 {
+    let ambient = shadow.params.w;
+    return ambient + (1.0 - ambient) * shadow_visibility(world_position);
+}
@@ -0,0 +1,29 @@
+// Set by the pipeline, if false the colors are encoded to sRGB in the shader
+override surface_needs_no_gamma_encoding: bool = true;
+
+fn linear_to_srgb(color: vec3<f32>) -> vec3<f32> {
+    let lower = color * 12.92;
+    let higher = 1.055 * pow(color, vec3<f32>(1.0 / 2.4)) - 0.055;
+    return select(higher, lower, color <= vec3<f32>(0.0031308));
+}
+
+fn srgb_to_linear(color: vec3<f32>) -> vec3<f32> {
+    let lower = color / 12.92;
+    let higher = pow((color + 0.055) / 1.055, vec3<f32>(2.4));
+    return select(higher, lower, color <= vec3<f32>(0.04045));
+}
+
+fn to_surface_color(color: vec4<f32>) -> vec4<f32> {
+    if (surface_needs_no_gamma_encoding) {
+        return color;
+    }
+    return vec4<f32>(linear_to_srgb(color.rgb), color.a);
+}
+
+// The linear color of a texel read from a target of the surface format
+fn from_surface_color(color: vec4<f32>) -> vec4<f32> {
+    if (surface_needs_no_gamma_encoding) {
+        return color;
+    }
+    return vec4<f32>(srgb_to_linear(color.rgb), color.a);
+}
@@ -0,0 +1,266 @@
+//! Resolves includes and conditional blocks line by line
+//!
+
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+
+use super::BUILTIN_MODULES;
+
+#[derive(Debug, PartialEq)]
+pub enum PreprocessorError {
+    UnknownModule { line: usize, name: String },
+    UnknownDirective { line: usize, directive: String },
+    MissingArgument { line: usize, directive: String },
+    UnexpectedElse { line: usize },
+    UnexpectedEndif { line: usize },
+    MissingEndif,
+}
+
+impl std::fmt::Display for PreprocessorError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PreprocessorError::UnknownModule { line, name } => {
+                write!(f, "line {line}: unknown module \"{name}\"")
+            }
+            PreprocessorError::UnknownDirective { line, directive } => {
+                write!(f, "line {line}: unknown directive #{directive}")
+            }
+            PreprocessorError::MissingArgument { line, directive } => {
+                write!(f, "line {line}: #{directive} needs an argument")
+            }
+            PreprocessorError::UnexpectedElse { line } => {
+                write!(f, "line {line}: #else without #ifdef")
+            }
+            PreprocessorError::UnexpectedEndif { line } => {
+                write!(f, "line {line}: #endif without #ifdef")
+            }
+            PreprocessorError::MissingEndif => write!(f, "#ifdef without #endif"),
+        }
+    }
+}
+
+impl std::error::Error for PreprocessorError {}
+
+/// State of an `#ifdef` block
+struct Condition {
+    /// True if the enclosing blocks are active
+    parent_active: bool,
+    /// True if the current branch is active
+    active: bool,
+    has_else: bool,
+}
+
+/// State while processing one shader, shared by all included modules
+#[derive(Default)]
+struct Context {
+    defines: HashMap<String, String>,
+    included: HashSet<String>,
+    /// Module files that were read from the module directory
+    files: Vec<PathBuf>,
+    output: String,
+}
+
+#[derive(Clone, Debug)]
+pub struct Preprocessor {
+    modules: HashMap<String, String>,
+    defines: HashMap<String, String>,
+    module_dir: Option<PathBuf>,
+}
+
+impl Default for Preprocessor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Preprocessor {
+    /// A preprocessor with the built-in modules
+    pub fn new() -> Self {
+        let modules = BUILTIN_MODULES
+            .iter()
+            .map(|(name, code)| (name.to_string(), code.to_string()))
+            .collect();
+
+        Self {
+            modules,
+            defines: HashMap::new(),
+            module_dir: None,
+        }
+    }
+
+    /// Adds a module that can be included by its name, replaces a built-in one with the same name
+    pub fn with_module(mut self, name: &str, code: &str) -> Self {
+        self.modules.insert(name.to_string(), code.to_string());
+        self
+    }
+
+    /// Defines a name before processing, like `#define NAME value`
+    ///
+    /// An empty value only defines the name for `#ifdef`, e.g. to select a shader variant.
+    pub fn with_define(mut self, name: &str, value: &str) -> Self {
+        self.defines.insert(name.to_string(), value.to_string());
+        self
+    }
+
+    /// Reads `#include "name"` from `name.wgsl` in the directory if the file exists
+    ///
+    /// The files take precedence over the added and built-in modules, e.g. to edit the
+    /// built-in modules in `src/wgsl_preprocessor/modules` while the shaders are hot reloaded.
+    pub fn with_module_dir(mut self, module_dir: impl Into<PathBuf>) -> Self {
+        self.module_dir = Some(module_dir.into());
+        self
+    }
+
+    pub fn process(&self, shader_code: &str) -> Result<String, PreprocessorError> {
+        self.process_with_files(shader_code)
+            .map(|(processed, _files)| processed)
+    }
+
+    /// Like `process`, also returns the module files that were included from the module directory
+    pub fn process_with_files(
+        &self,
+        shader_code: &str,
+    ) -> Result<(String, Vec<PathBuf>), PreprocessorError> {
+        let mut context = Context {
+            defines: self.defines.clone(),
+            ..Default::default()
+        };
+        self.process_code(shader_code, &mut context)?;
+        Ok((context.output, context.files))
+    }
+
+    fn module_file(&self, module: &str) -> Option<(PathBuf, String)> {
+        let path = self.module_dir.as_deref()?.join(format!("{module}.wgsl"));
+        let code = std::fs::read_to_string(&path).ok()?;
+        Some((path, code))
+    }
+
+    fn process_code(&self, code: &str, context: &mut Context) -> Result<(), PreprocessorError> {
+        let mut conditions: Vec<Condition> = Vec::new();
+
+        for (index, line) in code.lines().enumerate() {
+            let line_nr = index + 1;
+            let active = conditions.last().is_none_or(|condition| condition.active);
+
+            let Some(directive) = line.trim_start().strip_prefix('#') else {
+                if active {
+                    context.output += &substitute_defines(line, &context.defines);
+                    context.output.push('\n');
+                }
+                continue;
+            };
+
+            let (name, argument) = match directive.trim().split_once(char::is_whitespace) {
+                Some((name, argument)) => (name, argument.trim()),
+                None => (directive.trim(), ""),
+            };
+            let first_argument = || {
+                argument.split_whitespace().next().ok_or_else(|| {
+                    PreprocessorError::MissingArgument {
+                        line: line_nr,
+                        directive: name.to_string(),
+                    }
+                })
+            };
+
+            match name {
+                "ifdef" | "ifndef" => {
+                    let is_defined = context.defines.contains_key(first_argument()?);
+                    conditions.push(Condition {
+                        parent_active: active,
+                        active: active && (is_defined == (name == "ifdef")),
+                        has_else: false,
+                    });
+                }
+                "else" => {
+                    let condition = conditions
+                        .last_mut()
+                        .filter(|condition| !condition.has_else)
+                        .ok_or(PreprocessorError::UnexpectedElse { line: line_nr })?;
+                    condition.active = condition.parent_active && !condition.active;
+                    condition.has_else = true;
+                }
+                "endif" => {
+                    conditions
+                        .pop()
+                        .ok_or(PreprocessorError::UnexpectedEndif { line: line_nr })?;
+                }
+                _ if !active => {}
+                "include" => {
+                    let module = argument.trim_matches(|c| c == '"' || c == '<' || c == '>');
+                    if module.is_empty() {
+                        return Err(PreprocessorError::MissingArgument {
+                            line: line_nr,
+                            directive: name.to_string(),
+                        });
+                    }
+                    // Each module once, this also stops cyclic includes
+                    if !context.included.insert(module.to_string()) {
+                        continue;
+                    }
+                    let module_code: Cow<str> = match self.module_file(module) {
+                        Some((path, code)) => {
+                            context.files.push(path);
+                            Cow::Owned(code)
+                        }
+                        None => Cow::Borrowed(self.modules.get(module).ok_or_else(|| {
+                            PreprocessorError::UnknownModule {
+                                line: line_nr,
+                                name: module.to_string(),
+                            }
+                        })?),
+                    };
+                    self.process_code(&module_code, context)?;
+                }
+                "define" => {
+                    let define = first_argument()?;
+                    let value = argument[define.len()..].trim();
+                    context
+                        .defines
+                        .insert(define.to_string(), value.to_string());
+                }
+                "undef" => {
+                    context.defines.remove(first_argument()?);
+                }
+                _ => {
+                    return Err(PreprocessorError::UnknownDirective {
+                        line: line_nr,
+                        directive: name.to_string(),
+                    })
+                }
+            }
+        }
+
+        if conditions.is_empty() {
+            Ok(())
+        } else {
+            Err(PreprocessorError::MissingEndif)
+        }
+    }
+}
+
+/// Replaces identifiers that are defined with a value
+fn substitute_defines(line: &str, defines: &HashMap<String, String>) -> String {
+    if defines.values().all(|value| value.is_empty()) {
+        return line.to_string();
+    }
+
+    let mut result = String::with_capacity(line.len());
+    let mut rest = line;
+    while let Some(start) = rest.find(|c: char| c.is_alphabetic() || c == '_') {
+        result += &rest[..start];
+        rest = &rest[start..];
+        let end = rest
+            .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+            .unwrap_or(rest.len());
+        let identifier = &rest[..end];
+        match defines.get(identifier) {
+            Some(value) if !value.is_empty() => result += value,
+            _ => result += identifier,
+        }
+        rest = &rest[end..];
+    }
+    result += rest;
+    result
+}
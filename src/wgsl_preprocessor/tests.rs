@@ -0,0 +1,62 @@
+//! Unit tests
+
+use super::*;
+
+#[test]
+fn includes_modules_once() {
+    let preprocessor = Preprocessor::new()
+        .with_module("a", "#include \"camera\"\nfn a() {}")
+        .with_module("b", "#include \"a\"\n#include \"b\"\nfn b() {}");
+
+    let code = preprocessor
+        .process("#include \"a\"\n#include <b>\nfn main() {}")
+        .unwrap();
+
+    assert_eq!(code.matches("struct CameraUniform").count(), 1);
+    assert_eq!(code.matches("fn a()").count(), 1);
+    assert!(code.ends_with("fn a() {}\nfn b() {}\nfn main() {}\n"));
+}
+
+#[test]
+fn selects_variants() {
+    let code = "#define COUNT 4\n\
+        #ifdef LIT\n\
+        lit(COUNT);\n\
+        #else\n\
+        #ifndef INSTANCED\n\
+        unlit(COUNT_2);\n\
+        #endif\n\
+        #endif";
+
+    let unlit = Preprocessor::new().process(code).unwrap();
+    assert_eq!(unlit, "unlit(COUNT_2);\n");
+
+    let lit = Preprocessor::new()
+        .with_define("LIT", "")
+        .process(code)
+        .unwrap();
+    assert_eq!(lit, "lit(4);\n");
+}
+
+#[test]
+fn reports_errors() {
+    let preprocessor = Preprocessor::new();
+
+    assert_eq!(
+        preprocessor.process("\n#include \"missing\""),
+        Err(PreprocessorError::UnknownModule {
+            line: 2,
+            name: String::from("missing")
+        })
+    );
+    assert_eq!(
+        preprocessor.process("#ifdef A"),
+        Err(PreprocessorError::MissingEndif)
+    );
+    assert_eq!(
+        preprocessor.process("#endif"),
+        Err(PreprocessorError::UnexpectedEndif { line: 1 })
+    );
+    // invalid code is passed on, so wgpu reports the errors
+    assert_eq!(preprocess("#else"), "#else");
+}